@@ -2,17 +2,149 @@ use sdl2::rect::*;
 use sdl2::pixels::*;
 use sdl2::render::*;
 
-pub fn draw_filled_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, color: Color)
+use crate::LayoutRect;
+use crate::RasterTarget;
+use crate::color::{Color as GuiColor, ColorFormatName, RgbF, RgbaF, Srgb};
+
+/*  SDL blends translucent colors itself, on gamma-compressed 8-bit values, so nothing drawn through
+ *  this module- SdlRaster included- gets the color module's linear-light compositing. Edges and
+ *  translucent fills come out slightly darker than the exact result. Draw on a SoftwareCanvas
+ *  when the blending has to be exact.
+ */
+
+//SDL expects gamma-compressed 8-bit channels with straight alpha
+pub fn sdl_color(color: GuiColor) -> Color {
+    match GuiColor::convert(color, ColorFormatName::RGBAf) {
+        GuiColor::RGBAf(rgbaf) => sdl_color_with_alpha(rgbaf, rgbaf.a),
+        _ => unreachable!("Color::convert always returns the requested format")
+    }
+}
+
+fn sdl_color_with_alpha(color: RgbaF, alpha: f32) -> Color {
+    let srgb: Srgb = RgbF::new(color.r, color.g, color.b).into();
+    let a = (alpha.clamp(0.0f32, 1.0f32) * (u8::MAX as f32)).round() as u8;
+    Color::RGBA(srgb.r, srgb.g, srgb.b, a)
+}
+
+/// Draws the anti-aliased shapes from `raster` on an SDL canvas. Each shape's spans and edge pixels are
+/// batched by color, so the draw color is set once per color rather than once per pixel. The canvas's
+/// draw color and blend mode are put back after every shape. Blending is SDL's, in gamma space.
+#[allow(dead_code)]
+pub struct SdlRaster<'a, T: RenderTarget> {
+    canvas: &'a mut Canvas<T>,
+    spans: Vec<(Color, Rect)>,
+    points: Vec<(Color, Point)>
+}
+
+#[allow(dead_code)]
+impl<'a, T: RenderTarget> SdlRaster<'a, T> {
+    pub fn new(canvas: &'a mut Canvas<T>) -> SdlRaster<'a, T> {
+        SdlRaster {
+            canvas,
+            spans: Vec::new(),
+            points: Vec::new()
+        }
+    }
+}
+
+impl<'a, T: RenderTarget> RasterTarget for SdlRaster<'a, T> {
+    fn fill_span(&mut self, x_start: i32, x_end: i32, y: i32, color: RgbaF) {
+        if x_end <= x_start {
+            return;
+        }
+
+        self.spans.push((sdl_color_with_alpha(color, color.a), Rect::new(x_start, y, (x_end - x_start) as u32, 1)));
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: RgbaF, coverage: f32) {
+        let color = sdl_color_with_alpha(color, color.a * coverage);
+        if color.a > 0 {
+            self.points.push((color, Point::new(x, y)));
+        }
+    }
+
+    fn end_primitive(&mut self) {
+        if self.spans.is_empty() && self.points.is_empty() {
+            return;
+        }
+
+        let original_color = self.canvas.draw_color();
+        let original_blend = self.canvas.blend_mode();
+        //Blending an opaque color is the same as copying it, so one blend mode does for the whole shape
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        let color_key = |color: &Color| (color.r, color.g, color.b, color.a);
+
+        self.spans.sort_by_key(|(color, _)| color_key(color));
+        for batch in self.spans.chunk_by(|a, b| a.0 == b.0) {
+            let rects: Vec<Rect> = batch.iter().map(|(_, rect)| *rect).collect();
+            self.canvas.set_draw_color(batch[0].0);
+            self.canvas.fill_rects(&rects).unwrap();
+        }
+
+        self.points.sort_by_key(|(color, _)| color_key(color));
+        for batch in self.points.chunk_by(|a, b| a.0 == b.0) {
+            let points: Vec<Point> = batch.iter().map(|(_, point)| *point).collect();
+            self.canvas.set_draw_color(batch[0].0);
+            self.canvas.draw_points(&points[..]).unwrap();
+        }
+
+        self.spans.clear();
+        self.points.clear();
+        self.canvas.set_draw_color(original_color);
+        self.canvas.set_blend_mode(original_blend);
+    }
+}
+
+//Anything drawn outside of a raster function still gets onto the canvas
+impl<'a, T: RenderTarget> Drop for SdlRaster<'a, T> {
+    fn drop(&mut self) {
+        self.end_primitive();
+    }
+}
+
+impl From<LayoutRect> for Rect {
+    fn from(rect: LayoutRect) -> Rect {
+        Rect::new(rect.x, rect.y, rect.w, rect.h)
+    }
+}
+
+//Calls draw once per damaged rectangle with the canvas clipped to it, so only damaged pixels get touched
+pub fn redraw_damaged<T: RenderTarget, F>(canvas: &mut Canvas<T>, damage: &[LayoutRect], mut draw: F)
+    where F: FnMut(&mut Canvas<T>, Rect)
 {
-    let original_color = canvas.draw_color();
+    let original_clip = canvas.clip_rect();
+    for rect in damage {
+        let rect: Rect = (*rect).into();
+        canvas.set_clip_rect(rect);
+        draw(canvas, rect);
+    }
+    canvas.set_clip_rect(original_clip);
+}
+
+//Opaque colors are copied, translucent ones blended by SDL in gamma space
+fn set_draw_color_blended<T: RenderTarget>(canvas: &mut Canvas<T>, color: Color) {
+    canvas.set_blend_mode(if color.a < u8::MAX { BlendMode::Blend } else { BlendMode::None });
     canvas.set_draw_color(color);
+}
+
+/// Fills rect with color. A translucent color is blended by SDL in gamma space, not in linear light.
+pub fn draw_filled_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, color: GuiColor)
+{
+    let original_color = canvas.draw_color();
+    let original_blend = canvas.blend_mode();
+    set_draw_color_blended(canvas, sdl_color(color));
     canvas.fill_rect(rect).unwrap();
     canvas.set_draw_color(original_color);
+    canvas.set_blend_mode(original_blend);
 }
 
-pub fn draw_bordered_filled_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, border: u32, color: Color, border_color: Color) 
+/// Fills rect and draws a border around it. Translucent colors are blended by SDL in gamma space.
+//The border is centered on the edge of rect. With odd widths the extra pixel goes inside.
+pub fn draw_bordered_filled_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect: Rect, border: u32, color: GuiColor, border_color: GuiColor) 
 {
     let original_color = canvas.draw_color();
+    let original_blend = canvas.blend_mode();
+    let inner_offset = (border - border / 2) as i32;
     let border_rect = Rect::new(rect.x() - (border as i32) / 2,
                                 rect.y() - (border as i32) / 2,
                                 rect.width() + border,
@@ -21,33 +153,14 @@ pub fn draw_bordered_filled_rect<T: RenderTarget>(canvas: &mut Canvas<T>, rect:
     let adjusted_width = if rect.width() > border { rect.width() - border } else { 1 };
     let adjusted_height = if rect.height() > border { rect.height() - border } else { 1 };
 
-	let adjusted_rect = Rect::new(rect.x() + (border as i32) / 2,
-								  rect.y() + (border as i32) / 2,
+	let adjusted_rect = Rect::new(rect.x() + inner_offset,
+								  rect.y() + inner_offset,
 								  adjusted_width,
 								  adjusted_height);
-    canvas.set_draw_color(border_color);
+    set_draw_color_blended(canvas, sdl_color(border_color));
     canvas.fill_rect(border_rect).unwrap();
-    canvas.set_draw_color(color);
+    set_draw_color_blended(canvas, sdl_color(color));
     canvas.fill_rect(adjusted_rect).unwrap();
     canvas.set_draw_color(original_color);
+    canvas.set_blend_mode(original_blend);
 }
-
-pub fn blend_colors(base: Color, addition: Color, weight: f32) -> Color
-{
-	let base_corrected = [(base.r as f32 / 255.0f32).powf(2.2f32),
-						  (base.g as f32 / 255.0f32).powf(2.2f32),
-						  (base.b as f32 / 255.0f32).powf(2.2f32)];
-	let addition_corrected = [(addition.r as f32 / 255.0f32).powf(2.2f32),
-							  (addition.g as f32 / 255.0f32).powf(2.2f32),
-							  (addition.b as f32 / 255.0f32).powf(2.2f32)];
-							  
-	let mut final_color = [0.0f32, 0.0f32, 0.0f32];
-	let mut as_rgb24 = [0u8, 0u8, 0u8];
-	
-	for i in 0..final_color.len() {
-		final_color[i] = ((base_corrected[i] * (1.0f32 - weight) + addition_corrected[i] * weight)).powf(1.0f32/2.2f32);
-		as_rgb24[i] = (final_color[i] * 255.0f32) as u8;
-	}
-	
-	Color::RGBA(as_rgb24[0], as_rgb24[1], as_rgb24[2], 255)
-}
\ No newline at end of file
@@ -0,0 +1,3 @@
+pub use self::rendering::*;
+
+pub mod rendering;
@@ -0,0 +1,179 @@
+use crate::color::*;
+use crate::RasterTarget;
+
+/// A CPU-side framebuffer. Pixels are linear RGBA with straight alpha, and blending happens in linear light.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoftwareCanvas {
+    image: ImageBuffer<RgbaF>
+}
+
+impl SoftwareCanvas {
+    pub fn new(width: u32, height: u32) -> SoftwareCanvas {
+        SoftwareCanvas {
+            image: ImageBuffer::new(width, height)
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    pub fn image(&self) -> &ImageBuffer<RgbaF> {
+        &self.image
+    }
+
+    pub fn pixels(&self) -> &[RgbaF] {
+        self.image.pixels()
+    }
+
+    pub fn pixel(&self, x: i32, y: i32) -> Option<RgbaF> {
+        if x >= 0 && y >= 0 {
+            self.image.get_pixel(x as u32, y as u32)
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        let color = match Color::convert(color, ColorFormatName::RGBAf) {
+            Color::RGBAf(rgbaf) => rgbaf,
+            _ => unreachable!("Color::convert always returns the requested format")
+        };
+
+        self.image.fill(color);
+    }
+
+    fn pixel_mut(&mut self, x: i32, y: i32) -> Option<&mut RgbaF> {
+        if x >= 0 && y >= 0 {
+            self.image.pixel_mut(x as u32, y as u32)
+        } else {
+            None
+        }
+    }
+}
+
+fn blend_over(dst: RgbaF, src: RgbaF, src_alpha: f32) -> RgbaF {
+    RgbaF::new(src.r, src.g, src.b, src_alpha).composite(dst, PorterDuff::SrcOver)
+}
+
+impl RasterTarget for SoftwareCanvas {
+    fn fill_span(&mut self, x_start: i32, x_end: i32, y: i32, color: RgbaF) {
+        if y < 0 || y as u32 >= self.height() {
+            return;
+        }
+
+        let x_start = x_start.max(0);
+        let x_end = x_end.min(self.width() as i32);
+        for x in x_start..x_end {
+            let pixel = self.pixel_mut(x, y).unwrap();
+            *pixel = if color.a >= 1.0f32 {
+                color
+            } else {
+                blend_over(*pixel, color, color.a)
+            };
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: RgbaF, coverage: f32) {
+        if let Some(pixel) = self.pixel_mut(x, y) {
+            *pixel = blend_over(*pixel, color, color.a * coverage.clamp(0.0f32, 1.0f32));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LayoutRect, CornerRadii, BorderPlacement, Shadow, fill_rounded_rect, stroke_rounded_rect, draw_shadow};
+
+    const WHITE: Color = Color::SRGB(Srgb { r: 255, g: 255, b: 255 });
+    const BLACK: Color = Color::SRGB(Srgb { r: 0, g: 0, b: 0 });
+
+    fn blank(width: u32, height: u32) -> SoftwareCanvas {
+        let mut canvas = SoftwareCanvas::new(width, height);
+        canvas.clear(Color::RGBAf(RgbaF::new(0.0f32, 0.0f32, 0.0f32, 0.0f32)));
+        canvas
+    }
+
+    fn alpha(canvas: &SoftwareCanvas, x: i32, y: i32) -> f32 {
+        canvas.pixel(x, y).unwrap().a
+    }
+
+    #[test]
+    fn rounded_rects_cover_the_inside_and_fade_at_the_corners() {
+        let mut canvas = blank(20, 20);
+        fill_rounded_rect(&mut canvas, LayoutRect::new(2, 2, 16, 16), CornerRadii::uniform(6.0f32), WHITE);
+
+        //Interior and straight edges are covered, the corner pixel is outside the arc
+        assert_eq!(canvas.pixel(10, 10), Some(RgbaF::new(1.0f32, 1.0f32, 1.0f32, 1.0f32)));
+        assert_eq!(alpha(&canvas, 2, 10), 1.0f32);
+        assert_eq!(alpha(&canvas, 2, 2), 0.0f32);
+        assert_eq!(alpha(&canvas, 1, 10), 0.0f32);
+
+        //The arc passes through pixel (3, 3) about a third of a pixel from its center
+        let arc = alpha(&canvas, 3, 3);
+        assert!(arc > 0.0f32 && arc < 1.0f32, "{}", arc);
+        assert!((arc - 0.14f32).abs() < 0.01f32, "{}", arc);
+    }
+
+    #[test]
+    fn radii_are_clamped_to_half_the_size() {
+        let rect = LayoutRect::new(0, 0, 10, 4);
+
+        let mut huge = blank(10, 4);
+        fill_rounded_rect(&mut huge, rect, CornerRadii::uniform(100.0f32), WHITE);
+        let mut half = blank(10, 4);
+        fill_rounded_rect(&mut half, rect, CornerRadii::uniform(2.0f32), WHITE);
+
+        assert_eq!(huge, half);
+        assert_eq!(alpha(&huge, 5, 0), 1.0f32);
+        assert!(alpha(&huge, 0, 0) < 0.5f32);
+    }
+
+    #[test]
+    fn inset_strokes_stay_inside_and_outset_ones_surround() {
+        let rect = LayoutRect::new(5, 5, 10, 10);
+        let row = |placement: BorderPlacement| {
+            let mut canvas = blank(20, 20);
+            stroke_rounded_rect(&mut canvas, rect, CornerRadii::default(), 2.0f32, placement, WHITE);
+            (0..20).map(|x| alpha(&canvas, x, 10)).collect::<Vec<f32>>()
+        };
+
+        let covered = |row: &[f32]| (0..20).filter(|&x| row[x] > 0.0f32).collect::<Vec<usize>>();
+        let inset = row(BorderPlacement::Inset);
+        let outset = row(BorderPlacement::Outset);
+
+        assert_eq!(covered(&inset), vec![5, 6, 13, 14]);
+        assert_eq!(covered(&outset), vec![3, 4, 15, 16]);
+        assert!(inset[5] == 1.0f32 && outset[3] == 1.0f32);
+    }
+
+    #[test]
+    fn shadows_fall_off_further_with_more_blur() {
+        let rect = LayoutRect::new(15, 15, 10, 10);
+        let row = |blur: f32| {
+            let mut canvas = blank(40, 40);
+            let shadow = Shadow { offset: (0.0f32, 0.0f32), blur, spread: 0.0f32, color: BLACK };
+            draw_shadow(&mut canvas, rect, CornerRadii::default(), shadow);
+            (0..=20).map(|x| alpha(&canvas, x, 20)).collect::<Vec<f32>>()
+        };
+
+        let rows: Vec<Vec<f32>> = [0.0f32, 2.0f32, 4.0f32, 8.0f32].iter().map(|&blur| row(blur)).collect();
+        for row in &rows {
+            //Darkest in the middle, and never darker further out
+            assert!(row[20] > 0.8f32, "{:?}", row);
+            assert!(row.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", row);
+        }
+
+        //Outside the rect more blur reaches further, inside its edge more blur is lighter
+        for pair in rows.windows(2) {
+            assert!(pair[1][12] > pair[0][12], "{} then {}", pair[0][12], pair[1][12]);
+            assert!(pair[1][15] < pair[0][15], "{} then {}", pair[0][15], pair[1][15]);
+        }
+        assert_eq!(rows[0][14], 0.0f32);
+    }
+}
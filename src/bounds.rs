@@ -1,6 +1,7 @@
 use crate::ElementId;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HorizAlign {
     LeftOffset(i32),
     //LeftOffsetProportional(f32),
@@ -12,6 +13,7 @@ pub enum HorizAlign {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VertAlign {
     TopOffset(i32),
     //TopOffsetProportional(f32),
@@ -24,6 +26,7 @@ pub enum VertAlign {
 
 /// The position of the center of the bounds of a GUI element
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Position {
     Absolute((i32, i32)),
     Relative((i32, i32)),
@@ -32,6 +35,7 @@ pub enum Position {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Size {
     Absolute((u32, u32)), //direct value of width and height in pixels
     Relative((i32, i32)), //how many fewer or more pixels than the parent element
@@ -45,6 +49,7 @@ pub enum Size {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
     position: Position,
     size: Size
@@ -66,3 +71,124 @@ impl Bounds {
         self.size
     }
 }
+
+/// A resolved, absolute rectangle in window coordinates. This is what layout produces from `Bounds`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct LayoutRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32
+}
+
+impl LayoutRect {
+    pub fn new(x: i32, y: i32, w: u32, h: u32) -> LayoutRect {
+        LayoutRect {
+            x,
+            y,
+            w,
+            h
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.w as i32
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.h as i32
+    }
+
+    pub fn intersects(&self, other: &LayoutRect) -> bool {
+        !self.is_empty() && !other.is_empty() &&
+        self.x < other.right() && other.x < self.right() &&
+        self.y < other.bottom() && other.y < self.bottom()
+    }
+
+    /// Smallest rectangle containing both. Empty rectangles don't contribute.
+    pub fn union(&self, other: &LayoutRect) -> LayoutRect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+
+        LayoutRect::new(x, y, (right - x) as u32, (bottom - y) as u32)
+    }
+}
+
+impl Bounds {
+    /// Resolves these bounds against the rectangle of the parent element
+    pub fn resolve(&self, parent: LayoutRect) -> LayoutRect {
+        let (w, h) = match self.size {
+            Size::Absolute((w, h)) => (w, h),
+            Size::Relative((dw, dh)) => {
+                ((parent.w as i32 + dw).max(0) as u32, (parent.h as i32 + dh).max(0) as u32)
+            },
+            Size::Fill => (parent.w, parent.h)
+        };
+
+        let parent_center = (parent.x + (parent.w / 2) as i32, parent.y + (parent.h / 2) as i32);
+
+        //Position is the center of the element, so shift back by half the size to get the top-left corner
+        let (x, y) = match self.position {
+            Position::Absolute((cx, cy)) => (cx - (w / 2) as i32, cy - (h / 2) as i32),
+            Position::Relative((dx, dy)) => {
+                (parent_center.0 + dx - (w / 2) as i32, parent_center.1 + dy - (h / 2) as i32)
+            },
+            Position::Align(horiz, vert) => {
+                let x = match horiz {
+                    HorizAlign::LeftOffset(offset) => parent.x + offset,
+                    HorizAlign::Center => parent_center.0 - (w / 2) as i32,
+                    HorizAlign::RightOffset(offset) => parent.right() - offset - w as i32
+                };
+                let y = match vert {
+                    VertAlign::TopOffset(offset) => parent.y + offset,
+                    VertAlign::Center => parent_center.1 - (h / 2) as i32,
+                    VertAlign::BottomOffset(offset) => parent.bottom() - offset - h as i32
+                };
+                (x, y)
+            }
+        };
+
+        LayoutRect::new(x, y, w, h)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_and_element_ids_round_trip_through_json_and_bincode() {
+        let bounds = [
+            Bounds::new(Position::Absolute((10, -20)), Size::Absolute((300, 200))),
+            Bounds::new(Position::Relative((-5, 5)), Size::Relative((-10, 0))),
+            Bounds::new(Position::Align(HorizAlign::RightOffset(8), VertAlign::Center), Size::Fill),
+        ];
+
+        //Bounds has no PartialEq, so compare what Debug shows
+        for b in bounds.iter() {
+            let json: Bounds = serde_json::from_str(&serde_json::to_string(b).unwrap()).unwrap();
+            assert_eq!(format!("{:?}", json), format!("{:?}", b));
+
+            let binary: Bounds = bincode::deserialize(&bincode::serialize(b).unwrap()).unwrap();
+            assert_eq!(format!("{:?}", binary), format!("{:?}", b));
+        }
+
+        let id = ElementId::new(7, 3);
+        assert_eq!(serde_json::to_string(&id).unwrap(), r#"{"id":7,"gen":3}"#);
+        assert_eq!(serde_json::from_str::<ElementId>(r#"{"id":7,"gen":3}"#).unwrap(), id);
+        assert_eq!(bincode::deserialize::<ElementId>(&bincode::serialize(&id).unwrap()).unwrap(), id);
+    }
+}
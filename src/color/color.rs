@@ -1,6 +1,7 @@
 pub use super::colorformat::*;
 
 #[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Color {
     Gray8(Gray8),
@@ -17,7 +18,16 @@ pub enum Color {
     HSV(Hsv),
     HSL(Hsl),
     CIEXYZ(CieXyz),
-    CIELab(CieLab)
+    CIELab(CieLab),
+    CIELch(CieLch),
+    CIELuv(CieLuv),
+    CIELchUv(CieLchUv),
+    CIExyY(CieXyY),
+    Oklab(Oklab),
+    Oklch(Oklch),
+    CMYK(Cmyk),
+    YCbCr(YCbCr),
+    HWB(Hwb)
 }
 
 impl Default for Color {
@@ -75,6 +85,33 @@ impl Color {
             },
             Color::CIELab(_lab) => {
                 CieLab::format_name()
+            },
+            Color::CIELch(_lch) => {
+                CieLch::format_name()
+            },
+            Color::CIELuv(_luv) => {
+                CieLuv::format_name()
+            },
+            Color::CIELchUv(_lch_uv) => {
+                CieLchUv::format_name()
+            },
+            Color::CIExyY(_xyy) => {
+                CieXyY::format_name()
+            },
+            Color::Oklab(_oklab) => {
+                Oklab::format_name()
+            },
+            Color::Oklch(_oklch) => {
+                Oklch::format_name()
+            },
+            Color::CMYK(_cmyk) => {
+                Cmyk::format_name()
+            },
+            Color::YCbCr(_ycbcr) => {
+                YCbCr::format_name()
+            },
+            Color::HWB(_hwb) => {
+                Hwb::format_name()
             }
         }
     }
@@ -125,6 +162,33 @@ impl Color {
             },
             Color::CIELab(_lab) => {
                 CieLab::bytes_per_pixel()
+            },
+            Color::CIELch(_lch) => {
+                CieLch::bytes_per_pixel()
+            },
+            Color::CIELuv(_luv) => {
+                CieLuv::bytes_per_pixel()
+            },
+            Color::CIELchUv(_lch_uv) => {
+                CieLchUv::bytes_per_pixel()
+            },
+            Color::CIExyY(_xyy) => {
+                CieXyY::bytes_per_pixel()
+            },
+            Color::Oklab(_oklab) => {
+                Oklab::bytes_per_pixel()
+            },
+            Color::Oklch(_oklch) => {
+                Oklch::bytes_per_pixel()
+            },
+            Color::CMYK(_cmyk) => {
+                Cmyk::bytes_per_pixel()
+            },
+            Color::YCbCr(_ycbcr) => {
+                YCbCr::bytes_per_pixel()
+            },
+            Color::HWB(_hwb) => {
+                Hwb::bytes_per_pixel()
             }
         }
     }
@@ -175,6 +239,33 @@ impl Color {
             },
             Color::CIELab(lab) => {
                 lab.as_bytes()
+            },
+            Color::CIELch(lch) => {
+                lch.as_bytes()
+            },
+            Color::CIELuv(luv) => {
+                luv.as_bytes()
+            },
+            Color::CIELchUv(lch_uv) => {
+                lch_uv.as_bytes()
+            },
+            Color::CIExyY(xyy) => {
+                xyy.as_bytes()
+            },
+            Color::Oklab(oklab) => {
+                oklab.as_bytes()
+            },
+            Color::Oklch(oklch) => {
+                oklch.as_bytes()
+            },
+            Color::CMYK(cmyk) => {
+                cmyk.as_bytes()
+            },
+            Color::YCbCr(ycbcr) => {
+                ycbcr.as_bytes()
+            },
+            Color::HWB(hwb) => {
+                hwb.as_bytes()
             }
         }
     }
@@ -226,6 +317,33 @@ impl Color {
             Color::CIELab(lab) => {
                 lab.to_raw_parts()
             },
+            Color::CIELch(lch) => {
+                lch.to_raw_parts()
+            },
+            Color::CIELuv(luv) => {
+                luv.to_raw_parts()
+            },
+            Color::CIELchUv(lch_uv) => {
+                lch_uv.to_raw_parts()
+            },
+            Color::CIExyY(xyy) => {
+                xyy.to_raw_parts()
+            },
+            Color::Oklab(oklab) => {
+                oklab.to_raw_parts()
+            },
+            Color::Oklch(oklch) => {
+                oklch.to_raw_parts()
+            },
+            Color::CMYK(cmyk) => {
+                cmyk.to_raw_parts()
+            },
+            Color::YCbCr(ycbcr) => {
+                ycbcr.to_raw_parts()
+            },
+            Color::HWB(hwb) => {
+                hwb.to_raw_parts()
+            },
         }
     }
 
@@ -351,743 +469,176 @@ impl Color {
                     Err(lab.err().unwrap())
                 }
             },
+            ColorFormatName::CIELch => {
+                let lch = CieLch::from_bytes(&data);
+                if lch.is_ok() {
+                    Ok(Color::CIELch(lch.unwrap()))
+                } else {
+                    Err(lch.err().unwrap())
+                }
+            },
+            ColorFormatName::CIELuv => {
+                let luv = CieLuv::from_bytes(&data);
+                if luv.is_ok() {
+                    Ok(Color::CIELuv(luv.unwrap()))
+                } else {
+                    Err(luv.err().unwrap())
+                }
+            },
+            ColorFormatName::CIELchUv => {
+                let lch_uv = CieLchUv::from_bytes(&data);
+                if lch_uv.is_ok() {
+                    Ok(Color::CIELchUv(lch_uv.unwrap()))
+                } else {
+                    Err(lch_uv.err().unwrap())
+                }
+            },
+            ColorFormatName::CIExyY => {
+                let xyy = CieXyY::from_bytes(&data);
+                if xyy.is_ok() {
+                    Ok(Color::CIExyY(xyy.unwrap()))
+                } else {
+                    Err(xyy.err().unwrap())
+                }
+            },
+            ColorFormatName::Oklab => {
+                let oklab = Oklab::from_bytes(&data);
+                if oklab.is_ok() {
+                    Ok(Color::Oklab(oklab.unwrap()))
+                } else {
+                    Err(oklab.err().unwrap())
+                }
+            },
+            ColorFormatName::Oklch => {
+                let oklch = Oklch::from_bytes(&data);
+                if oklch.is_ok() {
+                    Ok(Color::Oklch(oklch.unwrap()))
+                } else {
+                    Err(oklch.err().unwrap())
+                }
+            },
+            ColorFormatName::CMYK => {
+                let cmyk = Cmyk::from_bytes(&data);
+                if cmyk.is_ok() {
+                    Ok(Color::CMYK(cmyk.unwrap()))
+                } else {
+                    Err(cmyk.err().unwrap())
+                }
+            },
+            ColorFormatName::YCbCr => {
+                let ycbcr = YCbCr::from_bytes(&data);
+                if ycbcr.is_ok() {
+                    Ok(Color::YCbCr(ycbcr.unwrap()))
+                } else {
+                    Err(ycbcr.err().unwrap())
+                }
+            },
+            ColorFormatName::HWB => {
+                let hwb = Hwb::from_bytes(&data);
+                if hwb.is_ok() {
+                    Ok(Color::HWB(hwb.unwrap()))
+                } else {
+                    Err(hwb.err().unwrap())
+                }
+            },
         }
     }
 
-    //This is an iterative function if there is no direct conversion from one color format to another
-    //Each step, if taken, converts to an intermediary format.
-    //For example, HSV -> Lab will do HSV -> RGBf -> XYZ -> Lab
-    pub fn convert(from: Color, to: ColorFormatName) -> Color {
-        let mut conversion = from;
-        loop {
-            //Here be... not dragons, honestly. Just a lot of snakes. Like a *lot*. The cute kind not the dangerous kind.
-            //(A lot of nested match statements that are individually very simple)
-            match conversion {
-                Color::Gray8(g8) => {
-                    match to {
-                        ColorFormatName::Gray8 => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = g8.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = g8.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = g8.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = g8.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::SRGB | ColorFormatName::RGB48 |
-                        ColorFormatName::RGBf | ColorFormatName::SRGBf |
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab =>
-                        {
-                            let rgb: Rgb = g8.into();
-                            conversion = Color::RGB(rgb);
-                            continue;
-                        },
-                        ColorFormatName::RGBA64 | ColorFormatName::RGBAf => {
-                            let rgba: Rgba = g8.into();
-                            conversion = Color::RGBA(rgba);
-                            continue;
-                        },
-                    }
-                },
-                Color::Gray16(g16) => {
-                    match to {
-                        ColorFormatName::Gray16 => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = g16.into();
-                            return Color::Gray8(g8);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = g16.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = g16.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = g16.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = g16.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = g16.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = g16.into();
-                            return Color::RGBAf(rgbaf);
-                        }
-                        ColorFormatName::SRGB => {
-                            let rgb: Rgb = g16.into();
-                            let srgb: Srgb = rgb.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let rgb_f: RgbF = g16.into();
-                            let srgbf: SrgbF = rgb_f.into();
-                            return Color::SRGBf(srgbf);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgbaf: RgbaF = g16.into();
-                            let rgba: Rgba = rgbaf.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab =>
-                        {
-                            let rgbf: RgbF = g16.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::Grayf(gf) => {
-                    match to {
-                        ColorFormatName::Grayf => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = gf.into();
-                            return Color::Gray8(g8);
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = gf.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = gf.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = gf.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = gf.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = gf.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = gf.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = gf.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = gf.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = gf.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab => {
-                            let rgbf: RgbF = gf.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::RGB(rgb) => {
-                    match to {
-                        ColorFormatName::RGB => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = rgb.into();
-                            return Color::Gray8(g8);
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgb.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgb.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = rgb.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = rgb.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = rgb.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = rgb.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = rgb.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = rgb.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = rgb.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = rgb.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = rgb.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = rgb.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::CIELab => {
-                            let xyz: CieXyz = rgb.into();
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                    }
-                },
-                Color::SRGB(srgb) => {
-                    match to {
-                        ColorFormatName::SRGB => {
-                            return conversion;
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = srgb.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = srgb.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = srgb.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = srgb.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::RGBAf => {
-                            let rgbf: RgbF = srgb.into();
-                            let rgbaf: RgbaF = rgbf.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = srgb.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = srgb.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = srgb.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::CIELab => {
-                            let xyz: CieXyz = srgb.into();
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 => {
-                            let gf: GrayF = srgb.into();
-                            conversion = Color::Grayf(gf);
-                            continue;
-                        },
-                        ColorFormatName::RGB48 | ColorFormatName::RGBA | ColorFormatName::RGBA64 => {
-                            let rgb: Rgb = srgb.into();
-                            conversion = Color::RGB(rgb);
-                            continue;
-                        },
-                    }
-                },
-                Color::RGB48(rgb_w) => {
-                    match to {
-                        ColorFormatName::RGB48 => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgb_w.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgb_w.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = rgb_w.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = rgb_w.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = rgb_w.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::Gray8 |
-                        ColorFormatName::SRGB | ColorFormatName::SRGBf |
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab =>
-                        {
-                            let rgbf: RgbF = rgb_w.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                        ColorFormatName::RGBA | ColorFormatName::RGBAf => {
-                            let rgba_w: Rgba64 = rgb_w.into();
-                            conversion = Color::RGBA64(rgba_w);
-                            continue;
-                        },
-                    }
-                },
-                Color::RGBA(rgba) => {
-                    match to {
-                        ColorFormatName::RGBA => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = rgba.into();
-                            return Color::Gray8(g8);
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgba.into();
-                            return Color::Gray16(g16);
-                        }
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgba.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = rgba.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = rgba.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = rgba.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::SRGB | ColorFormatName::RGB48 |
-                        ColorFormatName::RGBf | ColorFormatName::SRGBf =>
-                        {
-                            let rgb: Rgb = rgba.into();
-                            conversion = Color::RGB(rgb);
-                            continue;
-                        }
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab =>
-                        {
-                            let rgbf: RgbF = rgba.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::RGBA64(rgba_w) => {
-                    match to {
-                        ColorFormatName::RGBA64 => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgba_w.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgba_w.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = rgba_w.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = rgba_w.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = rgba_w.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = rgba_w.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::Gray8 |
-                        ColorFormatName::RGB | ColorFormatName::SRGB | ColorFormatName::SRGBf |
-                        ColorFormatName::HSV | ColorFormatName::HSL |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab => {
-                            let rgbf: RgbF = rgba_w.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::RGBf(rgbf) => {
-                    match to {
-                        ColorFormatName::RGBf => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = rgbf.into();
-                            return Color::Gray8(g8);
-                        },
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgbf.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgbf.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = rgbf.into();
-                            return Color::RGB(rgb);
-                        }
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = rgbf.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = rgbf.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = rgbf.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = rgbf.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = rgbf.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBAf => {
-                            let rgbaf: RgbaF = rgbf.into();
-                            return Color::RGBAf(rgbaf);
-                        },
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = rgbf.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = rgbf.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = rgbf.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::CIELab => {
-                            let xyz: CieXyz = rgbf.into();
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                    }
-                },
-                Color::SRGBf(srgbf) => {
-                    match to {
-                        ColorFormatName::SRGBf => {
-                            return conversion;
-                        },
-                        ColorFormatName::Grayf => {
-                            let grayf: GrayF = srgbf.into();
-                            return Color::Grayf(grayf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = srgbf.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = srgbf.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = srgbf.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = srgbf.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = srgbf.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = srgbf.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgb: Rgb = srgbf.into();
-                            let rgba: Rgba = rgb.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::CIELab => {
-                            let xyz: CieXyz = srgbf.into();
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                        ColorFormatName::RGBAf | ColorFormatName::RGB48 | ColorFormatName::RGBA64 => {
-                            let rgbf: RgbF = srgbf.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 => {
-                            let grayf: GrayF = srgbf.into();
-                            conversion = Color::Grayf(grayf);
-                            continue;
-                        },
-                    }
-                }
-                Color::RGBAf(rgbaf) => {
-                    match to {
-                        ColorFormatName::RGBAf => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 => {
-                            let g8: Gray8 = rgbaf.into();
-                            return Color::Gray8(g8);
-                        }
-                        ColorFormatName::Gray16 => {
-                            let g16: Gray16 = rgbaf.into();
-                            return Color::Gray16(g16);
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = rgbaf.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = rgbaf.into();
-                            return Color::RGB(rgb);
-                        }
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = rgbaf.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGB48 => {
-                            let rgb_w: Rgb48 = rgbaf.into();
-                            return Color::RGB48(rgb_w);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgba: Rgba = rgbaf.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::RGBA64 => {
-                            let rgba_w: Rgba64 = rgbaf.into();
-                            return Color::RGBA64(rgba_w);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = rgbaf.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = rgbaf.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = rgbaf.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = rgbaf.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = rgbaf.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::CIELab => {
-                            let xyz: CieXyz = rgbaf.into();
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                    }
-                },
-                Color::HSV(hsv) => {
-                    match to {
-                        ColorFormatName::HSV => {
-                            return conversion;
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = hsv.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = hsv.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = hsv.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = hsv.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::HSL => {
-                            let hsl: Hsl = hsv.into();
-                            return Color::HSL(hsl);
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 | ColorFormatName::Grayf |
-                        ColorFormatName::RGB48 | ColorFormatName::RGBA64 |
-                        ColorFormatName::RGBA | ColorFormatName::RGBAf |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab => {
-                            let rgbf: RgbF = hsv.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::HSL(hsl) => {
-                    match to {
-                        ColorFormatName::HSL => {
-                            return conversion;
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 | ColorFormatName::Grayf =>
-                        {
+    pub fn to_raw_parts_le(self) -> (ColorFormatName, Vec<u8>) {
+        let (fmt, mut data) = self.to_raw_parts();
+        if cfg!(target_endian = "big") {
+            swap_byte_order(fmt, &mut data);
+        }
+        (fmt, data)
+    }
+
+    pub fn to_raw_parts_be(self) -> (ColorFormatName, Vec<u8>) {
+        let (fmt, mut data) = self.to_raw_parts();
+        if cfg!(target_endian = "little") {
+            swap_byte_order(fmt, &mut data);
+        }
+        (fmt, data)
+    }
 
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = hsl.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = hsl.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = hsl.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = hsl.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::HSV => {
-                            let hsv: Hsv = hsl.into();
-                            return Color::HSV(hsv);
-                        },
-                        ColorFormatName::RGB48 | ColorFormatName::RGBA64 |
-                        ColorFormatName::RGBA | ColorFormatName::RGBAf |
-                        ColorFormatName::CIEXYZ | ColorFormatName::CIELab => {
-                            let rgbf: RgbF = hsl.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::CIEXYZ(xyz) => {
-                    match to {
-                        ColorFormatName::CIEXYZ => {
-                            return conversion;
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = xyz.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::RGB => {
-                            let rgb: Rgb = xyz.into();
-                            return Color::RGB(rgb);
-                        },
-                        ColorFormatName::SRGB => {
-                            let srgb: Srgb = xyz.into();
-                            return Color::SRGB(srgb);
-                        },
-                        ColorFormatName::RGBf => {
-                            let rgbf: RgbF = xyz.into();
-                            return Color::RGBf(rgbf);
-                        },
-                        ColorFormatName::SRGBf => {
-                            let srgbf: SrgbF = xyz.into();
-                            return Color::SRGBf(srgbf);
-                        }
-                        ColorFormatName::CIELab => {
-                            let lab: CieLab = xyz.into();
-                            return Color::CIELab(lab);
-                        },
-                        ColorFormatName::RGBA => {
-                            let rgb: Rgb = xyz.into();
-                            let rgba: Rgba = rgb.into();
-                            return Color::RGBA(rgba);
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 => {
-                            let gf: GrayF = xyz.into();
-                            conversion = Color::Grayf(gf);
-                            continue;
-                        },
-                        ColorFormatName::RGBAf | ColorFormatName::RGB48 |ColorFormatName::RGBA64 |
-                        ColorFormatName::HSV | ColorFormatName::HSL => {
-                            let rgbf: RgbF = xyz.into();
-                            conversion = Color::RGBf(rgbf);
-                            continue;
-                        },
-                    }
-                },
-                Color::CIELab(lab) => {
-                    match to {
-                        ColorFormatName::CIELab => {
-                            return conversion;
-                        },
-                        ColorFormatName::Grayf => {
-                            let gf: GrayF = lab.into();
-                            return Color::Grayf(gf);
-                        },
-                        ColorFormatName::CIEXYZ => {
-                            let xyz: CieXyz = lab.into();
-                            return Color::CIEXYZ(xyz);
-                        },
-                        ColorFormatName::Gray8 | ColorFormatName::Gray16 => {
-                            let gf: GrayF = lab.into();
-                            conversion = Color::Grayf(gf);
-                            continue;
-                        },
-                        ColorFormatName::RGB | ColorFormatName::SRGB | ColorFormatName::RGBA |
-                        ColorFormatName::RGB48 | ColorFormatName::RGBA64 |
-                        ColorFormatName::RGBf | ColorFormatName::SRGBf | ColorFormatName::RGBAf |
-                        ColorFormatName::HSV | ColorFormatName::HSL=>
-                        {
-                            let xyz: CieXyz = lab.into();
-                            conversion = Color::CIEXYZ(xyz);
-                            continue;
-                        },
-                    }
-                },
+    pub fn from_raw_parts_le(fmt: ColorFormatName, mut data: Vec<u8>) -> Result<Color, String> {
+        if cfg!(target_endian = "big") {
+            swap_byte_order(fmt, &mut data);
+        }
+        Color::from_raw_parts(fmt, data)
+    }
+
+    pub fn from_raw_parts_be(fmt: ColorFormatName, mut data: Vec<u8>) -> Result<Color, String> {
+        if cfg!(target_endian = "little") {
+            swap_byte_order(fmt, &mut data);
+        }
+        Color::from_raw_parts(fmt, data)
+    }
+
+    /*  A self-describing encoding, safe to save or send anywhere:
+     *      1 byte      the format's tag (ColorFormatName::tag)
+     *      1 byte      the length of what follows- the CIE formats are longer with a reference white
+     *      n bytes     the color, little-endian
+     */
+    pub fn to_tagged_bytes(self) -> Vec<u8> {
+        let (fmt, data) = self.to_raw_parts_le();
+
+        let mut bytes = Vec::with_capacity(data.len() + 2);
+        bytes.push(fmt.tag());
+        bytes.push(data.len() as u8);
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    /// Reads one tagged color from the start of bytes, returning it and how many bytes it took up
+    pub fn read_tagged_bytes(bytes: &[u8]) -> Result<(Color, usize), String> {
+        if bytes.len() < 2 {
+            return Err(format!("A tagged color needs at least 2 bytes. {} bytes were provided.", bytes.len()));
+        }
+
+        let fmt = match ColorFormatName::from_tag(bytes[0]) {
+            Some(fmt) => fmt,
+            None => {
+                return Err(format!("Unknown color format tag {}", bytes[0]));
             }
+        };
+
+        let len = bytes[1] as usize;
+        if bytes.len() < len + 2 {
+            return Err(format!("Tagged {:?} color says it has {} bytes, only {} are left", fmt, len, bytes.len() - 2));
         }
+
+        let color = Color::from_raw_parts_le(fmt, bytes[2..len + 2].to_vec())?;
+        Ok((color, len + 2))
+    }
+
+    /// Reads a tagged color that takes up all of bytes
+    pub fn from_tagged_bytes(bytes: &[u8]) -> Result<Color, String> {
+        let (color, used) = Color::read_tagged_bytes(bytes)?;
+        if used == bytes.len() {
+            Ok(color)
+        } else {
+            Err(format!("{} bytes left over after a tagged color", bytes.len() - used))
+        }
+    }
+
+    /// A color given as stored RGB in another RGB space, like Display P3. The result is `Color::CIEXYZ`.
+    pub fn from_rgb_space(space: &RgbSpace, rgb: [f32; 3]) -> Color {
+        Color::CIEXYZ(space.rgb_to_xyz(rgb))
     }
 
+    /// The stored RGB for color in another RGB space, unclamped
+    pub fn to_rgb_space(color: Color, space: &RgbSpace) -> [f32; 3] {
+        match Color::convert(color, ColorFormatName::CIEXYZ) {
+            Color::CIEXYZ(xyz) => space.xyz_to_rgb(xyz),
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    /// Estimates the correlated color temperature of a color in kelvin. None for black.
+    pub fn cct(&self, method: CctMethod) -> Option<f32> {
+        match Color::convert(*self, ColorFormatName::CIEXYZ) {
+            Color::CIEXYZ(xyz) => xyz.cct(method),
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
 }
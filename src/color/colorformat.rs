@@ -6,12 +6,25 @@ pub use self::grayscale::*;
 pub use self::rgb::*;
 pub use self::hsv_hsl::*;
 pub use self::cie::*;
+pub use self::oklab::*;
+pub use self::bulk::*;
+pub use self::rgbspace::*;
+pub use self::illuminant::*;
+pub use self::cmyk::*;
+pub use self::ycbcr::*;
 
 pub mod grayscale;
 pub mod rgb;
 pub mod hsv_hsl;
 pub mod cie;
+pub mod oklab;
+pub mod bulk;
+pub mod rgbspace;
+pub mod illuminant;
+pub mod cmyk;
+pub mod ycbcr;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ColorFormatName {
     Gray8, Gray16, Grayf,
@@ -19,7 +32,10 @@ pub enum ColorFormatName {
     RGBA, RGBA64,
     RGBf, SRGBf, RGBAf,
     HSV, HSL,
-    CIEXYZ, CIELab
+    CIEXYZ, CIELab,
+    CIELch, CIELuv, CIELchUv, CIExyY,
+    Oklab, Oklch,
+    CMYK, YCbCr, HWB
 }
 
 pub trait ColorFormat: Sized {
@@ -36,9 +52,106 @@ pub trait ColorFormat: Sized {
     fn as_bytes(&self) -> Vec<u8>;
     fn from_bytes(bytes: &[u8]) -> Result<Self, String>;
 
+    //Fixed byte orders, for anything that leaves the machine- files on disk, the network
+    fn to_le_bytes(self) -> Vec<u8> {
+        let mut bytes = self.to_bytes();
+        if cfg!(target_endian = "big") {
+            swap_byte_order(Self::format_name(), &mut bytes);
+        }
+        bytes
+    }
+
+    fn to_be_bytes(self) -> Vec<u8> {
+        let mut bytes = self.to_bytes();
+        if cfg!(target_endian = "little") {
+            swap_byte_order(Self::format_name(), &mut bytes);
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut bytes = bytes.to_vec();
+        if cfg!(target_endian = "big") {
+            swap_byte_order(Self::format_name(), &mut bytes);
+        }
+        Self::from_bytes(&bytes)
+    }
+
+    fn from_be_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut bytes = bytes.to_vec();
+        if cfg!(target_endian = "little") {
+            swap_byte_order(Self::format_name(), &mut bytes);
+        }
+        Self::from_bytes(&bytes)
+    }
+
     fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>);
 }
 
+#[allow(dead_code)]
+impl ColorFormatName {
+    /// A stable number for each format, for encodings that have to outlive this build.
+    /// New formats get new numbers- existing ones never change.
+    pub fn tag(&self) -> u8 {
+        match self {
+            ColorFormatName::Gray8 => 0,
+            ColorFormatName::Gray16 => 1,
+            ColorFormatName::Grayf => 2,
+            ColorFormatName::RGB => 3,
+            ColorFormatName::SRGB => 4,
+            ColorFormatName::RGB48 => 5,
+            ColorFormatName::RGBA => 6,
+            ColorFormatName::RGBA64 => 7,
+            ColorFormatName::RGBf => 8,
+            ColorFormatName::SRGBf => 9,
+            ColorFormatName::RGBAf => 10,
+            ColorFormatName::HSV => 11,
+            ColorFormatName::HSL => 12,
+            ColorFormatName::CIEXYZ => 13,
+            ColorFormatName::CIELab => 14,
+            ColorFormatName::CIELch => 15,
+            ColorFormatName::CIELuv => 16,
+            ColorFormatName::CIELchUv => 17,
+            ColorFormatName::CIExyY => 18,
+            ColorFormatName::Oklab => 19,
+            ColorFormatName::Oklch => 20,
+            ColorFormatName::CMYK => 21,
+            ColorFormatName::YCbCr => 22,
+            ColorFormatName::HWB => 23,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<ColorFormatName> {
+        match tag {
+            0 => Some(ColorFormatName::Gray8),
+            1 => Some(ColorFormatName::Gray16),
+            2 => Some(ColorFormatName::Grayf),
+            3 => Some(ColorFormatName::RGB),
+            4 => Some(ColorFormatName::SRGB),
+            5 => Some(ColorFormatName::RGB48),
+            6 => Some(ColorFormatName::RGBA),
+            7 => Some(ColorFormatName::RGBA64),
+            8 => Some(ColorFormatName::RGBf),
+            9 => Some(ColorFormatName::SRGBf),
+            10 => Some(ColorFormatName::RGBAf),
+            11 => Some(ColorFormatName::HSV),
+            12 => Some(ColorFormatName::HSL),
+            13 => Some(ColorFormatName::CIEXYZ),
+            14 => Some(ColorFormatName::CIELab),
+            15 => Some(ColorFormatName::CIELch),
+            16 => Some(ColorFormatName::CIELuv),
+            17 => Some(ColorFormatName::CIELchUv),
+            18 => Some(ColorFormatName::CIExyY),
+            19 => Some(ColorFormatName::Oklab),
+            20 => Some(ColorFormatName::Oklch),
+            21 => Some(ColorFormatName::CMYK),
+            22 => Some(ColorFormatName::YCbCr),
+            23 => Some(ColorFormatName::HWB),
+            _ => None,
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub fn get_bytes_per_pixel<C: ColorFormat>(_p: C) -> usize {
     C::bytes_per_pixel()
@@ -91,10 +204,62 @@ pub fn get_bpp_for_format(cfn: ColorFormatName) -> usize {
         },
         ColorFormatName::CIELab => {
             CieLab::bytes_per_pixel()
+        },
+        ColorFormatName::CIELch => {
+            CieLch::bytes_per_pixel()
+        },
+        ColorFormatName::CIELuv => {
+            CieLuv::bytes_per_pixel()
+        },
+        ColorFormatName::CIELchUv => {
+            CieLchUv::bytes_per_pixel()
+        },
+        ColorFormatName::CIExyY => {
+            CieXyY::bytes_per_pixel()
+        },
+        ColorFormatName::Oklab => {
+            Oklab::bytes_per_pixel()
+        },
+        ColorFormatName::Oklch => {
+            Oklch::bytes_per_pixel()
+        },
+        ColorFormatName::CMYK => {
+            Cmyk::bytes_per_pixel()
+        },
+        ColorFormatName::YCbCr => {
+            YCbCr::bytes_per_pixel()
+        },
+        ColorFormatName::HWB => {
+            Hwb::bytes_per_pixel()
+        }
+    }
+}
+
+//The size of each value in a format's bytes. Every format is a run of same-sized values,
+//which is all that changing byte order needs to know.
+#[allow(dead_code)]
+pub fn get_channel_size_for_format(cfn: ColorFormatName) -> usize {
+    match cfn {
+        ColorFormatName::Gray8 | ColorFormatName::RGB | ColorFormatName::SRGB | ColorFormatName::RGBA |
+        ColorFormatName::YCbCr => {
+            std::mem::size_of::<u8>()
+        },
+        ColorFormatName::Gray16 | ColorFormatName::RGB48 | ColorFormatName::RGBA64 => {
+            std::mem::size_of::<u16>()
+        },
+        _ => {
+            std::mem::size_of::<f32>()
         }
     }
 }
 
+/// Reverses the byte order of every value in bytes- native to the other endianness, or back
+pub fn swap_byte_order(cfn: ColorFormatName, bytes: &mut [u8]) {
+    for value in bytes.chunks_exact_mut(get_channel_size_for_format(cfn)) {
+        value.reverse();
+    }
+}
+
 // Utiliity functions for conversions
 
 fn gray_from_rgb(r: f32, g: f32, b: f32) -> f32 {
@@ -2,6 +2,7 @@ use super::*;
 
 //A note on scale: Y = 1.0 is maximum luminance. Some systems use Y = 100 for this- they need to be scaled
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CieXyz {
     pub x: f32,
     pub y: f32,
@@ -16,12 +17,7 @@ pub struct CieXyz {
  */
 impl Default for CieXyz {
     fn default() -> CieXyz {
-        CieXyz {
-            x: 0.95047f32,
-            y: 1.0f32,
-            z: 1.08883f32,
-            ref_xyz: None,
-        }
+        CieXyz::D65
     }
 }
 
@@ -197,8 +193,8 @@ impl ColorFormat for CieXyz {
 
         if let Some(triple) = self.ref_xyz {
             let ref_x = f32::to_ne_bytes(triple.0);
-            let ref_z = f32::to_ne_bytes(triple.1);
-            let ref_y = f32::to_ne_bytes(triple.2);
+            let ref_y = f32::to_ne_bytes(triple.1);
+            let ref_z = f32::to_ne_bytes(triple.2);
 
             bytes.extend_from_slice(&ref_x);
             bytes.extend_from_slice(&ref_y);
@@ -219,8 +215,8 @@ impl ColorFormat for CieXyz {
 
         if let Some(triple) = self.ref_xyz {
             let ref_x = f32::to_ne_bytes(triple.0);
-            let ref_z = f32::to_ne_bytes(triple.1);
-            let ref_y = f32::to_ne_bytes(triple.2);
+            let ref_y = f32::to_ne_bytes(triple.1);
+            let ref_z = f32::to_ne_bytes(triple.2);
 
             bytes.extend_from_slice(&ref_x);
             bytes.extend_from_slice(&ref_y);
@@ -439,6 +435,7 @@ impl From<CieLab> for CieXyz {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CieLab {
     pub l: f32,
     pub a: f32,
@@ -505,8 +502,8 @@ impl ColorFormat for CieLab {
 
         if let Some(triple) = self.ref_xyz {
             let ref_x = f32::to_ne_bytes(triple.0);
-            let ref_z = f32::to_ne_bytes(triple.1);
-            let ref_y = f32::to_ne_bytes(triple.2);
+            let ref_y = f32::to_ne_bytes(triple.1);
+            let ref_z = f32::to_ne_bytes(triple.2);
 
             bytes.extend_from_slice(&ref_x);
             bytes.extend_from_slice(&ref_y);
@@ -527,8 +524,8 @@ impl ColorFormat for CieLab {
 
         if let Some(triple) = self.ref_xyz {
             let ref_x = f32::to_ne_bytes(triple.0);
-            let ref_z = f32::to_ne_bytes(triple.1);
-            let ref_y = f32::to_ne_bytes(triple.2);
+            let ref_y = f32::to_ne_bytes(triple.1);
+            let ref_z = f32::to_ne_bytes(triple.2);
 
             bytes.extend_from_slice(&ref_x);
             bytes.extend_from_slice(&ref_y);
@@ -608,3 +605,599 @@ impl From<CieXyz> for CieLab {
         CieLab::new(l, a, b).with_reference_white(ref_white)
     }
 }
+
+//CIELab in cylindrical coordinates: lightness, chroma (distance from gray) and hue in degrees
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CieLch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub ref_xyz: Option<(f32, f32, f32)>,
+}
+
+#[allow(dead_code)]
+impl CieLch {
+    pub fn new(l: f32, c: f32, h: f32) -> CieLch {
+        CieLch {
+            l,
+            c,
+            h,
+            ref_xyz: None,
+        }
+    }
+
+    pub fn with_reference_white(mut self, ref_white: CieXyz) -> CieLch {
+        match ref_white.ref_xyz {
+            None => {
+                self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+            },
+            Some(triplet) => {
+                let def = CieXyz::default();
+
+                if triplet.0 == def.x && triplet.1 == def.y && triplet.2 == def.z {
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                } else {
+                    let ref_white = CieXyz::chromatic_adaptation(ref_white, def);
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                }
+            }
+        }
+
+        self
+    }
+}
+
+impl ColorFormat for CieLch {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::CIELch
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.as_bytes()
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let c = f32::to_ne_bytes(self.c);
+        let h = f32::to_ne_bytes(self.h);
+
+        let mut bytes = vec![l[0], l[1], l[2], l[3],
+                             c[0], c[1], c[2], c[3],
+                             h[0], h[1], h[2], h[3]];
+
+        if let Some(triple) = self.ref_xyz {
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.0));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.1));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.2));
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 || bytes.len() == 24 {
+            let l = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let c = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let h = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            let color = CieLch::new(l, c, h);
+
+            if bytes.len() == 24 {
+                let ref_x = f32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+                let ref_y = f32::from_ne_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+                let ref_z = f32::from_ne_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+
+                Ok(color.with_reference_white(CieXyz::new(ref_x, ref_y, ref_z)))
+            } else {
+                Ok(color)
+            }
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly either {} or {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), Self::bytes_per_pixel() * 2, bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::CIELch, self.to_bytes())
+    }
+}
+
+impl From<CieLab> for CieLch {
+    fn from(lab: CieLab) -> CieLch {
+        let c = (lab.a * lab.a + lab.b * lab.b).sqrt();
+
+        //Gray has no hue- keep it at 0 rather than whatever atan2 makes of rounding noise
+        let h = if c < 1e-4f32 {
+            0.0f32
+        } else {
+            lab.b.atan2(lab.a).to_degrees().rem_euclid(360.0f32)
+        };
+
+        CieLch {
+            l: lab.l,
+            c,
+            h,
+            ref_xyz: lab.ref_xyz
+        }
+    }
+}
+
+impl From<CieLch> for CieLab {
+    fn from(lch: CieLch) -> CieLab {
+        let h = lch.h.to_radians();
+
+        CieLab {
+            l: lch.l,
+            a: lch.c * h.cos(),
+            b: lch.c * h.sin(),
+            ref_xyz: lch.ref_xyz
+        }
+    }
+}
+
+//CIE 1976 L*u*v*. Like L*a*b*, but additive mixtures of lights fall on straight lines, which suits emissive displays
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CieLuv {
+    pub l: f32,
+    pub u: f32,
+    pub v: f32,
+    pub ref_xyz: Option<(f32, f32, f32)>,
+}
+
+#[allow(dead_code)]
+impl CieLuv {
+    pub fn new(l: f32, u: f32, v: f32) -> CieLuv {
+        CieLuv {
+            l,
+            u,
+            v,
+            ref_xyz: None,
+        }
+    }
+
+    pub fn with_reference_white(mut self, ref_white: CieXyz) -> CieLuv {
+        match ref_white.ref_xyz {
+            None => {
+                self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+            },
+            Some(triplet) => {
+                let def = CieXyz::default();
+
+                if triplet.0 == def.x && triplet.1 == def.y && triplet.2 == def.z {
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                } else {
+                    let ref_white = CieXyz::chromatic_adaptation(ref_white, def);
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                }
+            }
+        }
+
+        self
+    }
+}
+
+impl ColorFormat for CieLuv {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::CIELuv
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.as_bytes()
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let u = f32::to_ne_bytes(self.u);
+        let v = f32::to_ne_bytes(self.v);
+
+        let mut bytes = vec![l[0], l[1], l[2], l[3],
+                             u[0], u[1], u[2], u[3],
+                             v[0], v[1], v[2], v[3]];
+
+        if let Some(triple) = self.ref_xyz {
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.0));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.1));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.2));
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 || bytes.len() == 24 {
+            let l = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let u = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let v = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            let color = CieLuv::new(l, u, v);
+
+            if bytes.len() == 24 {
+                let ref_x = f32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+                let ref_y = f32::from_ne_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+                let ref_z = f32::from_ne_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+
+                Ok(color.with_reference_white(CieXyz::new(ref_x, ref_y, ref_z)))
+            } else {
+                Ok(color)
+            }
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly either {} or {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), Self::bytes_per_pixel() * 2, bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::CIELuv, self.to_bytes())
+    }
+}
+
+//u' and v' chromaticity coordinates
+fn uv_prime(x: f32, y: f32, z: f32) -> (f32, f32) {
+    let denominator = x + 15.0f32 * y + 3.0f32 * z;
+    if denominator == 0.0f32 {
+        (0.0f32, 0.0f32)
+    } else {
+        ((4.0f32 * x) / denominator, (9.0f32 * y) / denominator)
+    }
+}
+
+impl From<CieXyz> for CieLuv {
+    fn from(xyz: CieXyz) -> CieLuv {
+        let ref_white = if let Some(ref_xyz) = xyz.ref_xyz {
+            CieXyz::new(ref_xyz.0, ref_xyz.1, ref_xyz.2)
+        } else {
+            CieXyz::default()
+        };
+
+        let epsilon = 0.008856f32;
+        let kappa = 903.3f32;
+
+        let y_r = xyz.y / ref_white.y;
+
+        let l = if y_r > epsilon {
+            116.0f32 * y_r.cbrt() - 16.0f32
+        } else {
+            kappa * y_r
+        };
+
+        let (u_prime, v_prime) = uv_prime(xyz.x, xyz.y, xyz.z);
+        let (ref_u_prime, ref_v_prime) = uv_prime(ref_white.x, ref_white.y, ref_white.z);
+
+        //Black has no chromaticity to speak of
+        let (u, v) = if xyz.x + 15.0f32 * xyz.y + 3.0f32 * xyz.z == 0.0f32 {
+            (0.0f32, 0.0f32)
+        } else {
+            (13.0f32 * l * (u_prime - ref_u_prime), 13.0f32 * l * (v_prime - ref_v_prime))
+        };
+
+        CieLuv::new(l, u, v).with_reference_white(ref_white)
+    }
+}
+
+impl From<CieLuv> for CieXyz {
+    fn from(luv: CieLuv) -> CieXyz {
+        let ref_white = if let Some(ref_xyz) = luv.ref_xyz {
+            CieXyz::new(ref_xyz.0, ref_xyz.1, ref_xyz.2)
+        } else {
+            CieXyz::default()
+        };
+
+        if luv.l <= 0.0f32 {
+            return CieXyz::new(0.0f32, 0.0f32, 0.0f32).with_reference_white(ref_white);
+        }
+
+        let epsilon = 0.008856f32;
+        let kappa = 903.3f32;
+
+        let y = if luv.l > kappa * epsilon {
+            ((luv.l + 16.0f32) / 116.0f32).powi(3)
+        } else {
+            luv.l / kappa
+        } * ref_white.y;
+
+        let (ref_u_prime, ref_v_prime) = uv_prime(ref_white.x, ref_white.y, ref_white.z);
+        let u_prime = luv.u / (13.0f32 * luv.l) + ref_u_prime;
+        let v_prime = luv.v / (13.0f32 * luv.l) + ref_v_prime;
+
+        let x = y * (9.0f32 * u_prime) / (4.0f32 * v_prime);
+        let z = y * (12.0f32 - 3.0f32 * u_prime - 20.0f32 * v_prime) / (4.0f32 * v_prime);
+
+        CieXyz::new(x, y, z).with_reference_white(ref_white)
+    }
+}
+
+//CIELuv in cylindrical coordinates: lightness, chroma and hue in degrees
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CieLchUv {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub ref_xyz: Option<(f32, f32, f32)>,
+}
+
+#[allow(dead_code)]
+impl CieLchUv {
+    pub fn new(l: f32, c: f32, h: f32) -> CieLchUv {
+        CieLchUv {
+            l,
+            c,
+            h,
+            ref_xyz: None,
+        }
+    }
+
+    pub fn with_reference_white(mut self, ref_white: CieXyz) -> CieLchUv {
+        match ref_white.ref_xyz {
+            None => {
+                self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+            },
+            Some(triplet) => {
+                let def = CieXyz::default();
+
+                if triplet.0 == def.x && triplet.1 == def.y && triplet.2 == def.z {
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                } else {
+                    let ref_white = CieXyz::chromatic_adaptation(ref_white, def);
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                }
+            }
+        }
+
+        self
+    }
+}
+
+impl ColorFormat for CieLchUv {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::CIELchUv
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.as_bytes()
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let c = f32::to_ne_bytes(self.c);
+        let h = f32::to_ne_bytes(self.h);
+
+        let mut bytes = vec![l[0], l[1], l[2], l[3],
+                             c[0], c[1], c[2], c[3],
+                             h[0], h[1], h[2], h[3]];
+
+        if let Some(triple) = self.ref_xyz {
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.0));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.1));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.2));
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 || bytes.len() == 24 {
+            let l = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let c = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let h = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            let color = CieLchUv::new(l, c, h);
+
+            if bytes.len() == 24 {
+                let ref_x = f32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+                let ref_y = f32::from_ne_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+                let ref_z = f32::from_ne_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+
+                Ok(color.with_reference_white(CieXyz::new(ref_x, ref_y, ref_z)))
+            } else {
+                Ok(color)
+            }
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly either {} or {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), Self::bytes_per_pixel() * 2, bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::CIELchUv, self.to_bytes())
+    }
+}
+
+impl From<CieLuv> for CieLchUv {
+    fn from(luv: CieLuv) -> CieLchUv {
+        let c = (luv.u * luv.u + luv.v * luv.v).sqrt();
+
+        let h = if c < 1e-4f32 {
+            0.0f32
+        } else {
+            luv.v.atan2(luv.u).to_degrees().rem_euclid(360.0f32)
+        };
+
+        CieLchUv {
+            l: luv.l,
+            c,
+            h,
+            ref_xyz: luv.ref_xyz
+        }
+    }
+}
+
+impl From<CieLchUv> for CieLuv {
+    fn from(lch: CieLchUv) -> CieLuv {
+        let h = lch.h.to_radians();
+
+        CieLuv {
+            l: lch.l,
+            u: lch.c * h.cos(),
+            v: lch.c * h.sin(),
+            ref_xyz: lch.ref_xyz
+        }
+    }
+}
+
+//Chromaticity (x, y) plus luminance (Y), on the same scale as CieXyz
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CieXyY {
+    pub x: f32,
+    pub y: f32,
+    pub luminance: f32,
+    pub ref_xyz: Option<(f32, f32, f32)>,
+}
+
+#[allow(dead_code)]
+impl CieXyY {
+    pub fn new(x: f32, y: f32, luminance: f32) -> CieXyY {
+        CieXyY {
+            x,
+            y,
+            luminance,
+            ref_xyz: None,
+        }
+    }
+
+    pub fn with_reference_white(mut self, ref_white: CieXyz) -> CieXyY {
+        match ref_white.ref_xyz {
+            None => {
+                self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+            },
+            Some(triplet) => {
+                let def = CieXyz::default();
+
+                if triplet.0 == def.x && triplet.1 == def.y && triplet.2 == def.z {
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                } else {
+                    let ref_white = CieXyz::chromatic_adaptation(ref_white, def);
+                    self.ref_xyz = Some((ref_white.x, ref_white.y, ref_white.z));
+                }
+            }
+        }
+
+        self
+    }
+}
+
+impl ColorFormat for CieXyY {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::CIExyY
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        self.as_bytes()
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let x = f32::to_ne_bytes(self.x);
+        let y = f32::to_ne_bytes(self.y);
+        let luminance = f32::to_ne_bytes(self.luminance);
+
+        let mut bytes = vec![x[0], x[1], x[2], x[3],
+                             y[0], y[1], y[2], y[3],
+                             luminance[0], luminance[1], luminance[2], luminance[3]];
+
+        if let Some(triple) = self.ref_xyz {
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.0));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.1));
+            bytes.extend_from_slice(&f32::to_ne_bytes(triple.2));
+        }
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 || bytes.len() == 24 {
+            let x = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let y = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let luminance = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            let color = CieXyY::new(x, y, luminance);
+
+            if bytes.len() == 24 {
+                let ref_x = f32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+                let ref_y = f32::from_ne_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+                let ref_z = f32::from_ne_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+
+                Ok(color.with_reference_white(CieXyz::new(ref_x, ref_y, ref_z)))
+            } else {
+                Ok(color)
+            }
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly either {} or {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), Self::bytes_per_pixel() * 2, bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::CIExyY, self.to_bytes())
+    }
+}
+
+impl From<CieXyz> for CieXyY {
+    fn from(xyz: CieXyz) -> CieXyY {
+        let sum = xyz.x + xyz.y + xyz.z;
+
+        //Black has no chromaticity- use the reference white's, so it sits on the neutral axis
+        let (x, y) = if sum == 0.0f32 {
+            let ref_white = if let Some(ref_xyz) = xyz.ref_xyz {
+                CieXyz::new(ref_xyz.0, ref_xyz.1, ref_xyz.2)
+            } else {
+                CieXyz::default()
+            };
+            let ref_sum = ref_white.x + ref_white.y + ref_white.z;
+            (ref_white.x / ref_sum, ref_white.y / ref_sum)
+        } else {
+            (xyz.x / sum, xyz.y / sum)
+        };
+
+        CieXyY {
+            x,
+            y,
+            luminance: xyz.y,
+            ref_xyz: xyz.ref_xyz
+        }
+    }
+}
+
+impl From<CieXyY> for CieXyz {
+    fn from(xyy: CieXyY) -> CieXyz {
+        let (x, z) = if xyy.y == 0.0f32 {
+            (0.0f32, 0.0f32)
+        } else {
+            ((xyy.x * xyy.luminance) / xyy.y, ((1.0f32 - xyy.x - xyy.y) * xyy.luminance) / xyy.y)
+        };
+        let y = if xyy.y == 0.0f32 { 0.0f32 } else { xyy.luminance };
+
+        CieXyz {
+            x,
+            y,
+            z,
+            ref_xyz: xyy.ref_xyz
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::*;
+
+/*  CMYK, for print preview. Each channel is the ink coverage in [0, 1].
+ *  This is the device-independent "naive" CMYK- no press profile, just the inverse of sRGB with
+ *  as much of the gray as possible moved into black. Real presses limit how much ink paper can take,
+ *  which `with_ink_limit` approximates.
+ */
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cmyk {
+    pub c: f32,
+    pub m: f32,
+    pub y: f32,
+    pub k: f32,
+}
+
+#[allow(dead_code)]
+impl Cmyk {
+    pub fn new(c: f32, m: f32, y: f32, k: f32) -> Cmyk {
+        Cmyk {
+            c,
+            m,
+            y,
+            k,
+        }
+    }
+
+    /// The sum of all four inks- 4.0 would be 400% coverage
+    pub fn total_ink(&self) -> f32 {
+        self.c + self.m + self.y + self.k
+    }
+
+    /// Takes cyan, magenta and yellow down evenly until the total ink is at most limit.
+    /// Presses usually allow between 2.4 and 3.2 (240% to 320%). Black is only reduced if it's over the limit alone.
+    pub fn with_ink_limit(self, limit: f32) -> Cmyk {
+        let limit = limit.max(0.0f32);
+        if self.total_ink() <= limit {
+            return self;
+        }
+
+        if self.k >= limit {
+            return Cmyk::new(0.0f32, 0.0f32, 0.0f32, limit);
+        }
+
+        let scale = (limit - self.k) / (self.c + self.m + self.y);
+        Cmyk::new(self.c * scale, self.m * scale, self.y * scale, self.k)
+    }
+}
+
+impl ColorFormat for Cmyk {
+
+    fn channel_count() -> u8 {
+        4
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::CMYK
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let c = f32::to_ne_bytes(self.c);
+        let m = f32::to_ne_bytes(self.m);
+        let y = f32::to_ne_bytes(self.y);
+        let k = f32::to_ne_bytes(self.k);
+
+        vec![c[0], c[1], c[2], c[3],
+             m[0], m[1], m[2], m[3],
+             y[0], y[1], y[2], y[3],
+             k[0], k[1], k[2], k[3]]
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let c = f32::to_ne_bytes(self.c);
+        let m = f32::to_ne_bytes(self.m);
+        let y = f32::to_ne_bytes(self.y);
+        let k = f32::to_ne_bytes(self.k);
+
+        vec![c[0], c[1], c[2], c[3],
+             m[0], m[1], m[2], m[3],
+             y[0], y[1], y[2], y[3],
+             k[0], k[1], k[2], k[3]]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 16 {
+            let c = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let m = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let y = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            let k = f32::from_ne_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]);
+            Ok(Cmyk::new(c, m, y, k))
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::CMYK, self.to_bytes())
+    }
+}
+
+impl From<SrgbF> for Cmyk {
+    fn from(srgb_f: SrgbF) -> Cmyk {
+        let (r, g, b) = (srgb_f.r.clamp(0.0f32, 1.0f32), srgb_f.g.clamp(0.0f32, 1.0f32), srgb_f.b.clamp(0.0f32, 1.0f32));
+
+        let k = 1.0f32 - r.max(g).max(b);
+        if k >= 1.0f32 {
+            return Cmyk::new(0.0f32, 0.0f32, 0.0f32, 1.0f32);
+        }
+
+        Cmyk::new((1.0f32 - r - k) / (1.0f32 - k),
+                  (1.0f32 - g - k) / (1.0f32 - k),
+                  (1.0f32 - b - k) / (1.0f32 - k),
+                  k)
+    }
+}
+
+impl From<Cmyk> for SrgbF {
+    fn from(cmyk: Cmyk) -> SrgbF {
+        let white = 1.0f32 - cmyk.k.clamp(0.0f32, 1.0f32);
+
+        SrgbF::new((1.0f32 - cmyk.c.clamp(0.0f32, 1.0f32)) * white,
+                   (1.0f32 - cmyk.m.clamp(0.0f32, 1.0f32)) * white,
+                   (1.0f32 - cmyk.y.clamp(0.0f32, 1.0f32)) * white)
+    }
+}
@@ -3,6 +3,7 @@ use super::*;
 //Note: Grayscale formats are *linear* luminance (Y) rather than gamma-compressed luma (Y')
 //Equivalent to CIEXYZ's Y component
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gray8 {
     pub luminance: u8
 }
@@ -109,6 +110,7 @@ impl From<RgbaF> for Gray8 {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gray16 {
     pub luminance: u16
 }
@@ -245,6 +247,7 @@ impl From<RgbaF> for Gray16 {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrayF {
     pub luminance: f32
 }
@@ -1,6 +1,7 @@
 use super::*;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hsv {
     pub h: f32,
     pub s: f32,
@@ -97,6 +98,8 @@ impl From<Rgb> for Hsv {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
         let s = if v == 0.0f32 {
             0.0f32
@@ -143,6 +146,8 @@ impl From<Srgb> for Hsv {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
         let s = if v == 0.0f32 {
             0.0f32
@@ -185,6 +190,8 @@ impl From<SrgbF> for Hsv {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
         let s = if v == 0.0f32 {
             0.0f32
@@ -225,6 +232,8 @@ impl From<RgbF> for Hsv {
         } else {
             60.0f32 * (4.0f32 + ((rgbf.r - rgbf.g) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
         let s = if v == 0.0f32 {
             0.0f32
@@ -263,6 +272,8 @@ impl From<RgbaF> for Hsv {
         } else {
             60.0f32 * (4.0f32 + ((rgbaf.r - rgbaf.g) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
         let s = if v == 0.0f32 {
             0.0f32
@@ -288,6 +299,7 @@ impl From<Hsl> for Hsv {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hsl {
     pub h: f32,
     pub s: f32,
@@ -384,8 +396,15 @@ impl From<Rgb> for Hsl {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
-        let s = (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l });
+        //Grays, including black and white, would be 0 / 0
+        let s = if c == 0.0f32 {
+            0.0f32
+        } else {
+            (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l })
+        };
 
         Hsl::new(h, s, l)
     }
@@ -426,8 +445,15 @@ impl From<Srgb> for Hsl {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
-        let s = (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l });
+        //Grays, including black and white, would be 0 / 0
+        let s = if c == 0.0f32 {
+            0.0f32
+        } else {
+            (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l })
+        };
 
         Hsl::new(h, s, l)
     }
@@ -464,8 +490,15 @@ impl From<SrgbF> for Hsl {
         } else {
             60.0f32 * (4.0f32 + ((r_f - g_f) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
-        let s = (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l });
+        //Grays, including black and white, would be 0 / 0
+        let s = if c == 0.0f32 {
+            0.0f32
+        } else {
+            (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l })
+        };
 
         Hsl::new(h, s, l)
     }
@@ -500,8 +533,15 @@ impl From<RgbF> for Hsl {
         } else {
             60.0f32 * (4.0f32 + ((rgbf.r - rgbf.g) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
-        let s = (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l });
+        //Grays, including black and white, would be 0 / 0
+        let s = if c == 0.0f32 {
+            0.0f32
+        } else {
+            (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l })
+        };
 
         Hsl::new(h, s, l)
     }
@@ -534,8 +574,15 @@ impl From<RgbaF> for Hsl {
         } else {
             60.0f32 * (4.0f32 + ((rgbaf.r - rgbaf.g) / c))
         };
+        //Red at the top with more blue than green comes out negative
+        let h = if h < 0.0f32 { h + 360.0f32 } else { h };
 
-        let s = (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l });
+        //Grays, including black and white, would be 0 / 0
+        let s = if c == 0.0f32 {
+            0.0f32
+        } else {
+            (channels[max_idx] - l) / (if l >= 0.5f32 { 1.0f32 - l } else { l })
+        };
 
         Hsl::new(h, s, l)
     }
@@ -553,3 +600,98 @@ impl From<Hsv> for Hsl {
         Hsl::new(hsv.h, s, l)
     }
 }
+
+//Hue, whiteness and blackness: how much white and black to mix into a pure hue, as CSS's hwb() has it.
+//Unlike Hsv and Hsl, it's over gamma-encoded sRGB, so hwb() means what it does in CSS.
+//Whiteness and blackness adding up to 1 or more leave a gray.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hwb {
+    pub h: f32,
+    pub w: f32,
+    pub b: f32,
+}
+
+impl Hwb {
+    pub fn new(h: f32, w: f32, b: f32) -> Hwb {
+        Hwb {
+            h,
+            w,
+            b,
+        }
+    }
+}
+
+impl ColorFormat for Hwb {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::HWB
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let h = f32::to_ne_bytes(self.h);
+        let w = f32::to_ne_bytes(self.w);
+        let b = f32::to_ne_bytes(self.b);
+
+        vec![h[0], h[1], h[2], h[3],
+             w[0], w[1], w[2], w[3],
+             b[0], b[1], b[2], b[3]]
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let h = f32::to_ne_bytes(self.h);
+        let w = f32::to_ne_bytes(self.w);
+        let b = f32::to_ne_bytes(self.b);
+
+        vec![h[0], h[1], h[2], h[3],
+             w[0], w[1], w[2], w[3],
+             b[0], b[1], b[2], b[3]]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 {
+            let h = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let w = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let b = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            Ok(Hwb::new(h, w, b))
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::HWB, self.to_bytes())
+    }
+}
+
+//Whiteness and blackness are the smallest channel and what the largest falls short of 1, taken from the
+//gamma-encoded channels. Hue is worked out the same way Hsv's is, on the encoded values.
+impl From<SrgbF> for Hwb {
+    fn from(srgb_f: SrgbF) -> Hwb {
+        let hsv: Hsv = RgbF::new(srgb_f.r, srgb_f.g, srgb_f.b).into();
+        Hwb::new(hsv.h, (1.0f32 - hsv.s) * hsv.v, 1.0f32 - hsv.v)
+    }
+}
+
+impl From<Hwb> for SrgbF {
+    fn from(hwb: Hwb) -> SrgbF {
+        let (w, b) = (hwb.w.max(0.0f32), hwb.b.max(0.0f32));
+
+        let hsv = if w + b >= 1.0f32 {
+            Hsv::new(hwb.h, 0.0f32, w / (w + b))
+        } else {
+            let v = 1.0f32 - b;
+            Hsv::new(hwb.h, 1.0f32 - w / v, v)
+        };
+
+        let encoded: RgbF = hsv.into();
+        SrgbF::new(encoded.r, encoded.g, encoded.b)
+    }
+}
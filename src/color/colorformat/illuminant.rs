@@ -0,0 +1,211 @@
+use super::*;
+
+/*  Standard illuminants and color temperature.
+ *  The illuminants are the CIE 1931 2° observer whites, scaled to Y = 1 like everything else here.
+ *  Temperatures go through xy chromaticity- the Planckian locus for blackbodies (incandescent light,
+ *  candles, a "night mode" tint) and the CIE daylight locus for the D series.
+ */
+
+/// How to estimate the correlated color temperature of a color
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum CctMethod {
+    /// McCamy's cubic in xy. Quick, and within a few kelvin between about 2856K and 6504K.
+    McCamy,
+    /// Robertson's interpolation between isotemperature lines in CIE 1960 uv. Good from 1667K upwards.
+    #[default]
+    Robertson,
+}
+
+//Kim et al.'s cubic spline fit to the Planckian locus holds between these
+const PLANCKIAN_MIN: f32 = 1667.0f32;
+const PLANCKIAN_MAX: f32 = 25000.0f32;
+
+//The CIE daylight formulas hold between these
+const DAYLIGHT_MIN: f32 = 4000.0f32;
+const DAYLIGHT_MAX: f32 = 25000.0f32;
+
+//Robertson's table: (reciprocal megakelvin, u, v, slope of the isotemperature line)
+const ROBERTSON_TABLE: [(f32, f32, f32, f32); 31] = [
+    (0.0f32, 0.18006f32, 0.26352f32, -0.24341f32),
+    (10.0f32, 0.18066f32, 0.26589f32, -0.25479f32),
+    (20.0f32, 0.18133f32, 0.26846f32, -0.26876f32),
+    (30.0f32, 0.18208f32, 0.27119f32, -0.28539f32),
+    (40.0f32, 0.18293f32, 0.27407f32, -0.30470f32),
+    (50.0f32, 0.18388f32, 0.27709f32, -0.32675f32),
+    (60.0f32, 0.18494f32, 0.28021f32, -0.35156f32),
+    (70.0f32, 0.18611f32, 0.28342f32, -0.37915f32),
+    (80.0f32, 0.18740f32, 0.28668f32, -0.40955f32),
+    (90.0f32, 0.18880f32, 0.28997f32, -0.44278f32),
+    (100.0f32, 0.19032f32, 0.29326f32, -0.47888f32),
+    (125.0f32, 0.19462f32, 0.30141f32, -0.58204f32),
+    (150.0f32, 0.19962f32, 0.30921f32, -0.70471f32),
+    (175.0f32, 0.20525f32, 0.31647f32, -0.84901f32),
+    (200.0f32, 0.21142f32, 0.32312f32, -1.0182f32),
+    (225.0f32, 0.21807f32, 0.32909f32, -1.2168f32),
+    (250.0f32, 0.22511f32, 0.33439f32, -1.4512f32),
+    (275.0f32, 0.23247f32, 0.33904f32, -1.7298f32),
+    (300.0f32, 0.24010f32, 0.34308f32, -2.0637f32),
+    (325.0f32, 0.24792f32, 0.34655f32, -2.4681f32),
+    (350.0f32, 0.25591f32, 0.34951f32, -2.9641f32),
+    (375.0f32, 0.26400f32, 0.35200f32, -3.5814f32),
+    (400.0f32, 0.27218f32, 0.35407f32, -4.3633f32),
+    (425.0f32, 0.28039f32, 0.35577f32, -5.3762f32),
+    (450.0f32, 0.28863f32, 0.35714f32, -6.7262f32),
+    (475.0f32, 0.29685f32, 0.35823f32, -8.5955f32),
+    (500.0f32, 0.30505f32, 0.35907f32, -11.324f32),
+    (525.0f32, 0.31320f32, 0.35968f32, -15.628f32),
+    (550.0f32, 0.32129f32, 0.36011f32, -23.325f32),
+    (575.0f32, 0.32931f32, 0.36038f32, -40.770f32),
+    (600.0f32, 0.33724f32, 0.36051f32, -116.45f32),
+];
+
+const fn white(x: f32, z: f32) -> CieXyz {
+    CieXyz {
+        x,
+        y: 1.0f32,
+        z,
+        ref_xyz: None,
+    }
+}
+
+//A white with Y = 1 from its xy chromaticity
+fn white_from_xy(x: f32, y: f32) -> CieXyz {
+    CieXyz::new(x / y, 1.0f32, (1.0f32 - x - y) / y)
+}
+
+#[allow(dead_code)]
+impl CieXyz {
+    /// Incandescent (tungsten) light, about 2856K
+    pub const A: CieXyz = white(1.09850f32, 0.35585f32);
+    /// Horizon daylight, about 5003K. Used by print and ICC profiles.
+    pub const D50: CieXyz = white(0.96422f32, 0.82521f32);
+    /// Mid-morning daylight, about 5503K
+    pub const D55: CieXyz = white(0.95682f32, 0.92149f32);
+    /// Noon daylight, about 6504K. The white of sRGB and the default everywhere in this module.
+    pub const D65: CieXyz = white(0.95047f32, 1.08883f32);
+    /// North sky daylight, about 7504K
+    pub const D75: CieXyz = white(0.94972f32, 1.22638f32);
+    /// The equal-energy white
+    pub const E: CieXyz = white(1.0f32, 1.0f32);
+    /// Cool white fluorescent, about 4230K
+    pub const F2: CieXyz = white(0.99187f32, 0.67395f32);
+    /// Broadband daylight fluorescent, about 6500K
+    pub const F7: CieXyz = white(0.95044f32, 1.08755f32);
+    /// Narrowband (three-band) white fluorescent, about 4000K
+    pub const F11: CieXyz = white(1.00966f32, 0.64370f32);
+
+    /// The white of a blackbody at temperature kelvin, with Y = 1. Clamped to 1667K..25000K.
+    /// Use it as a reference white to tint- adapting D65 colors to `from_kelvin(3400.0)` warms them up.
+    pub fn from_kelvin(kelvin: f32) -> CieXyz {
+        let t = kelvin.clamp(PLANCKIAN_MIN, PLANCKIAN_MAX);
+        let (t2, t3) = (t * t, t * t * t);
+
+        let x = if t <= 4000.0f32 {
+            -0.2661239e9f32 / t3 - 0.2343589e6f32 / t2 + 0.8776956e3f32 / t + 0.179910f32
+        } else {
+            -3.0258469e9f32 / t3 + 2.107038e6f32 / t2 + 0.2226347e3f32 / t + 0.240390f32
+        };
+
+        let (x2, x3) = (x * x, x * x * x);
+        let y = if t <= 2222.0f32 {
+            -1.1063814f32 * x3 - 1.3481102f32 * x2 + 2.1855583f32 * x - 0.20219683f32
+        } else if t <= 4000.0f32 {
+            -0.9549476f32 * x3 - 1.3741859f32 * x2 + 2.09137f32 * x - 0.16748867f32
+        } else {
+            3.081758f32 * x3 - 5.873387f32 * x2 + 3.7511299f32 * x - 0.37001483f32
+        };
+
+        white_from_xy(x, y)
+    }
+
+    /// The CIE daylight white at temperature kelvin, with Y = 1. Clamped to 4000K..25000K.
+    /// The D illuminants are these at slightly odd temperatures- D65 is `daylight(6504.0)`.
+    pub fn daylight(kelvin: f32) -> CieXyz {
+        let t = kelvin.clamp(DAYLIGHT_MIN, DAYLIGHT_MAX);
+        let (t2, t3) = (t * t, t * t * t);
+
+        let x = if t <= 7000.0f32 {
+            -4.6070e9f32 / t3 + 2.9678e6f32 / t2 + 0.09911e3f32 / t + 0.244063f32
+        } else {
+            -2.0064e9f32 / t3 + 1.9018e6f32 / t2 + 0.24748e3f32 / t + 0.237040f32
+        };
+        let y = -3.0f32 * x * x + 2.870f32 * x - 0.275f32;
+
+        white_from_xy(x, y)
+    }
+
+    /// Estimates the correlated color temperature in kelvin- the temperature of the blackbody that looks closest.
+    /// None for black, and for colors past the ends of Robertson's table (below about 1667K).
+    pub fn cct(&self, method: CctMethod) -> Option<f32> {
+        let sum = self.x + self.y + self.z;
+        if sum <= 0.0f32 {
+            return None;
+        }
+
+        match method {
+            CctMethod::McCamy => {
+                let (x, y) = (self.x / sum, self.y / sum);
+                if y == 0.1858f32 {
+                    return None;
+                }
+
+                let n = (x - 0.3320f32) / (0.1858f32 - y);
+                Some(449.0f32 * n * n * n + 3525.0f32 * n * n + 6823.3f32 * n + 5520.33f32)
+            },
+            CctMethod::Robertson => {
+                let denominator = self.x + 15.0f32 * self.y + 3.0f32 * self.z;
+                let u = 4.0f32 * self.x / denominator;
+                let v = 6.0f32 * self.y / denominator;
+
+                //Signed distance from each isotemperature line- the color lies between the two where it flips
+                let distance = |(_, ui, vi, ti): (f32, f32, f32, f32)| ((v - vi) - ti * (u - ui)) / (1.0f32 + ti * ti).sqrt();
+
+                let mut previous = distance(ROBERTSON_TABLE[0]);
+                for i in 1..ROBERTSON_TABLE.len() {
+                    let current = distance(ROBERTSON_TABLE[i]);
+                    if current == 0.0f32 || previous.signum() != current.signum() {
+                        let (mired_a, mired_b) = (ROBERTSON_TABLE[i - 1].0, ROBERTSON_TABLE[i].0);
+                        let mired = mired_a + (mired_b - mired_a) * previous / (previous - current);
+                        //Zero would be an infinite temperature
+                        return if mired > 0.0f32 { Some(1.0e6f32 / mired) } else { None };
+                    }
+                    previous = current;
+                }
+
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daylight_at_6504k_is_d65() {
+        let d65 = CieXyz::daylight(6504.0f32);
+        assert!((d65.x - CieXyz::D65.x).abs() < 1e-3f32, "{:?}", d65);
+        assert_eq!(d65.y, 1.0f32);
+        assert!((d65.z - CieXyz::D65.z).abs() < 1e-3f32, "{:?}", d65);
+
+        let d50 = CieXyz::daylight(5003.0f32);
+        assert!((d50.x - CieXyz::D50.x).abs() < 1e-3f32 && (d50.z - CieXyz::D50.z).abs() < 1e-3f32, "{:?}", d50);
+    }
+
+    #[test]
+    fn cct_of_d65_is_6504k() {
+        for method in [CctMethod::McCamy, CctMethod::Robertson] {
+            let cct = CieXyz::D65.cct(method).unwrap();
+            assert!((cct - 6504.0f32).abs() < 10.0f32, "{:?}: {}", method, cct);
+        }
+
+        //Blackbody whites come back at the temperature they were made at
+        for kelvin in [2700.0f32, 4000.0f32, 10000.0f32] {
+            let cct = CieXyz::from_kelvin(kelvin).cct(CctMethod::Robertson).unwrap();
+            assert!((cct - kelvin).abs() < kelvin * 0.01f32, "{}K: {}", kelvin, cct);
+        }
+
+        assert_eq!(CieXyz::new(0.0f32, 0.0f32, 0.0f32).cct(CctMethod::Robertson), None);
+    }
+}
@@ -0,0 +1,333 @@
+use super::*;
+
+/*  Oklab, Björn Ottosson's perceptual color space (https://bottosson.github.io/posts/oklab/)
+ *  Like CIELab, but hue stays put as lightness and chroma change, which makes it much better
+ *  for gradients and for lightening/darkening colors. L is in [0, 1], a and b are roughly in [-0.4, 0.4].
+ *  It is defined relative to D65, the same reference white sRGB uses.
+ */
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Oklab {
+    pub fn new(l: f32, a: f32, b: f32) -> Oklab {
+        Oklab {
+            l,
+            a,
+            b,
+        }
+    }
+}
+
+//Oklab in cylindrical coordinates. Chroma is the distance from gray, hue is in degrees.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
+impl Oklch {
+    pub fn new(l: f32, c: f32, h: f32) -> Oklch {
+        Oklch {
+            l,
+            c,
+            h,
+        }
+    }
+}
+
+impl ColorFormat for Oklab {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::Oklab
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let a = f32::to_ne_bytes(self.a);
+        let b = f32::to_ne_bytes(self.b);
+
+        vec![l[0], l[1], l[2], l[3],
+             a[0], a[1], a[2], a[3],
+             b[0], b[1], b[2], b[3]]
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let a = f32::to_ne_bytes(self.a);
+        let b = f32::to_ne_bytes(self.b);
+
+        vec![l[0], l[1], l[2], l[3],
+             a[0], a[1], a[2], a[3],
+             b[0], b[1], b[2], b[3]]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 {
+            let l = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let a = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let b = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            Ok(Oklab::new(l, a, b))
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::Oklab, self.to_bytes())
+    }
+}
+
+impl ColorFormat for Oklch {
+
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::Oklch
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let c = f32::to_ne_bytes(self.c);
+        let h = f32::to_ne_bytes(self.h);
+
+        vec![l[0], l[1], l[2], l[3],
+             c[0], c[1], c[2], c[3],
+             h[0], h[1], h[2], h[3]]
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        let l = f32::to_ne_bytes(self.l);
+        let c = f32::to_ne_bytes(self.c);
+        let h = f32::to_ne_bytes(self.h);
+
+        vec![l[0], l[1], l[2], l[3],
+             c[0], c[1], c[2], c[3],
+             h[0], h[1], h[2], h[3]]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 12 {
+            let l = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let c = f32::from_ne_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+            let h = f32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+            Ok(Oklch::new(l, c, h))
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::Oklch, self.to_bytes())
+    }
+}
+
+//Both directions go through an LMS-like cone response- only the matrix into it differs
+//between linear sRGB and XYZ
+fn oklab_from_lms(lms: [f32; 3]) -> Oklab {
+    let lms = [lms[0].cbrt(), lms[1].cbrt(), lms[2].cbrt()];
+
+    let m_2 = [[0.21045426f32, 0.7936178f32, -0.004072047f32],
+               [1.9779985f32, -2.4285922f32, 0.4505937f32],
+               [0.025904037f32, 0.78277177f32, -0.80867577f32]];
+
+    let mut lab = [0.0f32, 0.0f32, 0.0f32];
+
+    for i in 0..lab.len() {
+        let mut row_sum = 0.0f32;
+        for j in 0..lms.len() {
+            row_sum += m_2[i][j] * lms[j];
+        }
+        lab[i] += row_sum;
+    }
+
+    Oklab::new(lab[0], lab[1], lab[2])
+}
+
+fn lms_from_oklab(oklab: Oklab) -> [f32; 3] {
+    let lab = [oklab.l, oklab.a, oklab.b];
+
+    let m_2_inverse = [[1.0f32, 0.39633778f32, 0.21580376f32],
+                       [1.0f32, -0.105561346f32, -0.06385417f32],
+                       [1.0f32, -0.08948418f32, -1.2914855f32]];
+
+    let mut lms = [0.0f32, 0.0f32, 0.0f32];
+
+    for i in 0..lms.len() {
+        let mut row_sum = 0.0f32;
+        for j in 0..lab.len() {
+            row_sum += m_2_inverse[i][j] * lab[j];
+        }
+        lms[i] += row_sum;
+    }
+
+    [lms[0].powi(3), lms[1].powi(3), lms[2].powi(3)]
+}
+
+impl From<RgbF> for Oklab {
+    fn from(rgbf: RgbF) -> Oklab {
+        let rgb = [rgbf.r, rgbf.g, rgbf.b];
+
+        let m = [[0.41222146f32, 0.53633255f32, 0.051445995f32],
+                 [0.2119035f32, 0.6806995f32, 0.10739696f32],
+                 [0.08830246f32, 0.28171885f32, 0.6299787f32]];
+
+        let mut lms = [0.0f32, 0.0f32, 0.0f32];
+
+        for i in 0..lms.len() {
+            let mut row_sum = 0.0f32;
+            for j in 0..rgb.len() {
+                row_sum += m[i][j] * rgb[j];
+            }
+            lms[i] += row_sum;
+        }
+
+        oklab_from_lms(lms)
+    }
+}
+
+//Colors outside of sRGB come out with channels outside of [0, 1]- they aren't clamped here
+impl From<Oklab> for RgbF {
+    fn from(oklab: Oklab) -> RgbF {
+        let lms = lms_from_oklab(oklab);
+
+        let m_inverse = [[4.0767417f32, -3.3077116f32, 0.23096994f32],
+                         [-1.268438f32, 2.6097574f32, -0.34131938f32],
+                         [-0.0041960863f32, -0.7034186f32, 1.7076147f32]];
+
+        let mut rgb = [0.0f32, 0.0f32, 0.0f32];
+
+        for i in 0..rgb.len() {
+            let mut row_sum = 0.0f32;
+            for j in 0..lms.len() {
+                row_sum += m_inverse[i][j] * lms[j];
+            }
+            rgb[i] += row_sum;
+        }
+
+        RgbF::new(rgb[0], rgb[1], rgb[2])
+    }
+}
+
+impl From<CieXyz> for Oklab {
+    fn from(xyz: CieXyz) -> Oklab {
+        let d65 = CieXyz::default();
+
+        //Oklab is defined for D65, so anything relative to another white has to be adapted first
+        let xyz = match xyz.ref_xyz {
+            Some(triplet) if triplet.0 != d65.x || triplet.1 != d65.y || triplet.2 != d65.z => {
+                CieXyz::chromatic_adaptation(xyz, d65)
+            },
+            _ => xyz
+        };
+
+        let xyz = [xyz.x, xyz.y, xyz.z];
+
+        let m_1 = [[0.818933f32, 0.36186674f32, -0.12885971f32],
+                   [0.032984544f32, 0.9293119f32, 0.03614564f32],
+                   [0.0482003f32, 0.26436627f32, 0.6338517f32]];
+
+        let mut lms = [0.0f32, 0.0f32, 0.0f32];
+
+        for i in 0..lms.len() {
+            let mut row_sum = 0.0f32;
+            for j in 0..xyz.len() {
+                row_sum += m_1[i][j] * xyz[j];
+            }
+            lms[i] += row_sum;
+        }
+
+        oklab_from_lms(lms)
+    }
+}
+
+impl From<Oklab> for CieXyz {
+    fn from(oklab: Oklab) -> CieXyz {
+        let lms = lms_from_oklab(oklab);
+
+        let m_1_inverse = [[1.2270138f32, -0.5578f32, 0.28125614f32],
+                           [-0.04058018f32, 1.1122569f32, -0.07167668f32],
+                           [-0.07638128f32, -0.42148197f32, 1.5861632f32]];
+
+        let mut xyz = [0.0f32, 0.0f32, 0.0f32];
+
+        for i in 0..xyz.len() {
+            let mut row_sum = 0.0f32;
+            for j in 0..lms.len() {
+                row_sum += m_1_inverse[i][j] * lms[j];
+            }
+            xyz[i] += row_sum;
+        }
+
+        CieXyz::new(xyz[0], xyz[1], xyz[2]).with_reference_white(CieXyz::default())
+    }
+}
+
+impl From<Oklab> for Oklch {
+    fn from(oklab: Oklab) -> Oklch {
+        let c = (oklab.a * oklab.a + oklab.b * oklab.b).sqrt();
+
+        //Gray has no hue- keep it at 0 rather than whatever atan2 makes of rounding noise
+        let h = if c < 1e-6f32 {
+            0.0f32
+        } else {
+            oklab.b.atan2(oklab.a).to_degrees().rem_euclid(360.0f32)
+        };
+
+        Oklch::new(oklab.l, c, h)
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(oklch: Oklch) -> Oklab {
+        let h = oklch.h.to_radians();
+
+        Oklab::new(oklch.l, oklch.c * h.cos(), oklch.c * h.sin())
+    }
+}
+
+impl From<RgbF> for Oklch {
+    fn from(rgbf: RgbF) -> Oklch {
+        let oklab: Oklab = rgbf.into();
+        oklab.into()
+    }
+}
+
+impl From<Oklch> for RgbF {
+    fn from(oklch: Oklch) -> RgbF {
+        let oklab: Oklab = oklch.into();
+        oklab.into()
+    }
+}
+
+impl From<CieXyz> for Oklch {
+    fn from(xyz: CieXyz) -> Oklch {
+        let oklab: Oklab = xyz.into();
+        oklab.into()
+    }
+}
+
+impl From<Oklch> for CieXyz {
+    fn from(oklch: Oklch) -> CieXyz {
+        let oklab: Oklab = oklch.into();
+        oklab.into()
+    }
+}
@@ -1,6 +1,7 @@
 use super::*;
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rgb {
     pub r: u8,
     pub g: u8,
@@ -92,7 +93,6 @@ impl From<SrgbF> for Rgb {
     fn from(srgb_f: SrgbF) -> Rgb {
         let (r_f, g_f, b_f) = (srgb_f.r, srgb_f.g, srgb_f.b);
         let (r_f, g_f, b_f) = (r_f.clamp(0.0f32, 1.0f32), g_f.clamp(0.0f32, 1.0f32), b_f.clamp(0.0f32, 1.0f32));
-        let (r_f, g_f, b_f) = (r_f / (u8::MAX as f32), g_f / (u8::MAX as f32), b_f / (u8::MAX as f32));
 
         let rl_f = srgb_gamma_expand(r_f);
         let gl_f = srgb_gamma_expand(g_f);
@@ -147,7 +147,7 @@ impl From<RgbaF> for Rgb {
 impl From<Hsv> for Rgb {
     fn from(hsv: Hsv) -> Rgb {
         let c = hsv.s * hsv.v;
-        let h = hsv.h / 60.0f32;
+        let h = hsv.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -181,7 +181,7 @@ impl From<Hsv> for Rgb {
 impl From<Hsl> for Rgb {
     fn from(hsl: Hsl) -> Rgb {
         let c = (1.0f32 - ((2.0f32 * hsl.l) - 1.0f32).abs()) * hsl.s;
-        let h = hsl.h / 60.0f32;
+        let h = hsl.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -261,6 +261,7 @@ impl From<CieXyz> for Rgb {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Srgb {
     pub r: u8,
     pub g: u8,
@@ -373,7 +374,7 @@ impl From<RgbaF> for Srgb {
 impl From<Hsv> for Srgb {
     fn from(hsv: Hsv) -> Srgb {
         let c = hsv.s * hsv.v;
-        let h = hsv.h / 60.0f32;
+        let h = hsv.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -411,7 +412,7 @@ impl From<Hsv> for Srgb {
 impl From<Hsl> for Srgb {
     fn from(hsl: Hsl) -> Srgb {
         let c = (1.0f32 - ((2.0f32 * hsl.l) - 1.0f32).abs()) * hsl.s;
-        let h = hsl.h / 60.0f32;
+        let h = hsl.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -501,6 +502,7 @@ impl From<CieXyz> for Srgb {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rgb48 {
     pub r: u16,
     pub g: u16,
@@ -620,6 +622,7 @@ impl From<RgbaF> for Rgb48 {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RgbF {
     pub r: f32,
     pub g: f32,
@@ -776,7 +779,7 @@ impl From<RgbaF> for RgbF {
 impl From<Hsv> for RgbF {
     fn from(hsv: Hsv) -> RgbF {
         let c = hsv.s * hsv.v;
-        let h = hsv.h / 60.0f32;
+        let h = hsv.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -805,7 +808,7 @@ impl From<Hsv> for RgbF {
 impl From<Hsl> for RgbF {
     fn from(hsl: Hsl) -> RgbF {
         let c = (1.0f32 - ((2.0f32 * hsl.l) - 1.0f32).abs()) * hsl.s;
-        let h = hsl.h / 60.0f32;
+        let h = hsl.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -879,6 +882,7 @@ impl From<CieXyz> for RgbF {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SrgbF {
     pub r: f32,
     pub g: f32,
@@ -1001,7 +1005,7 @@ impl From<RgbaF> for SrgbF {
 impl From<Hsv> for SrgbF {
     fn from(hsv: Hsv) -> SrgbF {
         let c = hsv.s * hsv.v;
-        let h = hsv.h / 60.0f32;
+        let h = hsv.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -1035,7 +1039,7 @@ impl From<Hsv> for SrgbF {
 impl From<Hsl> for SrgbF {
     fn from(hsl: Hsl) -> SrgbF {
         let c = (1.0f32 - ((2.0f32 * hsl.l) - 1.0f32).abs()) * hsl.s;
-        let h = hsl.h / 60.0f32;
+        let h = hsl.h.rem_euclid(360.0f32) / 60.0f32;
         let x = c * (1.0f32 - ((h % 2.0f32) - 1.0f32).abs());
 
         let (r, g, b) =
@@ -1121,6 +1125,7 @@ impl From<CieXyz> for SrgbF {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rgba {
     pub r: u8,
     pub g: u8,
@@ -1231,6 +1236,7 @@ impl From<RgbaF> for Rgba {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rgba64 {
     pub r: u16,
     pub g: u16,
@@ -1375,6 +1381,7 @@ impl From<RgbaF> for Rgba64 {
 }
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RgbaF {
     pub r: f32,
     pub g: f32,
@@ -0,0 +1,284 @@
+use super::*;
+
+/*  RGB color spaces other than sRGB. An RGB space is three primaries, given as chromaticities,
+ *  a white point, and the transfer function that turns linear light into the stored values.
+ *  Everything converts through CieXyz relative to D65, like the rest of the color module-
+ *  spaces with another white (ProPhoto is D50) are adapted with CieXyz::chromatic_adaptation.
+ */
+
+/// How a space's stored values relate to linear light. All of them are mirrored for negative values,
+/// so colors outside of the space survive a round trip.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransferFunction {
+    Linear,
+    //The piecewise sRGB curve, which Display P3 shares
+    Srgb,
+    //A pure power curve- Adobe RGB uses 563/256 (about 2.2)
+    Gamma(f32),
+    //ITU-R BT.2020, which is the BT.709 curve with more precise constants
+    Rec2020,
+    //ROMM RGB: 1.8 with a linear segment near black
+    ProPhoto,
+}
+
+const REC2020_ALPHA: f32 = 1.0992968f32;
+const REC2020_BETA: f32 = 0.01805397f32;
+
+impl TransferFunction {
+    /// Linear light -> stored value
+    pub fn encode(&self, linear: f32) -> f32 {
+        let v = linear.abs();
+        let encoded = match self {
+            TransferFunction::Linear => v,
+            TransferFunction::Srgb => srgb_gamma_compress(v),
+            TransferFunction::Gamma(gamma) => v.powf(1.0f32 / gamma),
+            TransferFunction::Rec2020 => {
+                if v < REC2020_BETA {
+                    4.5f32 * v
+                } else {
+                    REC2020_ALPHA * v.powf(0.45f32) - (REC2020_ALPHA - 1.0f32)
+                }
+            },
+            TransferFunction::ProPhoto => {
+                if v < 1.0f32 / 512.0f32 {
+                    16.0f32 * v
+                } else {
+                    v.powf(1.0f32 / 1.8f32)
+                }
+            },
+        };
+
+        encoded.copysign(linear)
+    }
+
+    /// Stored value -> linear light
+    pub fn decode(&self, encoded: f32) -> f32 {
+        let v = encoded.abs();
+        let linear = match self {
+            TransferFunction::Linear => v,
+            TransferFunction::Srgb => srgb_gamma_expand(v),
+            TransferFunction::Gamma(gamma) => v.powf(*gamma),
+            TransferFunction::Rec2020 => {
+                if v < 4.5f32 * REC2020_BETA {
+                    v / 4.5f32
+                } else {
+                    ((v + REC2020_ALPHA - 1.0f32) / REC2020_ALPHA).powf(1.0f32 / 0.45f32)
+                }
+            },
+            TransferFunction::ProPhoto => {
+                if v < 16.0f32 / 512.0f32 {
+                    v / 16.0f32
+                } else {
+                    v.powf(1.8f32)
+                }
+            },
+        };
+
+        linear.copysign(encoded)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RgbSpace {
+    red: (f32, f32),
+    green: (f32, f32),
+    blue: (f32, f32),
+    white: CieXyz,
+    transfer: TransferFunction,
+    //Linear RGB <-> XYZ relative to `white`, worked out once up front
+    to_xyz: [[f32; 3]; 3],
+    from_xyz: [[f32; 3]; 3],
+}
+
+fn invert_3x3(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+
+    let mut inverse = [[0.0f64; 3]; 3];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            //Cofactor of m[j][i], which transposes as it goes
+            let (r0, r1) = ((j + 1) % 3, (j + 2) % 3);
+            let (c0, c1) = ((i + 1) % 3, (i + 2) % 3);
+            *cell = (m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]) / det;
+        }
+    }
+
+    inverse
+}
+
+fn multiply_3x3(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    let row_sum = |row: &[f32; 3]| row.iter().zip(v.iter()).map(|(m, v)| m * v).sum::<f32>();
+    [row_sum(&m[0]), row_sum(&m[1]), row_sum(&m[2])]
+}
+
+fn is_same_white(a: (f32, f32, f32), b: CieXyz) -> bool {
+    a.0 == b.x && a.1 == b.y && a.2 == b.z
+}
+
+#[allow(dead_code)]
+impl RgbSpace {
+    /// A space from the xy chromaticities of its primaries, and its white as XYZ with Y = 1
+    pub fn new(red: (f32, f32), green: (f32, f32), blue: (f32, f32), white: CieXyz, transfer: TransferFunction) -> RgbSpace {
+        //Each primary's XYZ at Y = 1, as the columns of a matrix...
+        let column = |xy: (f32, f32)| {
+            let (x, y) = (xy.0 as f64, xy.1 as f64);
+            [x / y, 1.0f64, (1.0f64 - x - y) / y]
+        };
+        let (r, g, b) = (column(red), column(green), column(blue));
+        let primaries = [[r[0], g[0], b[0]],
+                         [r[1], g[1], b[1]],
+                         [r[2], g[2], b[2]]];
+
+        //...scaled so that full red, green and blue add up to the white
+        let inverse = invert_3x3(primaries);
+        let w = [white.x as f64, white.y as f64, white.z as f64];
+        let mut scale = [0.0f64; 3];
+        for i in 0..3 {
+            scale[i] = inverse[i][0] * w[0] + inverse[i][1] * w[1] + inverse[i][2] * w[2];
+        }
+
+        let mut to_xyz = [[0.0f64; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                to_xyz[i][j] = primaries[i][j] * scale[j];
+            }
+        }
+        let from_xyz = invert_3x3(to_xyz);
+
+        let to_f32 = |m: [[f64; 3]; 3]| {
+            [[m[0][0] as f32, m[0][1] as f32, m[0][2] as f32],
+             [m[1][0] as f32, m[1][1] as f32, m[1][2] as f32],
+             [m[2][0] as f32, m[2][1] as f32, m[2][2] as f32]]
+        };
+
+        RgbSpace {
+            red,
+            green,
+            blue,
+            white: CieXyz::new(white.x, white.y, white.z),
+            transfer,
+            to_xyz: to_f32(to_xyz),
+            from_xyz: to_f32(from_xyz),
+        }
+    }
+
+    /// The same primaries and curve Srgb and RgbF assume
+    pub fn srgb() -> RgbSpace {
+        RgbSpace::new((0.64f32, 0.33f32), (0.30f32, 0.60f32), (0.15f32, 0.06f32),
+                      CieXyz::default(), TransferFunction::Srgb)
+    }
+
+    /// DCI-P3 primaries with a D65 white and the sRGB curve, as used by Apple displays and CSS
+    pub fn display_p3() -> RgbSpace {
+        RgbSpace::new((0.680f32, 0.320f32), (0.265f32, 0.690f32), (0.150f32, 0.060f32),
+                      CieXyz::default(), TransferFunction::Srgb)
+    }
+
+    /// ITU-R BT.2020, for UHD video
+    pub fn rec2020() -> RgbSpace {
+        RgbSpace::new((0.708f32, 0.292f32), (0.170f32, 0.797f32), (0.131f32, 0.046f32),
+                      CieXyz::default(), TransferFunction::Rec2020)
+    }
+
+    /// Adobe RGB (1998), with its D65 white
+    pub fn adobe_rgb() -> RgbSpace {
+        RgbSpace::new((0.64f32, 0.33f32), (0.21f32, 0.71f32), (0.15f32, 0.06f32),
+                      CieXyz::default(), TransferFunction::Gamma(563.0f32 / 256.0f32))
+    }
+
+    /// ProPhoto (ROMM) RGB. Its white is D50, so conversions adapt to and from D65.
+    pub fn prophoto() -> RgbSpace {
+        RgbSpace::new((0.734699f32, 0.265301f32), (0.159597f32, 0.840403f32), (0.036598f32, 0.000105f32),
+                      CieXyz::D50, TransferFunction::ProPhoto)
+    }
+
+    pub fn primaries(&self) -> [(f32, f32); 3] {
+        [self.red, self.green, self.blue]
+    }
+
+    pub fn white(&self) -> CieXyz {
+        self.white
+    }
+
+    pub fn transfer(&self) -> TransferFunction {
+        self.transfer
+    }
+
+    /// Stored (transfer-encoded) RGB in this space -> XYZ relative to D65
+    pub fn rgb_to_xyz(&self, rgb: [f32; 3]) -> CieXyz {
+        let linear = [self.transfer.decode(rgb[0]), self.transfer.decode(rgb[1]), self.transfer.decode(rgb[2])];
+        let xyz = multiply_3x3(&self.to_xyz, linear);
+        let d65 = CieXyz::default();
+
+        if is_same_white((self.white.x, self.white.y, self.white.z), d65) {
+            CieXyz::new(xyz[0], xyz[1], xyz[2]).with_reference_white(d65)
+        } else {
+            let xyz = CieXyz::new(xyz[0], xyz[1], xyz[2]).with_reference_white(self.white);
+            let adapted = CieXyz::chromatic_adaptation(xyz, d65);
+            CieXyz::new(adapted.x, adapted.y, adapted.z).with_reference_white(d65)
+        }
+    }
+
+    /// XYZ -> stored RGB in this space. Colors outside of it come out below 0 or above 1, not clamped.
+    pub fn xyz_to_rgb(&self, xyz: CieXyz) -> [f32; 3] {
+        //Colors without a reference white are relative to D65, like everywhere else
+        let src_white = xyz.ref_xyz.unwrap_or({
+            let d65 = CieXyz::default();
+            (d65.x, d65.y, d65.z)
+        });
+
+        let xyz = if is_same_white(src_white, self.white) {
+            xyz
+        } else {
+            CieXyz::chromatic_adaptation(xyz, self.white)
+        };
+
+        let linear = multiply_3x3(&self.from_xyz, [xyz.x, xyz.y, xyz.z]);
+        [self.transfer.encode(linear[0]), self.transfer.encode(linear[1]), self.transfer.encode(linear[2])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_xyz(xyz: CieXyz, expected: [f32; 3], what: &str) {
+        let actual = [xyz.x, xyz.y, xyz.z];
+        for i in 0..3 {
+            assert!((actual[i] - expected[i]).abs() < 5e-4f32, "{}: got {:?}, expected {:?}", what, actual, expected);
+        }
+    }
+
+    //Each primary is a column of the space's RGB -> XYZ matrix
+    #[test]
+    fn primaries_match_the_published_matrices() {
+        //SMPTE EG 432-1 for Display P3, and ITU-R BT.2087 for Rec. 2020. Those take D65 from its xy
+        //chromaticity rather than as 0.95047, 1, 1.08883, which moves Z by a couple of ten-thousandths.
+        let p3 = RgbSpace::display_p3();
+        assert_xyz(p3.rgb_to_xyz([1.0f32, 0.0f32, 0.0f32]), [0.486571f32, 0.228975f32, 0.0f32], "P3 red");
+        assert_xyz(p3.rgb_to_xyz([0.0f32, 1.0f32, 0.0f32]), [0.265668f32, 0.691739f32, 0.045113f32], "P3 green");
+        assert_xyz(p3.rgb_to_xyz([0.0f32, 0.0f32, 1.0f32]), [0.198217f32, 0.079287f32, 1.043944f32], "P3 blue");
+
+        let rec2020 = RgbSpace::rec2020();
+        assert_xyz(rec2020.rgb_to_xyz([1.0f32, 0.0f32, 0.0f32]), [0.636958f32, 0.262700f32, 0.0f32], "Rec. 2020 red");
+        assert_xyz(rec2020.rgb_to_xyz([0.0f32, 1.0f32, 0.0f32]), [0.144617f32, 0.677998f32, 0.028073f32], "Rec. 2020 green");
+        assert_xyz(rec2020.rgb_to_xyz([0.0f32, 0.0f32, 1.0f32]), [0.168881f32, 0.059302f32, 1.060985f32], "Rec. 2020 blue");
+
+        //The primaries add up to the white
+        assert_xyz(rec2020.rgb_to_xyz([1.0f32, 1.0f32, 1.0f32]), [0.95047f32, 1.0f32, 1.08883f32], "Rec. 2020 white");
+    }
+
+    #[test]
+    fn round_trips_through_xyz() {
+        for space in [RgbSpace::srgb(), RgbSpace::display_p3(), RgbSpace::rec2020(), RgbSpace::adobe_rgb(), RgbSpace::prophoto()] {
+            for rgb in [[0.2f32, 0.5f32, 0.8f32], [1.0f32, 0.0f32, 0.0f32], [-0.1f32, 1.2f32, 0.01f32]] {
+                let back = space.xyz_to_rgb(space.rgb_to_xyz(rgb));
+                for i in 0..3 {
+                    assert!((back[i] - rgb[i]).abs() < 1e-3f32, "{:?}: {:?} came back {:?}", space.transfer(), rgb, back);
+                }
+            }
+        }
+    }
+}
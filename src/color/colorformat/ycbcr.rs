@@ -0,0 +1,219 @@
+use super::*;
+
+/*  Y'CbCr, the way video and JPEG store color: luma and two color differences as 8-bit code values.
+ *  The matrix (which luma weights) and the range (whether codes use all of 0-255, or leave
+ *  headroom like broadcast video) travel with each color, so it can always be decoded.
+ *  Only the matrix step is done here- R'G'B' is taken to be sRGB-encoded, as most software does.
+ */
+
+/// The luma weights, from the ITU-R recommendation a source follows
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YCbCrMatrix {
+    /// Standard definition video, and JPEG
+    #[default]
+    Bt601,
+    /// HD video
+    Bt709,
+    /// UHD video
+    Bt2020,
+}
+
+/// Which code values are used
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YCbCrRange {
+    /// 0-255 for everything, as in JPEG
+    #[default]
+    Full,
+    /// 16-235 for luma and 16-240 for chroma, as in broadcast video
+    Limited,
+}
+
+#[allow(dead_code)]
+impl YCbCrMatrix {
+    /// The red and blue luma weights- green's is whatever is left
+    pub fn kr_kb(&self) -> (f32, f32) {
+        match self {
+            YCbCrMatrix::Bt601 => (0.299f32, 0.114f32),
+            YCbCrMatrix::Bt709 => (0.2126f32, 0.0722f32),
+            YCbCrMatrix::Bt2020 => (0.2627f32, 0.0593f32),
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            YCbCrMatrix::Bt601 => 0,
+            YCbCrMatrix::Bt709 => 1,
+            YCbCrMatrix::Bt2020 => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<YCbCrMatrix> {
+        match tag {
+            0 => Some(YCbCrMatrix::Bt601),
+            1 => Some(YCbCrMatrix::Bt709),
+            2 => Some(YCbCrMatrix::Bt2020),
+            _ => None,
+        }
+    }
+}
+
+impl YCbCrRange {
+    //(offset, scale) for luma, then chroma, from [0, 1] and [-0.5, 0.5] to code values
+    fn scales(&self) -> ((f32, f32), (f32, f32)) {
+        match self {
+            YCbCrRange::Full => ((0.0f32, 255.0f32), (128.0f32, 255.0f32)),
+            YCbCrRange::Limited => ((16.0f32, 219.0f32), (128.0f32, 224.0f32)),
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            YCbCrRange::Full => 0,
+            YCbCrRange::Limited => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<YCbCrRange> {
+        match tag {
+            0 => Some(YCbCrRange::Full),
+            1 => Some(YCbCrRange::Limited),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct YCbCr {
+    pub y: u8,
+    pub cb: u8,
+    pub cr: u8,
+    pub matrix: YCbCrMatrix,
+    pub range: YCbCrRange,
+}
+
+//Black, rather than the green all-zero codes would be
+impl Default for YCbCr {
+    fn default() -> YCbCr {
+        YCbCr::new(0, 128, 128)
+    }
+}
+
+#[allow(dead_code)]
+impl YCbCr {
+    /// Code values in BT.601 full range, like JPEG. Use `with_matrix` and `with_range` for others.
+    pub fn new(y: u8, cb: u8, cr: u8) -> YCbCr {
+        YCbCr {
+            y,
+            cb,
+            cr,
+            matrix: YCbCrMatrix::default(),
+            range: YCbCrRange::default(),
+        }
+    }
+
+    /// Reinterprets the code values with another matrix- this doesn't convert
+    pub fn with_matrix(mut self, matrix: YCbCrMatrix) -> YCbCr {
+        self.matrix = matrix;
+        self
+    }
+
+    /// Reinterprets the code values with another range- this doesn't convert
+    pub fn with_range(mut self, range: YCbCrRange) -> YCbCr {
+        self.range = range;
+        self
+    }
+
+    /// Encodes sRGB with the given matrix and range
+    pub fn from_srgbf(srgb_f: SrgbF, matrix: YCbCrMatrix, range: YCbCrRange) -> YCbCr {
+        let (kr, kb) = matrix.kr_kb();
+        let (r, g, b) = (srgb_f.r.clamp(0.0f32, 1.0f32), srgb_f.g.clamp(0.0f32, 1.0f32), srgb_f.b.clamp(0.0f32, 1.0f32));
+
+        let luma = kr * r + (1.0f32 - kr - kb) * g + kb * b;
+        let cb = (b - luma) / (2.0f32 * (1.0f32 - kb));
+        let cr = (r - luma) / (2.0f32 * (1.0f32 - kr));
+
+        let ((y_offset, y_scale), (c_offset, c_scale)) = range.scales();
+        let code = |offset: f32, scale: f32, value: f32| (offset + scale * value).round().clamp(0.0f32, 255.0f32) as u8;
+
+        YCbCr {
+            y: code(y_offset, y_scale, luma),
+            cb: code(c_offset, c_scale, cb),
+            cr: code(c_offset, c_scale, cr),
+            matrix,
+            range,
+        }
+    }
+
+    /// The same color encoded with another matrix and range
+    pub fn reencode(self, matrix: YCbCrMatrix, range: YCbCrRange) -> YCbCr {
+        if self.matrix == matrix && self.range == range {
+            self
+        } else {
+            YCbCr::from_srgbf(self.into(), matrix, range)
+        }
+    }
+}
+
+impl ColorFormat for YCbCr {
+
+    //The matrix and range don't count- they describe the channels rather than being one
+    fn channel_count() -> u8 {
+        3
+    }
+
+    fn format_name() -> ColorFormatName {
+        ColorFormatName::YCbCr
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        vec![self.y, self.cb, self.cr, self.matrix.tag(), self.range.tag()]
+    }
+
+    fn as_bytes(&self) -> Vec<u8> {
+        vec![self.y, self.cb, self.cr, self.matrix.tag(), self.range.tag()]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() == 5 {
+            let matrix = YCbCrMatrix::from_tag(bytes[3]).ok_or(format!("Unknown YCbCr matrix {}", bytes[3]))?;
+            let range = YCbCrRange::from_tag(bytes[4]).ok_or(format!("Unknown YCbCr range {}", bytes[4]))?;
+            Ok(YCbCr::new(bytes[0], bytes[1], bytes[2]).with_matrix(matrix).with_range(range))
+        } else {
+            Err(format!("Tried to convert to {} with wrong number of bytes.\n\t \
+                            Hint: Should be exactly {} byte(s). {} bytes were provided.",
+                            type_name::<Self>(), Self::bytes_per_pixel(), bytes.len())
+                            .to_owned())
+        }
+    }
+
+    fn to_raw_parts(self) -> (ColorFormatName, Vec<u8>) {
+        (ColorFormatName::YCbCr, self.to_bytes())
+    }
+}
+
+impl From<SrgbF> for YCbCr {
+    fn from(srgb_f: SrgbF) -> YCbCr {
+        YCbCr::from_srgbf(srgb_f, YCbCrMatrix::default(), YCbCrRange::default())
+    }
+}
+
+impl From<YCbCr> for SrgbF {
+    fn from(ycbcr: YCbCr) -> SrgbF {
+        let (kr, kb) = ycbcr.matrix.kr_kb();
+        let ((y_offset, y_scale), (c_offset, c_scale)) = ycbcr.range.scales();
+
+        let luma = (ycbcr.y as f32 - y_offset) / y_scale;
+        let cb = (ycbcr.cb as f32 - c_offset) / c_scale;
+        let cr = (ycbcr.cr as f32 - c_offset) / c_scale;
+
+        let r = luma + 2.0f32 * (1.0f32 - kr) * cr;
+        let b = luma + 2.0f32 * (1.0f32 - kb) * cb;
+        let g = (luma - kr * r - kb * b) / (1.0f32 - kr - kb);
+
+        //Limited range has codes past white and black- and not every code is a valid color
+        SrgbF::new(r.clamp(0.0f32, 1.0f32), g.clamp(0.0f32, 1.0f32), b.clamp(0.0f32, 1.0f32))
+    }
+}
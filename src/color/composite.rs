@@ -0,0 +1,336 @@
+use super::color::*;
+
+/*  Alpha compositing, following the W3C Compositing and Blending spec (https://www.w3.org/TR/compositing-1/).
+ *  Everything happens in linear light- colors given as a `Color` are converted to RGBAf first, which
+ *  runs sRGB sources through srgb_gamma_expand. `RgbaF` is straight (not premultiplied) alpha unless
+ *  it came out of `premultiply`, and results are always handed back straight.
+ */
+
+/// The Porter-Duff operators. Src is the color being drawn, dst is what's already there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PorterDuff {
+    Clear,
+    Src,
+    Dst,
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+    Plus,
+}
+
+impl PorterDuff {
+    //How much of src and of dst survive, given their alphas
+    fn factors(&self, src_a: f32, dst_a: f32) -> (f32, f32) {
+        match self {
+            PorterDuff::Clear => (0.0f32, 0.0f32),
+            PorterDuff::Src => (1.0f32, 0.0f32),
+            PorterDuff::Dst => (0.0f32, 1.0f32),
+            PorterDuff::SrcOver => (1.0f32, 1.0f32 - src_a),
+            PorterDuff::DstOver => (1.0f32 - dst_a, 1.0f32),
+            PorterDuff::SrcIn => (dst_a, 0.0f32),
+            PorterDuff::DstIn => (0.0f32, src_a),
+            PorterDuff::SrcOut => (1.0f32 - dst_a, 0.0f32),
+            PorterDuff::DstOut => (0.0f32, 1.0f32 - src_a),
+            PorterDuff::SrcAtop => (dst_a, 1.0f32 - src_a),
+            PorterDuff::DstAtop => (1.0f32 - dst_a, src_a),
+            PorterDuff::Xor => (1.0f32 - dst_a, 1.0f32 - src_a),
+            PorterDuff::Plus => (1.0f32, 1.0f32),
+        }
+    }
+}
+
+/// How src and dst colors mix where they overlap. The result is then composited source-over.
+/// Hue, Saturation, Color and Luminosity are the non-separable modes- they work on whole colors
+/// rather than channel by channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+fn to_rgbaf(color: Color) -> RgbaF {
+    match Color::convert(color, ColorFormatName::RGBAf) {
+        Color::RGBAf(rgbaf) => rgbaf,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn multiply(cb: f32, cs: f32) -> f32 {
+    cb * cs
+}
+
+fn screen(cb: f32, cs: f32) -> f32 {
+    cb + cs - cb * cs
+}
+
+fn hard_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5f32 {
+        multiply(cb, 2.0f32 * cs)
+    } else {
+        screen(cb, 2.0f32 * cs - 1.0f32)
+    }
+}
+
+fn soft_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5f32 {
+        cb - (1.0f32 - 2.0f32 * cs) * cb * (1.0f32 - cb)
+    } else {
+        let d = if cb <= 0.25f32 {
+            ((16.0f32 * cb - 12.0f32) * cb + 4.0f32) * cb
+        } else {
+            cb.sqrt()
+        };
+        cb + (2.0f32 * cs - 1.0f32) * (d - cb)
+    }
+}
+
+fn color_dodge(cb: f32, cs: f32) -> f32 {
+    if cb <= 0.0f32 {
+        0.0f32
+    } else if cs >= 1.0f32 {
+        1.0f32
+    } else {
+        (cb / (1.0f32 - cs)).min(1.0f32)
+    }
+}
+
+fn color_burn(cb: f32, cs: f32) -> f32 {
+    if cb >= 1.0f32 {
+        1.0f32
+    } else if cs <= 0.0f32 {
+        0.0f32
+    } else {
+        1.0f32 - ((1.0f32 - cb) / cs).min(1.0f32)
+    }
+}
+
+//The spec weighs luminosity with the NTSC coefficients for gamma-compressed values-
+//since we're blending in linear light, the linear sRGB ones are the right match
+fn lum(c: [f32; 3]) -> f32 {
+    0.2126f32 * c[0] + 0.7152f32 * c[1] + 0.0722f32 * c[2]
+}
+
+//Pulls a color back into [0, 1] while keeping its luminosity
+fn clip_color(c: [f32; 3]) -> [f32; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut c = c;
+
+    if n < 0.0f32 {
+        for channel in c.iter_mut() {
+            *channel = l + (*channel - l) * l / (l - n);
+        }
+    }
+    if x > 1.0f32 {
+        for channel in c.iter_mut() {
+            *channel = l + (*channel - l) * (1.0f32 - l) / (x - l);
+        }
+    }
+
+    c
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    clip_color([c[0] + d, c[1] + d, c[2] + d])
+}
+
+fn sat(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let mut order = [0usize, 1usize, 2usize];
+    order.sort_by(|&i, &j| c[i].total_cmp(&c[j]));
+    let (min, mid, max) = (order[0], order[1], order[2]);
+
+    let mut out = [0.0f32, 0.0f32, 0.0f32];
+    if c[max] > c[min] {
+        out[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        out[max] = s;
+    }
+
+    out
+}
+
+fn blend_channels(cb: [f32; 3], cs: [f32; 3], mode: BlendMode) -> [f32; 3] {
+    let separable = |f: fn(f32, f32) -> f32| [f(cb[0], cs[0]), f(cb[1], cs[1]), f(cb[2], cs[2])];
+
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => separable(multiply),
+        BlendMode::Screen => separable(screen),
+        BlendMode::Overlay => separable(|cb, cs| hard_light(cs, cb)),
+        BlendMode::Darken => separable(f32::min),
+        BlendMode::Lighten => separable(f32::max),
+        BlendMode::ColorDodge => separable(color_dodge),
+        BlendMode::ColorBurn => separable(color_burn),
+        BlendMode::HardLight => separable(hard_light),
+        BlendMode::SoftLight => separable(soft_light),
+        BlendMode::Difference => separable(|cb, cs| (cb - cs).abs()),
+        BlendMode::Exclusion => separable(|cb, cs| cb + cs - 2.0f32 * cb * cs),
+        BlendMode::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        BlendMode::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        BlendMode::Color => set_lum(cs, lum(cb)),
+        BlendMode::Luminosity => set_lum(cb, lum(cs)),
+    }
+}
+
+#[allow(dead_code)]
+impl RgbaF {
+    /// Scales the color channels by alpha
+    pub fn premultiply(self) -> RgbaF {
+        RgbaF::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    /// Undoes `premultiply`. Fully transparent colors have no color left to recover and come back black.
+    pub fn unpremultiply(self) -> RgbaF {
+        if self.a <= 0.0f32 {
+            RgbaF::new(0.0f32, 0.0f32, 0.0f32, 0.0f32)
+        } else {
+            RgbaF::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+        }
+    }
+
+    /// Composites self (the source) onto dst with a Porter-Duff operator
+    pub fn composite(self, dst: RgbaF, op: PorterDuff) -> RgbaF {
+        let (f_src, f_dst) = op.factors(self.a, dst.a);
+        let (src, dst) = (self.premultiply(), dst.premultiply());
+
+        //RgbaF::new clamps, which is also what Plus needs
+        let out = RgbaF::new(src.r * f_src + dst.r * f_dst,
+                             src.g * f_src + dst.g * f_dst,
+                             src.b * f_src + dst.b * f_dst,
+                             src.a * f_src + dst.a * f_dst);
+        out.unpremultiply()
+    }
+
+    /// Blends self (the source) with dst, then composites the result source-over
+    pub fn blend(self, dst: RgbaF, mode: BlendMode) -> RgbaF {
+        let cs = [self.r, self.g, self.b];
+        let cb = [dst.r, dst.g, dst.b];
+        let blended = blend_channels(cb, cs, mode);
+
+        //Where dst is translucent, some of the unblended source shows through
+        let mixed = |i: usize| (1.0f32 - dst.a) * cs[i] + dst.a * blended[i];
+        let src = RgbaF::new(mixed(0), mixed(1), mixed(2), self.a);
+
+        src.composite(dst, PorterDuff::SrcOver)
+    }
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// Porter-Duff compositing of src onto dst. The result is `Color::RGBAf`.
+    pub fn composite(src: Color, dst: Color, op: PorterDuff) -> Color {
+        Color::RGBAf(to_rgbaf(src).composite(to_rgbaf(dst), op))
+    }
+
+    /// Blends src with dst and composites it source-over. The result is `Color::RGBAf`.
+    pub fn blend(src: Color, dst: Color, mode: BlendMode) -> Color {
+        Color::RGBAf(to_rgbaf(src).blend(to_rgbaf(dst), mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_rgba(actual: RgbaF, expected: [f32; 4], what: &str) {
+        let actual = [actual.r, actual.g, actual.b, actual.a];
+        for i in 0..4 {
+            assert!((actual[i] - expected[i]).abs() < 1e-3f32, "{}: got {:?}, expected {:?}", what, actual, expected);
+        }
+    }
+
+    #[test]
+    fn porter_duff_matches_reference_results() {
+        let red = RgbaF::new(1.0f32, 0.0f32, 0.0f32, 1.0f32);
+        let blue = RgbaF::new(0.0f32, 0.0f32, 1.0f32, 1.0f32);
+        let half_red = RgbaF::new(1.0f32, 0.0f32, 0.0f32, 0.5f32);
+        let half_blue = RgbaF::new(0.0f32, 0.0f32, 1.0f32, 0.5f32);
+
+        assert_rgba(half_red.composite(blue, PorterDuff::SrcOver), [0.5f32, 0.0f32, 0.5f32, 1.0f32], "src-over opaque");
+        //Alpha is 0.5 + 0.5 * 0.5, and the color is the premultiplied sum divided back out by it
+        assert_rgba(half_red.composite(half_blue, PorterDuff::SrcOver), [2.0f32 / 3.0f32, 0.0f32, 1.0f32 / 3.0f32, 0.75f32], "src-over translucent");
+        assert_rgba(half_red.composite(half_blue, PorterDuff::DstOver), [1.0f32 / 3.0f32, 0.0f32, 2.0f32 / 3.0f32, 0.75f32], "dst-over translucent");
+
+        //Xor keeps each side only where the other isn't
+        assert_rgba(half_red.composite(half_blue, PorterDuff::Xor), [0.5f32, 0.0f32, 0.5f32, 0.5f32], "xor translucent");
+        assert_rgba(red.composite(blue, PorterDuff::Xor), [0.0f32, 0.0f32, 0.0f32, 0.0f32], "xor opaque");
+
+        assert_rgba(red.composite(half_blue, PorterDuff::SrcIn), [1.0f32, 0.0f32, 0.0f32, 0.5f32], "src-in");
+        assert_rgba(red.composite(half_blue, PorterDuff::SrcOut), [1.0f32, 0.0f32, 0.0f32, 0.5f32], "src-out");
+        assert_rgba(half_red.composite(blue, PorterDuff::SrcAtop), [0.5f32, 0.0f32, 0.5f32, 1.0f32], "src-atop");
+        assert_rgba(half_red.composite(blue, PorterDuff::DstOut), [0.0f32, 0.0f32, 1.0f32, 0.5f32], "dst-out");
+        assert_rgba(half_red.composite(half_blue, PorterDuff::Plus), [0.5f32, 0.0f32, 0.5f32, 1.0f32], "plus");
+        assert_rgba(red.composite(blue, PorterDuff::Clear), [0.0f32, 0.0f32, 0.0f32, 0.0f32], "clear");
+    }
+
+    #[test]
+    fn separable_blends_match_the_spec() {
+        let white = RgbaF::new(1.0f32, 1.0f32, 1.0f32, 1.0f32);
+        let half_red = RgbaF::new(1.0f32, 0.0f32, 0.0f32, 0.5f32);
+        assert_rgba(half_red.blend(white, BlendMode::Multiply), [1.0f32, 0.5f32, 0.5f32, 1.0f32], "multiply");
+
+        let cb = RgbaF::new(0.8f32, 0.4f32, 0.2f32, 1.0f32);
+        let cs = RgbaF::new(0.25f32, 0.5f32, 0.75f32, 1.0f32);
+        assert_rgba(cs.blend(cb, BlendMode::Screen), [0.85f32, 0.7f32, 0.8f32, 1.0f32], "screen");
+        assert_rgba(cs.blend(cb, BlendMode::Overlay), [0.7f32, 0.4f32, 0.3f32, 1.0f32], "overlay");
+        assert_rgba(cs.blend(cb, BlendMode::Difference), [0.55f32, 0.1f32, 0.55f32, 1.0f32], "difference");
+        assert_rgba(cs.blend(cb, BlendMode::ColorDodge), [1.0f32, 0.8f32, 0.8f32, 1.0f32], "color-dodge");
+    }
+
+    //Worked through B(Cb, Cs) from section 10.2 of the spec by hand, with lum() weighing linear channels
+    #[test]
+    fn non_separable_blends_match_the_spec() {
+        let cb = RgbaF::new(0.8f32, 0.4f32, 0.2f32, 1.0f32);
+        let gray = RgbaF::new(0.5f32, 0.5f32, 0.5f32, 1.0f32);
+        let blue = RgbaF::new(0.0f32, 0.0f32, 1.0f32, 1.0f32);
+
+        //SetLum(SetSat(Cs, Sat(Cb)), Lum(Cb)): blue at cb's saturation 0.6, lifted to cb's luminosity 0.4706,
+        //then clipped back under 1
+        assert_rgba(blue.blend(cb, BlendMode::Hue), [0.4294f32, 0.4294f32, 1.0f32, 1.0f32], "hue");
+        //No saturation in the backdrop leaves no hue to take
+        assert_rgba(blue.blend(gray, BlendMode::Hue), [0.5f32, 0.5f32, 0.5f32, 1.0f32], "hue onto gray");
+
+        //SetLum(Cb, Lum(Cs)): cb pulled down to luminosity 0.2, then clipped up to 0
+        let dark = RgbaF::new(0.2f32, 0.2f32, 0.2f32, 1.0f32);
+        assert_rgba(dark.blend(cb, BlendMode::Luminosity), [0.4435f32, 0.1478f32, 0.0f32, 1.0f32], "luminosity");
+        assert_rgba(RgbaF::new(1.0f32, 1.0f32, 1.0f32, 1.0f32).blend(cb, BlendMode::Luminosity), [1.0f32, 1.0f32, 1.0f32, 1.0f32], "luminosity to white");
+
+        //Color and Luminosity are the same blend with the layers swapped
+        let color = cb.blend(dark, BlendMode::Color);
+        let luminosity = dark.blend(cb, BlendMode::Luminosity);
+        assert_rgba(color, [luminosity.r, luminosity.g, luminosity.b, luminosity.a], "color");
+
+        //Every non-separable mode keeps the luminosity its formula sets
+        for (mode, expected) in [(BlendMode::Hue, lum([0.8f32, 0.4f32, 0.2f32])), (BlendMode::Saturation, lum([0.8f32, 0.4f32, 0.2f32])),
+                                 (BlendMode::Color, lum([0.8f32, 0.4f32, 0.2f32])), (BlendMode::Luminosity, lum([0.0f32, 0.0f32, 1.0f32]))] {
+            let out = blue.blend(cb, mode);
+            assert!((lum([out.r, out.g, out.b]) - expected).abs() < 1e-4f32, "{:?}", mode);
+        }
+    }
+}
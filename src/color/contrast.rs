@@ -0,0 +1,199 @@
+use super::color::*;
+
+/// WCAG 2 conformance levels for text contrast
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WcagLevel {
+    AA,
+    AAA,
+}
+
+impl WcagLevel {
+    /// The lowest contrast ratio that passes. Large text is 18pt, or 14pt bold, and up.
+    pub fn min_ratio(&self, large_text: bool) -> f32 {
+        match (self, large_text) {
+            (WcagLevel::AA, false) => 4.5f32,
+            (WcagLevel::AA, true) => 3.0f32,
+            (WcagLevel::AAA, false) => 7.0f32,
+            (WcagLevel::AAA, true) => 4.5f32,
+        }
+    }
+}
+
+fn to_rgbf(color: Color) -> RgbF {
+    match Color::convert(color, ColorFormatName::RGBf) {
+        Color::RGBf(rgbf) => RgbF::new(rgbf.r.clamp(0.0f32, 1.0f32), rgbf.g.clamp(0.0f32, 1.0f32), rgbf.b.clamp(0.0f32, 1.0f32)),
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn to_oklch(color: Color) -> Oklch {
+    match Color::convert(color, ColorFormatName::Oklch) {
+        Color::Oklch(oklch) => oklch,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+/*  APCA-W3 0.0.98G-4g constants (https://github.com/Myndex/apca-w3)
+ *  APCA linearizes with a plain 2.4 exponent rather than the piecewise sRGB curve,
+ *  and soft-clamps near-black, since dark colors are harder to tell apart than their luminance suggests.
+ */
+const APCA_BLACK_THRESHOLD: f32 = 0.022f32;
+const APCA_BLACK_CLAMP: f32 = 1.414f32;
+const APCA_DELTA_Y_MIN: f32 = 0.0005f32;
+const APCA_SCALE: f32 = 1.14f32;
+const APCA_LOW_CLIP: f32 = 0.1f32;
+const APCA_LOW_OFFSET: f32 = 0.027f32;
+
+fn apca_luminance(color: Color) -> f32 {
+    let srgbf = match Color::convert(color, ColorFormatName::SRGBf) {
+        Color::SRGBf(srgbf) => srgbf,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    };
+
+    let y = 0.2126729f32 * srgbf.r.clamp(0.0f32, 1.0f32).powf(2.4f32)
+          + 0.7151522f32 * srgbf.g.clamp(0.0f32, 1.0f32).powf(2.4f32)
+          + 0.0721750f32 * srgbf.b.clamp(0.0f32, 1.0f32).powf(2.4f32);
+
+    if y < APCA_BLACK_THRESHOLD {
+        y + (APCA_BLACK_THRESHOLD - y).powf(APCA_BLACK_CLAMP)
+    } else {
+        y
+    }
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// WCAG relative luminance: the Y of linear RGB, from 0 for black to 1 for white
+    pub fn relative_luminance(color: Color) -> f32 {
+        let gray: GrayF = to_rgbf(color).into();
+        gray.luminance
+    }
+
+    /// The WCAG 2 contrast ratio, from 1 (no contrast) to 21 (black on white). The order doesn't matter.
+    pub fn contrast_ratio(fg: Color, bg: Color) -> f32 {
+        let lum_fg = Color::relative_luminance(fg);
+        let lum_bg = Color::relative_luminance(bg);
+        let (lighter, darker) = if lum_fg > lum_bg { (lum_fg, lum_bg) } else { (lum_bg, lum_fg) };
+
+        (lighter + 0.05f32) / (darker + 0.05f32)
+    }
+
+    pub fn meets_contrast(fg: Color, bg: Color, level: WcagLevel, large_text: bool) -> bool {
+        Color::contrast_ratio(fg, bg) >= level.min_ratio(large_text)
+    }
+
+    /// APCA lightness contrast (Lc) of text on a background. Unlike the WCAG ratio the order matters-
+    /// dark text on a light background is positive, light text on a dark one negative. |Lc| 75 is roughly
+    /// the minimum for body text, 60 for larger text, 45 for headlines.
+    pub fn apca_contrast(text: Color, bg: Color) -> f32 {
+        let y_text = apca_luminance(text);
+        let y_bg = apca_luminance(bg);
+
+        if (y_bg - y_text).abs() < APCA_DELTA_Y_MIN {
+            return 0.0f32;
+        }
+
+        let lc = if y_bg > y_text {
+            let sapc = (y_bg.powf(0.56f32) - y_text.powf(0.57f32)) * APCA_SCALE;
+            if sapc < APCA_LOW_CLIP { 0.0f32 } else { sapc - APCA_LOW_OFFSET }
+        } else {
+            let sapc = (y_bg.powf(0.65f32) - y_text.powf(0.62f32)) * APCA_SCALE;
+            if sapc > -APCA_LOW_CLIP { 0.0f32 } else { sapc + APCA_LOW_OFFSET }
+        };
+
+        lc * 100.0f32
+    }
+
+    /// Moves fg's Oklch lightness, keeping its hue and chroma, just far enough for its contrast ratio
+    /// against bg to reach target_ratio. It tries whichever of lighter or darker can reach it,
+    /// preferring the direction fg already leans. The result is in fg's format.
+    /// Returns `None` if even black or white wouldn't be enough.
+    pub fn nudge_for_contrast(fg: Color, bg: Color, target_ratio: f32) -> Option<Color> {
+        if Color::contrast_ratio(fg, bg) >= target_ratio {
+            return Some(fg);
+        }
+
+        let format = fg.get_format();
+        let start = to_oklch(fg);
+        let with_lightness = |l: f32| Color::convert(Color::Oklch(Oklch::new(l, start.c, start.h)), format);
+        let passes = |l: f32| Color::contrast_ratio(with_lightness(l), bg) >= target_ratio;
+
+        let lighter_first = Color::relative_luminance(fg) > Color::relative_luminance(bg);
+        let directions = if lighter_first { [1.0f32, 0.0f32] } else { [0.0f32, 1.0f32] };
+
+        let bg_l = to_oklch(bg).l;
+
+        for &extreme in directions.iter() {
+            if !passes(extreme) {
+                continue;
+            }
+
+            //Contrast only grows moving away from bg, so the smallest step that passes can be bisected.
+            //Going the other way fg first has to cross bg, so the search starts at bg's lightness.
+            let moving_away = (extreme > start.l) == (start.l >= bg_l);
+            let (mut failing, mut passing) = (if moving_away { start.l } else { bg_l }, extreme);
+            for _ in 0..24 {
+                let mid = (failing + passing) / 2.0f32;
+                if passes(mid) {
+                    passing = mid;
+                } else {
+                    failing = mid;
+                }
+            }
+
+            return Some(with_lightness(passing));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn srgb(r: u8, g: u8, b: u8) -> Color {
+        Color::SRGB(Srgb::new(r, g, b))
+    }
+
+    #[test]
+    fn wcag_ratios_match_reference_values() {
+        let black = srgb(0, 0, 0);
+        let white = srgb(255, 255, 255);
+
+        assert!((Color::contrast_ratio(black, white) - 21.0f32).abs() < 1e-3f32);
+        assert_eq!(Color::contrast_ratio(white, black), Color::contrast_ratio(black, white));
+        assert!((Color::contrast_ratio(white, white) - 1.0f32).abs() < 1e-6f32);
+
+        //#767676 is the lightest gray that passes AA on white, at 4.54:1
+        let gray = srgb(0x76, 0x76, 0x76);
+        assert!((Color::contrast_ratio(gray, white) - 4.54f32).abs() < 0.01f32);
+        assert!(Color::meets_contrast(gray, white, WcagLevel::AA, false));
+        assert!(!Color::meets_contrast(gray, white, WcagLevel::AAA, false));
+        assert!(!Color::meets_contrast(srgb(0x77, 0x77, 0x77), white, WcagLevel::AA, false));
+    }
+
+    #[test]
+    fn apca_matches_reference_values() {
+        let black = srgb(0, 0, 0);
+        let white = srgb(255, 255, 255);
+
+        //From the APCA-W3 reference implementation
+        assert!((Color::apca_contrast(black, white) - 106.04f32).abs() < 0.01f32, "{}", Color::apca_contrast(black, white));
+        assert!((Color::apca_contrast(white, black) + 107.88f32).abs() < 0.01f32, "{}", Color::apca_contrast(white, black));
+        assert!((Color::apca_contrast(srgb(0x88, 0x88, 0x88), white) - 63.06f32).abs() < 0.05f32, "{}", Color::apca_contrast(srgb(0x88, 0x88, 0x88), white));
+        assert_eq!(Color::apca_contrast(white, white), 0.0f32);
+    }
+
+    #[test]
+    fn nudging_reaches_the_target_ratio() {
+        let bg = srgb(255, 255, 255);
+        let nudged = Color::nudge_for_contrast(srgb(0x99, 0x99, 0xff), bg, 4.5f32).unwrap();
+        assert_eq!(nudged.get_format(), ColorFormatName::SRGB);
+        assert!(Color::contrast_ratio(nudged, bg) >= 4.5f32);
+        assert!(Color::contrast_ratio(nudged, bg) < 4.8f32);
+
+        assert_eq!(Color::nudge_for_contrast(srgb(0x80, 0x80, 0x80), srgb(0x80, 0x80, 0x80), 22.0f32), None);
+    }
+}
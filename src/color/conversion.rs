@@ -0,0 +1,244 @@
+use super::color::*;
+
+/*  The conversion graph behind Color::convert.
+ *  Every format is a node, and every direct From impl between two formats is an edge- declared once, below.
+ *  The route for each pair of formats is worked out at compile time: the cheapest path, where passing
+ *  through a format that would lose something both ends have (bit depth, alpha or color) costs far more
+ *  than an extra step. So Gray8 -> RGB48 goes through a wider gray rather than 8-bit Rgb.
+ *  Adding a format means adding its node to FORMATS and fidelity, and its From impls to the graph.
+ */
+
+pub(super) const FORMAT_COUNT: usize = 24;
+
+//In the same order as ColorFormatName, so `format as usize` indexes into it
+pub(super) const FORMATS: [ColorFormatName; FORMAT_COUNT] = [
+    ColorFormatName::Gray8, ColorFormatName::Gray16, ColorFormatName::Grayf,
+    ColorFormatName::RGB, ColorFormatName::SRGB, ColorFormatName::RGB48,
+    ColorFormatName::RGBA, ColorFormatName::RGBA64,
+    ColorFormatName::RGBf, ColorFormatName::SRGBf, ColorFormatName::RGBAf,
+    ColorFormatName::HSV, ColorFormatName::HSL,
+    ColorFormatName::CIEXYZ, ColorFormatName::CIELab,
+    ColorFormatName::CIELch, ColorFormatName::CIELuv, ColorFormatName::CIELchUv, ColorFormatName::CIExyY,
+    ColorFormatName::Oklab, ColorFormatName::Oklch,
+    ColorFormatName::CMYK, ColorFormatName::YCbCr, ColorFormatName::HWB,
+];
+
+//Each edge taken costs HOP_COST, plus a little for going through a format with fewer bits.
+//Going through a format that drops something the path needs costs LOSSY_COST on top.
+const HOP_COST: u32 = 16;
+const LOSSY_COST: u32 = 1024;
+
+const NO_ROUTE: u8 = u8::MAX;
+
+//Declares the graph: for each format, the formats it has a direct From impl to.
+//The names are both the Color variant and the ColorFormatName.
+macro_rules! conversion_graph {
+    ($($from:ident => [$($to:ident),* $(,)?]),* $(,)?) => {
+        const EDGES: &[(ColorFormatName, ColorFormatName)] = &[
+            $($((ColorFormatName::$from, ColorFormatName::$to),)*)*
+        ];
+
+        //Takes a single edge of the graph
+        fn step(from: Color, to: ColorFormatName) -> Color {
+            match (from, to) {
+                $($((Color::$from(color), ColorFormatName::$to) => Color::$to(color.into()),)*)*
+                _ => unreachable!("there is no direct conversion from {:?} to {:?}", from.get_format(), to),
+            }
+        }
+    };
+}
+
+conversion_graph! {
+    Gray8 => [Gray16, Grayf, RGB, RGBA],
+    Gray16 => [Gray8, Grayf, RGB, RGB48, RGBf, RGBA64, RGBAf],
+    Grayf => [Gray8, Gray16, RGB, SRGB, RGB48, RGBA, RGBA64, RGBf, SRGBf, RGBAf],
+    RGB => [Gray8, Gray16, Grayf, SRGB, RGB48, RGBA, RGBA64, RGBf, SRGBf, RGBAf, HSV, HSL, CIEXYZ],
+    SRGB => [Grayf, RGB, RGBf, SRGBf, HSV, HSL, CIEXYZ],
+    RGB48 => [Gray16, Grayf, RGB, RGBA64, RGBf],
+    RGBA => [Gray8, Gray16, Grayf, RGB, RGBA64, RGBf, RGBAf],
+    RGBA64 => [Gray16, Grayf, RGB48, RGBA, RGBf, RGBAf],
+    RGBf => [Gray8, Gray16, Grayf, RGB, SRGB, RGB48, RGBA, RGBA64, SRGBf, RGBAf, HSV, HSL, CIEXYZ, Oklab, Oklch],
+    SRGBf => [Grayf, RGB, SRGB, RGBf, HSV, HSL, CIEXYZ, CMYK, YCbCr, HWB],
+    RGBAf => [Gray8, Gray16, Grayf, RGB, SRGB, RGB48, RGBA, RGBA64, RGBf, SRGBf, HSV, HSL, CIEXYZ],
+    HSV => [RGB, SRGB, RGBf, SRGBf, HSL],
+    HSL => [RGB, SRGB, RGBf, SRGBf, HSV],
+    CIEXYZ => [Grayf, RGB, SRGB, RGBf, SRGBf, CIELab, CIELuv, CIExyY, Oklab, Oklch],
+    CIELab => [Grayf, CIEXYZ, CIELch],
+    CIELch => [CIELab],
+    CIELuv => [CIEXYZ, CIELchUv],
+    CIELchUv => [CIELuv],
+    CIExyY => [CIEXYZ],
+    Oklab => [RGBf, CIEXYZ, Oklch],
+    Oklch => [RGBf, CIEXYZ, Oklab],
+    CMYK => [SRGBf],
+    YCbCr => [SRGBf],
+    HWB => [SRGBf],
+}
+
+//What a format can hold: bits per channel (0 = 8, 1 = 16, 2 = float), and whether it has alpha and color
+#[derive(Copy, Clone)]
+struct Fidelity {
+    depth: u8,
+    alpha: bool,
+    color: bool,
+}
+
+const fn fidelity(format: ColorFormatName) -> Fidelity {
+    match format {
+        ColorFormatName::Gray8 => Fidelity { depth: 0, alpha: false, color: false },
+        ColorFormatName::Gray16 => Fidelity { depth: 1, alpha: false, color: false },
+        ColorFormatName::Grayf => Fidelity { depth: 2, alpha: false, color: false },
+        ColorFormatName::RGB | ColorFormatName::SRGB | ColorFormatName::YCbCr => Fidelity { depth: 0, alpha: false, color: true },
+        ColorFormatName::RGB48 => Fidelity { depth: 1, alpha: false, color: true },
+        ColorFormatName::RGBA => Fidelity { depth: 0, alpha: true, color: true },
+        ColorFormatName::RGBA64 => Fidelity { depth: 1, alpha: true, color: true },
+        ColorFormatName::RGBAf => Fidelity { depth: 2, alpha: true, color: true },
+        ColorFormatName::RGBf | ColorFormatName::SRGBf |
+        ColorFormatName::HSV | ColorFormatName::HSL |
+        ColorFormatName::CIEXYZ | ColorFormatName::CIELab |
+        ColorFormatName::CIELch | ColorFormatName::CIELuv |
+        ColorFormatName::CIELchUv | ColorFormatName::CIExyY |
+        ColorFormatName::Oklab | ColorFormatName::Oklch |
+        ColorFormatName::CMYK | ColorFormatName::HWB => Fidelity { depth: 2, alpha: false, color: true },
+    }
+}
+
+//The cost of passing through `via` on the way from `from` to `to`
+const fn intermediate_cost(from: ColorFormatName, to: ColorFormatName, via: ColorFormatName) -> u32 {
+    let (from, to, via) = (fidelity(from), fidelity(to), fidelity(via));
+    let wanted_depth = if from.depth > to.depth { from.depth } else { to.depth };
+
+    let mut cost = HOP_COST + (2 - via.depth) as u32;
+    if via.depth < wanted_depth || (from.alpha && to.alpha && !via.alpha) || (from.color && to.color && !via.color) {
+        cost += LOSSY_COST;
+    }
+
+    cost
+}
+
+//Dijkstra's algorithm from `from`, stopping at `to`. Gives the first format along the way.
+const fn first_hop(from: usize, to: usize) -> u8 {
+    let mut cost = [u32::MAX; FORMAT_COUNT];
+    let mut first = [NO_ROUTE; FORMAT_COUNT];
+    let mut done = [false; FORMAT_COUNT];
+    cost[from] = 0;
+
+    loop {
+        let mut current = FORMAT_COUNT;
+        let mut i = 0;
+        while i < FORMAT_COUNT {
+            if !done[i] && cost[i] != u32::MAX && (current == FORMAT_COUNT || cost[i] < cost[current]) {
+                current = i;
+            }
+            i += 1;
+        }
+
+        if current == FORMAT_COUNT || current == to {
+            return first[to];
+        }
+        done[current] = true;
+
+        let mut e = 0;
+        while e < EDGES.len() {
+            let (a, b) = (EDGES[e].0 as usize, EDGES[e].1 as usize);
+            if a == current && !done[b] {
+                let edge_cost = if b == to {
+                    HOP_COST
+                } else {
+                    intermediate_cost(FORMATS[from], FORMATS[to], FORMATS[b])
+                };
+
+                if cost[current] + edge_cost < cost[b] {
+                    cost[b] = cost[current] + edge_cost;
+                    first[b] = if current == from { b as u8 } else { first[current] };
+                }
+            }
+            e += 1;
+        }
+    }
+}
+
+const fn route_table() -> [[u8; FORMAT_COUNT]; FORMAT_COUNT] {
+    let mut table = [[NO_ROUTE; FORMAT_COUNT]; FORMAT_COUNT];
+
+    let mut from = 0;
+    while from < FORMAT_COUNT {
+        let mut to = 0;
+        while to < FORMAT_COUNT {
+            if from != to {
+                table[from][to] = first_hop(from, to);
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+
+    table
+}
+
+//ROUTES[from][to] is the format to convert to next, on the way from `from` to `to`
+const ROUTES: [[u8; FORMAT_COUNT]; FORMAT_COUNT] = route_table();
+
+fn next_hop(from: ColorFormatName, to: ColorFormatName) -> ColorFormatName {
+    let hop = ROUTES[from as usize][to as usize];
+    assert!(hop != NO_ROUTE, "no conversion from {:?} to {:?}", from, to);
+    FORMATS[hop as usize]
+}
+
+impl Color {
+    //Follows the conversion graph one From impl at a time, so HSV -> Lab will do HSV -> RGBf -> XYZ -> Lab
+    pub fn convert(from: Color, to: ColorFormatName) -> Color {
+        let mut conversion = from;
+        while conversion.get_format() != to {
+            conversion = step(conversion, next_hop(conversion.get_format(), to));
+        }
+
+        conversion
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_match_color_format_name_order() {
+        for (i, format) in FORMATS.iter().enumerate() {
+            assert_eq!(*format as usize, i);
+            assert_eq!(format.tag() as usize, i);
+        }
+    }
+
+    #[test]
+    fn every_format_reaches_every_other() {
+        for from in FORMATS {
+            for to in FORMATS {
+                //Walk the route without converting, so a cycle fails here instead of hanging
+                let mut current = from;
+                let mut steps = 0;
+                while current != to {
+                    let hop = ROUTES[current as usize][to as usize];
+                    assert_ne!(hop, NO_ROUTE, "no conversion from {:?} to {:?}", from, to);
+
+                    let next = FORMATS[hop as usize];
+                    assert!(EDGES.contains(&(current, next)), "{:?} -> {:?} isn't an edge", current, next);
+
+                    current = next;
+                    steps += 1;
+                    assert!(steps < FORMAT_COUNT, "the route from {:?} to {:?} loops", from, to);
+                }
+
+                let color = Color::convert(Color::convert(Color::SRGB(Srgb::new(200, 100, 50)), from), to);
+                assert_eq!(color.get_format(), to);
+            }
+        }
+    }
+
+    #[test]
+    fn routes_keep_precision() {
+        //Through a wider gray, not the 8-bit Rgb
+        assert_ne!(next_hop(ColorFormatName::Gray8, ColorFormatName::RGB48), ColorFormatName::RGB);
+        //Through float, not the 8-bit Rgb
+        assert_ne!(next_hop(ColorFormatName::SRGB, ColorFormatName::Oklab), ColorFormatName::RGB);
+    }
+}
@@ -0,0 +1,347 @@
+use super::color::*;
+use super::image::*;
+
+/*  Color vision deficiency (color blindness) simulation, for checking that a theme still works for
+ *  people who can't tell some colors apart, and daltonization, which shifts colors to make up for it.
+ *  Both work in linear RGB. Machado et al. (2009) model anomalous trichromacy at any severity;
+ *  Brettel et al. (1997) model full dichromacy by projecting onto two half-planes, and are blended
+ *  with the original color for lower severities.
+ */
+
+/// Which kind of cone is missing or shifted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Deficiency {
+    /// Long-wavelength (red) cones
+    Protan,
+    /// Medium-wavelength (green) cones- the most common
+    Deutan,
+    /// Short-wavelength (blue) cones
+    Tritan,
+}
+
+/// Which model to simulate with
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum CvdModel {
+    /// Machado, Oliveira and Fernandes (2009). Made for any severity.
+    #[default]
+    Machado,
+    /// Brettel, Viénot and Mollon (1997). Made for dichromacy, and the more accurate one for tritans.
+    Brettel,
+}
+
+/// A color vision deficiency to simulate or correct for
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CvdSimulation {
+    pub deficiency: Deficiency,
+    /// 0 is normal vision, 1 is dichromacy (protanopia, deuteranopia or tritanopia)
+    pub severity: f32,
+    pub model: CvdModel,
+}
+
+type Matrix = [[f32; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0f32, 0.0f32, 0.0f32], [0.0f32, 1.0f32, 0.0f32], [0.0f32, 0.0f32, 1.0f32]];
+
+//Machado et al.'s matrices for severities 0.1 to 1.0, in steps of 0.1. Severity 0 is the identity.
+const MACHADO_PROTAN: [Matrix; 10] = [
+    [[0.856167f32, 0.182038f32, -0.038205f32], [0.029342f32, 0.955115f32, 0.015544f32], [-0.002880f32, -0.001563f32, 1.004443f32]],
+    [[0.734766f32, 0.334872f32, -0.069637f32], [0.051840f32, 0.919198f32, 0.028963f32], [-0.004928f32, -0.004209f32, 1.009137f32]],
+    [[0.630323f32, 0.465641f32, -0.095964f32], [0.069181f32, 0.890046f32, 0.040773f32], [-0.006308f32, -0.007724f32, 1.014032f32]],
+    [[0.539009f32, 0.579343f32, -0.118352f32], [0.082546f32, 0.866121f32, 0.051332f32], [-0.007136f32, -0.011959f32, 1.019095f32]],
+    [[0.458064f32, 0.679578f32, -0.137642f32], [0.092785f32, 0.846313f32, 0.060902f32], [-0.007494f32, -0.016807f32, 1.024301f32]],
+    [[0.385450f32, 0.769005f32, -0.154455f32], [0.100526f32, 0.829802f32, 0.069673f32], [-0.007442f32, -0.022190f32, 1.029632f32]],
+    [[0.319627f32, 0.849633f32, -0.169261f32], [0.106241f32, 0.815969f32, 0.077790f32], [-0.007025f32, -0.028051f32, 1.035076f32]],
+    [[0.259411f32, 0.923008f32, -0.182420f32], [0.110296f32, 0.804340f32, 0.085364f32], [-0.006276f32, -0.034346f32, 1.040622f32]],
+    [[0.203876f32, 0.990338f32, -0.194214f32], [0.112975f32, 0.794542f32, 0.092483f32], [-0.005222f32, -0.041043f32, 1.046265f32]],
+    [[0.152286f32, 1.052583f32, -0.204868f32], [0.114503f32, 0.786281f32, 0.099216f32], [-0.003882f32, -0.048116f32, 1.051998f32]],
+];
+
+const MACHADO_DEUTAN: [Matrix; 10] = [
+    [[0.866435f32, 0.177704f32, -0.044139f32], [0.049567f32, 0.939063f32, 0.011370f32], [-0.003453f32, 0.007233f32, 0.996220f32]],
+    [[0.760729f32, 0.319078f32, -0.079807f32], [0.090568f32, 0.889315f32, 0.020117f32], [-0.006027f32, 0.013325f32, 0.992702f32]],
+    [[0.675425f32, 0.433850f32, -0.109275f32], [0.125303f32, 0.847755f32, 0.026942f32], [-0.007950f32, 0.018572f32, 0.989378f32]],
+    [[0.605511f32, 0.528560f32, -0.134071f32], [0.155318f32, 0.812366f32, 0.032316f32], [-0.009376f32, 0.023176f32, 0.986200f32]],
+    [[0.547494f32, 0.607765f32, -0.155259f32], [0.181692f32, 0.781742f32, 0.036566f32], [-0.010410f32, 0.027275f32, 0.983136f32]],
+    [[0.498864f32, 0.674741f32, -0.173604f32], [0.205199f32, 0.754872f32, 0.039929f32], [-0.011131f32, 0.030969f32, 0.980162f32]],
+    [[0.457771f32, 0.731899f32, -0.189670f32], [0.226409f32, 0.731012f32, 0.042579f32], [-0.011595f32, 0.034333f32, 0.977261f32]],
+    [[0.422823f32, 0.781057f32, -0.203881f32], [0.245752f32, 0.709602f32, 0.044646f32], [-0.011843f32, 0.037423f32, 0.974421f32]],
+    [[0.392952f32, 0.823610f32, -0.216562f32], [0.263559f32, 0.690210f32, 0.046232f32], [-0.011910f32, 0.040281f32, 0.971630f32]],
+    [[0.367322f32, 0.860646f32, -0.227968f32], [0.280085f32, 0.672501f32, 0.047413f32], [-0.011820f32, 0.042940f32, 0.968881f32]],
+];
+
+const MACHADO_TRITAN: [Matrix; 10] = [
+    [[0.926670f32, 0.092514f32, -0.019184f32], [0.021191f32, 0.964503f32, 0.014306f32], [0.008437f32, 0.054813f32, 0.936750f32]],
+    [[0.895720f32, 0.133330f32, -0.029050f32], [0.029997f32, 0.945400f32, 0.024603f32], [0.013027f32, 0.104707f32, 0.882266f32]],
+    [[0.905871f32, 0.127791f32, -0.033662f32], [0.026856f32, 0.941251f32, 0.031893f32], [0.013410f32, 0.148296f32, 0.838294f32]],
+    [[0.948035f32, 0.089490f32, -0.037526f32], [0.014364f32, 0.946792f32, 0.038844f32], [0.010853f32, 0.193991f32, 0.795156f32]],
+    [[1.017277f32, 0.027029f32, -0.044306f32], [-0.006113f32, 0.958479f32, 0.047634f32], [0.006379f32, 0.248708f32, 0.744913f32]],
+    [[1.104996f32, -0.046633f32, -0.058363f32], [-0.032137f32, 0.971635f32, 0.060503f32], [0.001336f32, 0.317922f32, 0.680742f32]],
+    [[1.193214f32, -0.109812f32, -0.083402f32], [-0.058496f32, 0.979410f32, 0.079086f32], [-0.002346f32, 0.403492f32, 0.598854f32]],
+    [[1.257728f32, -0.139648f32, -0.118081f32], [-0.078003f32, 0.975409f32, 0.102594f32], [-0.003316f32, 0.501214f32, 0.502102f32]],
+    [[1.278864f32, -0.125333f32, -0.153531f32], [-0.084748f32, 0.957674f32, 0.127074f32], [-0.000989f32, 0.601151f32, 0.399838f32]],
+    [[1.255528f32, -0.076749f32, -0.178779f32], [-0.078411f32, 0.930809f32, 0.147602f32], [0.004733f32, 0.691367f32, 0.303900f32]],
+];
+
+//Brettel's two half-plane projections, folded into linear sRGB (as in Viénot et al. and DaltonLens),
+//with the normal of the plane that separates them. Which side a color is on picks the projection.
+struct BrettelProjection {
+    positive: Matrix,
+    negative: Matrix,
+    normal: [f32; 3],
+}
+
+const BRETTEL_PROTAN: BrettelProjection = BrettelProjection {
+    positive: [[0.14510f32, 1.20165f32, -0.34675f32], [0.10447f32, 0.85316f32, 0.04237f32], [0.00429f32, -0.00603f32, 1.00174f32]],
+    negative: [[0.14115f32, 1.16782f32, -0.30897f32], [0.10495f32, 0.85730f32, 0.03776f32], [0.00431f32, -0.00586f32, 1.00155f32]],
+    normal: [0.00048f32, 0.00416f32, -0.00464f32],
+};
+
+const BRETTEL_DEUTAN: BrettelProjection = BrettelProjection {
+    positive: [[0.36198f32, 0.86755f32, -0.22953f32], [0.26099f32, 0.64512f32, 0.09389f32], [-0.01975f32, 0.02686f32, 0.99289f32]],
+    negative: [[0.37009f32, 0.88540f32, -0.25549f32], [0.25767f32, 0.63782f32, 0.10451f32], [-0.01950f32, 0.02741f32, 0.99209f32]],
+    normal: [-0.00293f32, -0.00645f32, 0.00938f32],
+};
+
+const BRETTEL_TRITAN: BrettelProjection = BrettelProjection {
+    positive: [[1.01354f32, 0.14268f32, -0.15622f32], [-0.01181f32, 0.87561f32, 0.13619f32], [0.07707f32, 0.81208f32, 0.11085f32]],
+    negative: [[0.93337f32, 0.19999f32, -0.13336f32], [0.05809f32, 0.82565f32, 0.11626f32], [-0.37923f32, 1.13825f32, 0.24098f32]],
+    normal: [0.03960f32, -0.02831f32, -0.01129f32],
+};
+
+//Fidaner et al.'s daltonization: the error a dichromat can't see is moved into the channels they can
+const DALTONIZE_RED_GREEN: Matrix = [[0.0f32, 0.0f32, 0.0f32], [0.7f32, 1.0f32, 0.0f32], [0.7f32, 0.0f32, 1.0f32]];
+const DALTONIZE_BLUE_YELLOW: Matrix = [[1.0f32, 0.0f32, 0.7f32], [0.0f32, 1.0f32, 0.7f32], [0.0f32, 0.0f32, 0.0f32]];
+
+fn multiply(m: &Matrix, rgb: [f32; 3]) -> [f32; 3] {
+    [m[0][0] * rgb[0] + m[0][1] * rgb[1] + m[0][2] * rgb[2],
+     m[1][0] * rgb[0] + m[1][1] * rgb[1] + m[1][2] * rgb[2],
+     m[2][0] * rgb[0] + m[2][1] * rgb[1] + m[2][2] * rgb[2]]
+}
+
+fn lerp(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+fn clamp_unit(rgb: [f32; 3]) -> [f32; 3] {
+    [rgb[0].clamp(0.0f32, 1.0f32), rgb[1].clamp(0.0f32, 1.0f32), rgb[2].clamp(0.0f32, 1.0f32)]
+}
+
+fn to_rgbaf(color: Color) -> RgbaF {
+    match Color::convert(color, ColorFormatName::RGBAf) {
+        Color::RGBAf(rgbaf) => rgbaf,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+#[allow(dead_code)]
+impl CvdSimulation {
+    /// Anomalous trichromacy at severity in [0, 1], with the Machado model
+    pub fn new(deficiency: Deficiency, severity: f32) -> CvdSimulation {
+        CvdSimulation {
+            deficiency,
+            severity: severity.clamp(0.0f32, 1.0f32),
+            model: CvdModel::default(),
+        }
+    }
+
+    /// Full dichromacy, with the Brettel model
+    pub fn dichromacy(deficiency: Deficiency) -> CvdSimulation {
+        CvdSimulation::new(deficiency, 1.0f32).with_model(CvdModel::Brettel)
+    }
+
+    pub fn with_model(mut self, model: CvdModel) -> CvdSimulation {
+        self.model = model;
+        self
+    }
+
+    /// How linear RGB looks with the deficiency. The result can be slightly outside [0, 1].
+    pub fn simulate_rgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let severity = self.severity.clamp(0.0f32, 1.0f32);
+
+        match self.model {
+            CvdModel::Machado => {
+                let table = match self.deficiency {
+                    Deficiency::Protan => &MACHADO_PROTAN,
+                    Deficiency::Deutan => &MACHADO_DEUTAN,
+                    Deficiency::Tritan => &MACHADO_TRITAN,
+                };
+
+                //Between the two nearest tabulated severities
+                let position = severity * 10.0f32;
+                let index = (position.floor() as usize).min(9);
+                let lower = if index == 0 { &IDENTITY } else { &table[index - 1] };
+                lerp(multiply(lower, rgb), multiply(&table[index], rgb), position - index as f32)
+            },
+            CvdModel::Brettel => {
+                let projection = match self.deficiency {
+                    Deficiency::Protan => &BRETTEL_PROTAN,
+                    Deficiency::Deutan => &BRETTEL_DEUTAN,
+                    Deficiency::Tritan => &BRETTEL_TRITAN,
+                };
+
+                let side = projection.normal[0] * rgb[0] + projection.normal[1] * rgb[1] + projection.normal[2] * rgb[2];
+                let dichromat = if side >= 0.0f32 {
+                    multiply(&projection.positive, rgb)
+                } else {
+                    multiply(&projection.negative, rgb)
+                };
+
+                lerp(rgb, dichromat, severity)
+            },
+        }
+    }
+
+    /// Shifts linear RGB so that what the deficiency hides shows up as a difference it can see.
+    /// Colors it already sees correctly are left alone.
+    pub fn daltonize_rgb(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let simulated = self.simulate_rgb(rgb);
+        let error = [rgb[0] - simulated[0], rgb[1] - simulated[1], rgb[2] - simulated[2]];
+
+        let shift = match self.deficiency {
+            Deficiency::Protan | Deficiency::Deutan => multiply(&DALTONIZE_RED_GREEN, error),
+            Deficiency::Tritan => multiply(&DALTONIZE_BLUE_YELLOW, error),
+        };
+
+        clamp_unit([rgb[0] + shift[0], rgb[1] + shift[1], rgb[2] + shift[2]])
+    }
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// How the color looks with a color vision deficiency, in the color's own format. Alpha is kept.
+    pub fn simulate_cvd(&self, simulation: &CvdSimulation) -> Color {
+        let rgbaf = to_rgbaf(*self);
+        let rgb = clamp_unit(simulation.simulate_rgb([rgbaf.r, rgbaf.g, rgbaf.b]));
+        Color::convert(Color::RGBAf(RgbaF::new(rgb[0], rgb[1], rgb[2], rgbaf.a)), self.get_format())
+    }
+
+    /// The color corrected for a color vision deficiency, in the color's own format. Alpha is kept.
+    pub fn daltonize(&self, simulation: &CvdSimulation) -> Color {
+        let rgbaf = to_rgbaf(*self);
+        let rgb = simulation.daltonize_rgb([rgbaf.r, rgbaf.g, rgbaf.b]);
+        Color::convert(Color::RGBAf(RgbaF::new(rgb[0], rgb[1], rgb[2], rgbaf.a)), self.get_format())
+    }
+}
+
+//Runs every pixel through f in linear RGB. The result is tightly packed, like `convert`.
+fn filter_image<C: BulkConvert, F: Fn([f32; 3]) -> [f32; 3]>(image: &ImageBuffer<C>, f: F) -> ImageBuffer<C> {
+    let mut filtered = image.convert::<C>();
+    let mut linear = vec![[0.0f32; 4]; image.width() as usize];
+
+    for row in filtered.rows_mut() {
+        C::decode(row, &mut linear);
+        for rgba in linear.iter_mut() {
+            let rgb = f([rgba[0], rgba[1], rgba[2]]);
+            *rgba = [rgb[0], rgb[1], rgb[2], rgba[3]];
+        }
+        C::encode(&linear, row);
+    }
+
+    filtered
+}
+
+#[allow(dead_code)]
+impl<C: BulkConvert> ImageBuffer<C> {
+    /// The whole image as seen with a color vision deficiency- run a rendered frame through it to review a screen
+    pub fn simulate_cvd(&self, simulation: &CvdSimulation) -> ImageBuffer<C> {
+        filter_image(self, |rgb| clamp_unit(simulation.simulate_rgb(rgb)))
+    }
+
+    /// The whole image corrected for a color vision deficiency
+    pub fn daltonize(&self, simulation: &CvdSimulation) -> ImageBuffer<C> {
+        filter_image(self, |rgb| simulation.daltonize_rgb(rgb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFICIENCIES: [Deficiency; 3] = [Deficiency::Protan, Deficiency::Deutan, Deficiency::Tritan];
+
+    fn linear(r: u8, g: u8, b: u8) -> [f32; 3] {
+        let rgbaf = to_rgbaf(Color::SRGB(Srgb::new(r, g, b)));
+        [rgbaf.r, rgbaf.g, rgbaf.b]
+    }
+
+    fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn grays_look_the_same_to_everyone() {
+        for deficiency in DEFICIENCIES {
+            for model in [CvdModel::Machado, CvdModel::Brettel] {
+                for severity in [0.0f32, 0.25f32, 0.5f32, 0.95f32, 1.0f32] {
+                    let simulation = CvdSimulation::new(deficiency, severity).with_model(model);
+                    for gray in [0.0f32, 0.2f32, 1.0f32] {
+                        let simulated = simulation.simulate_rgb([gray, gray, gray]);
+                        assert!(distance(simulated, [gray, gray, gray]) < 1e-4f32,
+                                "{:?} at {} turned {} into {:?}", simulation.model, severity, gray, simulated);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn no_severity_is_normal_vision() {
+        let rgb = [0.8f32, 0.1f32, 0.3f32];
+        for deficiency in DEFICIENCIES {
+            for model in [CvdModel::Machado, CvdModel::Brettel] {
+                let simulation = CvdSimulation::new(deficiency, 0.0f32).with_model(model);
+                assert!(distance(simulation.simulate_rgb(rgb), rgb) < 1e-6f32);
+                assert!(distance(simulation.daltonize_rgb(rgb), rgb) < 1e-6f32);
+            }
+        }
+    }
+
+    #[test]
+    fn confusion_colors_become_hard_to_tell_apart() {
+        let (red, olive) = (linear(0xc0, 0x20, 0x20), linear(0x5a, 0x60, 0x00));
+        let (blue, teal) = (linear(0x40, 0x40, 0xff), linear(0x20, 0x80, 0x80));
+
+        for (deficiency, a, b) in [(Deficiency::Protan, red, olive), (Deficiency::Deutan, red, olive),
+                                   (Deficiency::Tritan, blue, teal)] {
+            let normal = distance(a, b);
+            for simulation in [CvdSimulation::new(deficiency, 1.0f32), CvdSimulation::dichromacy(deficiency)] {
+                let seen = distance(simulation.simulate_rgb(a), simulation.simulate_rgb(b));
+                assert!(seen < normal * 0.25f32, "{:?} still sees {} of {}", simulation, seen, normal);
+            }
+
+            //Milder deficiencies see more of the difference
+            let (mild, severe) = (CvdSimulation::new(deficiency, 0.3f32), CvdSimulation::new(deficiency, 1.0f32));
+            assert!(distance(mild.simulate_rgb(a), mild.simulate_rgb(b)) > distance(severe.simulate_rgb(a), severe.simulate_rgb(b)));
+        }
+    }
+
+    #[test]
+    fn daltonizing_brings_back_the_difference() {
+        let (red, green) = (linear(0xd0, 0x30, 0x30), linear(0x6f, 0x8f, 0x00));
+        let simulation = CvdSimulation::dichromacy(Deficiency::Deutan);
+
+        let before = distance(simulation.simulate_rgb(red), simulation.simulate_rgb(green));
+        let (red, green) = (simulation.daltonize_rgb(red), simulation.daltonize_rgb(green));
+        let after = distance(simulation.simulate_rgb(red), simulation.simulate_rgb(green));
+        assert!(after > before, "{} is no better than {}", after, before);
+    }
+
+    #[test]
+    fn images_match_single_colors() {
+        let simulation = CvdSimulation::new(Deficiency::Protan, 0.6f32);
+        let pixel = RgbaF::new(0.7f32, 0.2f32, 0.1f32, 0.5f32);
+        let image = ImageBuffer::filled(3, 2, pixel);
+
+        let expected = match Color::RGBAf(pixel).simulate_cvd(&simulation) {
+            Color::RGBAf(rgbaf) => rgbaf,
+            _ => unreachable!("simulate_cvd keeps the format"),
+        };
+        assert!(image.simulate_cvd(&simulation).pixels().iter().all(|p| *p == expected));
+
+        let expected = match Color::RGBAf(pixel).daltonize(&simulation) {
+            Color::RGBAf(rgbaf) => rgbaf,
+            _ => unreachable!("daltonize keeps the format"),
+        };
+        assert!(image.daltonize(&simulation).pixels().iter().all(|p| *p == expected));
+    }
+}
@@ -0,0 +1,258 @@
+use super::color::*;
+
+/// How to measure the perceived difference between two colors.
+/// A difference of about 1 is the smallest most people can notice side by side- for Oklab it's about 0.02.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeltaEMethod {
+    /// Euclidean distance in CIELab. Cheap, but overstates differences in saturated colors.
+    Cie76,
+    /// CIE76 corrected for chroma and hue, with the graphic arts weights. Not symmetric- `a` is the reference.
+    Cie94,
+    /// The current CIE recommendation. Expensive, but the most accurate of these.
+    Ciede2000,
+    /// Euclidean distance in Oklab
+    Oklab,
+}
+
+fn to_lab(color: Color) -> CieLab {
+    match Color::convert(color, ColorFormatName::CIELab) {
+        Color::CIELab(lab) => lab,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn to_oklab(color: Color) -> Oklab {
+    match Color::convert(color, ColorFormatName::Oklab) {
+        Color::Oklab(oklab) => oklab,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// The perceived difference between a and b. Both are converted to whatever space the method needs.
+    pub fn delta_e(a: Color, b: Color, method: DeltaEMethod) -> f32 {
+        match method {
+            DeltaEMethod::Cie76 => {
+                let (lab_a, lab_b) = (to_lab(a), to_lab(b));
+                ((lab_a.l - lab_b.l).powi(2) + (lab_a.a - lab_b.a).powi(2) + (lab_a.b - lab_b.b).powi(2)).sqrt()
+            },
+            DeltaEMethod::Cie94 => {
+                delta_e_94(to_lab(a), to_lab(b))
+            },
+            DeltaEMethod::Ciede2000 => {
+                delta_e_2000(to_lab(a), to_lab(b))
+            },
+            DeltaEMethod::Oklab => {
+                let (ok_a, ok_b) = (to_oklab(a), to_oklab(b));
+                ((ok_a.l - ok_b.l).powi(2) + (ok_a.a - ok_b.a).powi(2) + (ok_a.b - ok_b.b).powi(2)).sqrt()
+            },
+        }
+    }
+}
+
+fn delta_e_94(reference: CieLab, sample: CieLab) -> f32 {
+    //Graphic arts weights. Textiles use k_l = 2, k_1 = 0.048, k_2 = 0.014
+    let (k_l, k_1, k_2) = (1.0f32, 0.045f32, 0.015f32);
+
+    let c_1 = (reference.a * reference.a + reference.b * reference.b).sqrt();
+    let c_2 = (sample.a * sample.a + sample.b * sample.b).sqrt();
+
+    let delta_l = reference.l - sample.l;
+    let delta_c = c_1 - c_2;
+    let delta_a = reference.a - sample.a;
+    let delta_b = reference.b - sample.b;
+
+    //Rounding can push this slightly below zero for colors of the same hue
+    let delta_h_sq = (delta_a * delta_a + delta_b * delta_b - delta_c * delta_c).max(0.0f32);
+
+    let s_l = 1.0f32;
+    let s_c = 1.0f32 + k_1 * c_1;
+    let s_h = 1.0f32 + k_2 * c_1;
+
+    ((delta_l / (k_l * s_l)).powi(2) + (delta_c / s_c).powi(2) + delta_h_sq / (s_h * s_h)).sqrt()
+}
+
+//Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula: Implementation Notes,
+//Supplementary Test Data, and Mathematical Observations" (2005).
+//Done in f64- the hue terms lose too much in f32 to match the reference data.
+fn delta_e_2000(lab_1: CieLab, lab_2: CieLab) -> f32 {
+    let (l_1, a_1, b_1) = (lab_1.l as f64, lab_1.a as f64, lab_1.b as f64);
+    let (l_2, a_2, b_2) = (lab_2.l as f64, lab_2.a as f64, lab_2.b as f64);
+
+    let pow_25_7 = 25.0f64.powi(7);
+
+    let c_1 = (a_1 * a_1 + b_1 * b_1).sqrt();
+    let c_2 = (a_2 * a_2 + b_2 * b_2).sqrt();
+    let c_bar = (c_1 + c_2) / 2.0f64;
+
+    let g = 0.5f64 * (1.0f64 - (c_bar.powi(7) / (c_bar.powi(7) + pow_25_7)).sqrt());
+
+    let a_1_prime = (1.0f64 + g) * a_1;
+    let a_2_prime = (1.0f64 + g) * a_2;
+
+    let c_1_prime = (a_1_prime * a_1_prime + b_1 * b_1).sqrt();
+    let c_2_prime = (a_2_prime * a_2_prime + b_2 * b_2).sqrt();
+
+    let hue = |b: f64, a_prime: f64| -> f64 {
+        if b == 0.0f64 && a_prime == 0.0f64 {
+            0.0f64
+        } else {
+            b.atan2(a_prime).to_degrees().rem_euclid(360.0f64)
+        }
+    };
+    let h_1_prime = hue(b_1, a_1_prime);
+    let h_2_prime = hue(b_2, a_2_prime);
+
+    let chroma_product = c_1_prime * c_2_prime;
+
+    let delta_l_prime = l_2 - l_1;
+    let delta_c_prime = c_2_prime - c_1_prime;
+
+    let delta_h_prime = if chroma_product == 0.0f64 {
+        0.0f64
+    } else {
+        let diff = h_2_prime - h_1_prime;
+        if diff.abs() <= 180.0f64 {
+            diff
+        } else if diff > 180.0f64 {
+            diff - 360.0f64
+        } else {
+            diff + 360.0f64
+        }
+    };
+    let delta_big_h_prime = 2.0f64 * chroma_product.sqrt() * (delta_h_prime.to_radians() / 2.0f64).sin();
+
+    let l_bar_prime = (l_1 + l_2) / 2.0f64;
+    let c_bar_prime = (c_1_prime + c_2_prime) / 2.0f64;
+
+    let h_sum = h_1_prime + h_2_prime;
+    let h_bar_prime = if chroma_product == 0.0f64 {
+        h_sum
+    } else if (h_1_prime - h_2_prime).abs() <= 180.0f64 {
+        h_sum / 2.0f64
+    } else if h_sum < 360.0f64 {
+        (h_sum + 360.0f64) / 2.0f64
+    } else {
+        (h_sum - 360.0f64) / 2.0f64
+    };
+
+    let t = 1.0f64
+        - 0.17f64 * (h_bar_prime - 30.0f64).to_radians().cos()
+        + 0.24f64 * (2.0f64 * h_bar_prime).to_radians().cos()
+        + 0.32f64 * (3.0f64 * h_bar_prime + 6.0f64).to_radians().cos()
+        - 0.20f64 * (4.0f64 * h_bar_prime - 63.0f64).to_radians().cos();
+
+    let delta_theta = 30.0f64 * (-((h_bar_prime - 275.0f64) / 25.0f64).powi(2)).exp();
+    let r_c = 2.0f64 * (c_bar_prime.powi(7) / (c_bar_prime.powi(7) + pow_25_7)).sqrt();
+
+    let l_offset_sq = (l_bar_prime - 50.0f64).powi(2);
+    let s_l = 1.0f64 + (0.015f64 * l_offset_sq) / (20.0f64 + l_offset_sq).sqrt();
+    let s_c = 1.0f64 + 0.045f64 * c_bar_prime;
+    let s_h = 1.0f64 + 0.015f64 * c_bar_prime * t;
+
+    let r_t = -(2.0f64 * delta_theta).to_radians().sin() * r_c;
+
+    let l_term = delta_l_prime / s_l;
+    let c_term = delta_c_prime / s_c;
+    let h_term = delta_big_h_prime / s_h;
+
+    (l_term * l_term + c_term * c_term + h_term * h_term + r_t * c_term * h_term).sqrt() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lab(l: f32, a: f32, b: f32) -> Color {
+        Color::CIELab(CieLab::new(l, a, b))
+    }
+
+    type LabPair = ((f32, f32, f32), (f32, f32, f32), f32);
+
+    //The full test data set from Sharma, Wu and Dalal (2005), Table 1
+    const SHARMA_DATA: [LabPair; 34] = [
+        ((50.0000, 2.6772, -79.7751), (50.0000, 0.0000, -82.7485), 2.0425),
+        ((50.0000, 3.1571, -77.2803), (50.0000, 0.0000, -82.7485), 2.8615),
+        ((50.0000, 2.8361, -74.0200), (50.0000, 0.0000, -82.7485), 3.4412),
+        ((50.0000, -1.3802, -84.2814), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, -1.1848, -84.8006), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, -0.9009, -85.5211), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, 0.0000, 0.0000), (50.0000, -1.0000, 2.0000), 2.3669),
+        ((50.0000, -1.0000, 2.0000), (50.0000, 0.0000, 0.0000), 2.3669),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0009), 7.1792),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0010), 7.1792),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0011), 7.2195),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0012), 7.2195),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0009, -2.4900), 4.8045),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0010, -2.4900), 4.8045),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0011, -2.4900), 4.7461),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 0.0000, -2.5000), 4.3065),
+        ((50.0000, 2.5000, 0.0000), (73.0000, 25.0000, -18.0000), 27.1492),
+        ((50.0000, 2.5000, 0.0000), (61.0000, -5.0000, 29.0000), 22.8977),
+        ((50.0000, 2.5000, 0.0000), (56.0000, -27.0000, -3.0000), 31.9030),
+        ((50.0000, 2.5000, 0.0000), (58.0000, 24.0000, 15.0000), 19.4535),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.1736, 0.5854), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.2972, 0.0000), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 1.8634, 0.5757), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.2592, 0.3350), 1.0000),
+        ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+        ((63.0109, -31.0961, -5.8663), (62.8187, -29.7946, -4.0864), 1.2630),
+        ((61.2901, 3.7196, -5.3901), (61.4292, 2.2480, -4.9620), 1.8731),
+        ((35.0831, -44.1164, 3.7933), (35.0232, -40.0716, 1.5901), 1.8645),
+        ((22.7233, 20.0904, -46.6940), (23.0331, 14.9730, -42.5619), 2.0373),
+        ((36.4612, 47.8580, 18.3852), (36.2715, 50.5065, 21.2231), 1.4146),
+        ((90.8027, -2.0831, 1.4410), (91.1528, -1.6435, 0.0447), 1.4441),
+        ((90.9257, -0.5406, -0.9208), (88.6381, -0.8985, -0.7239), 1.5381),
+        ((6.7747, -0.2908, -2.4247), (5.8714, -0.0985, -2.2286), 0.6377),
+        ((2.0776, 0.0795, -1.1350), (0.9033, -0.0636, -0.5514), 0.9082),
+    ];
+
+    #[test]
+    fn ciede2000_matches_reference_data() {
+        for (i, &(a, b, expected)) in SHARMA_DATA.iter().enumerate() {
+            let a = lab(a.0, a.1, a.2);
+            let b = lab(b.0, b.1, b.2);
+
+            let forward = Color::delta_e(a, b, DeltaEMethod::Ciede2000);
+            let backward = Color::delta_e(b, a, DeltaEMethod::Ciede2000);
+
+            assert!((forward - expected).abs() < 1e-4, "pair {}: got {}, expected {}", i + 1, forward, expected);
+            assert!((backward - expected).abs() < 1e-4, "pair {} reversed: got {}, expected {}", i + 1, backward, expected);
+        }
+    }
+
+    #[test]
+    fn cie76_is_euclidean_lab_distance() {
+        let d = Color::delta_e(lab(50.0, 0.0, 0.0), lab(53.0, 4.0, 0.0), DeltaEMethod::Cie76);
+        assert!((d - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cie94_matches_known_value() {
+        //Reference pair 17 from the CIEDE2000 data, checked against an independent implementation
+        let d = Color::delta_e(lab(50.0, 2.5, 0.0), lab(73.0, 25.0, -18.0), DeltaEMethod::Cie94);
+        assert!((d - 34.6892).abs() < 1e-3, "got {}", d);
+    }
+
+    #[test]
+    fn identical_colors_have_no_difference() {
+        let colors = [Color::SRGB(Srgb::new(12, 200, 99)), Color::HSV(Hsv::new(200.0, 0.5, 0.5)), lab(40.0, -20.0, 30.0)];
+        let methods = [DeltaEMethod::Cie76, DeltaEMethod::Cie94, DeltaEMethod::Ciede2000, DeltaEMethod::Oklab];
+
+        for &c in colors.iter() {
+            for &m in methods.iter() {
+                assert!(Color::delta_e(c, c, m) < 1e-4, "{:?} {:?}", c, m);
+            }
+        }
+    }
+
+    #[test]
+    fn inputs_are_converted_automatically() {
+        let srgb = Color::SRGB(Srgb::new(255, 0, 0));
+        let as_lab = Color::convert(srgb, ColorFormatName::CIELab);
+
+        assert!(Color::delta_e(srgb, as_lab, DeltaEMethod::Ciede2000) < 1e-3);
+        assert!(Color::delta_e(srgb, Color::SRGB(Srgb::new(0, 0, 255)), DeltaEMethod::Oklab) > 0.5);
+    }
+}
@@ -0,0 +1,274 @@
+use super::color::*;
+use super::difference::*;
+
+/*  Gamut mapping: bringing a color that an RGB space can't show into that space.
+ *  Clipping each channel is cheap but can shift the hue a long way- a saturated blue clipped
+ *  into sRGB can go purple. The other strategies give up chroma instead, keeping hue and lightness.
+ */
+
+/// How to bring an out-of-gamut color into an RGB space
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum GamutMapping {
+    /// Clamp each channel to [0, 1]
+    Clip,
+    /// The CSS Color 4 algorithm: reduce chroma in Oklch until clipping what's left is imperceptible
+    #[default]
+    ChromaReduction,
+    /// Search for the in-gamut color with the smallest CIEDE2000 difference. The slowest of these.
+    NearestDeltaE,
+}
+
+//Channels this far past 0 or 1 still count as in gamut, to absorb rounding in the conversions
+const GAMUT_EPSILON: f32 = 1e-4f32;
+
+//From CSS Color 4: a difference in Oklab under JND is just noticeable, and the chroma search
+//stops once the interval is narrower than CHROMA_EPSILON
+const JND: f32 = 0.02f32;
+const CHROMA_EPSILON: f32 = 0.0001f32;
+
+fn rgb_in_gamut(rgb: [f32; 3]) -> bool {
+    rgb.iter().all(|&c| (-GAMUT_EPSILON..=1.0f32 + GAMUT_EPSILON).contains(&c))
+}
+
+fn clip(rgb: [f32; 3]) -> [f32; 3] {
+    [rgb[0].clamp(0.0f32, 1.0f32), rgb[1].clamp(0.0f32, 1.0f32), rgb[2].clamp(0.0f32, 1.0f32)]
+}
+
+fn to_oklch(color: Color) -> Oklch {
+    match Color::convert(color, ColorFormatName::Oklch) {
+        Color::Oklch(oklch) => oklch,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn chroma_reduction(color: Color, space: &RgbSpace) -> [f32; 3] {
+    let origin = to_oklch(color);
+    if origin.l >= 1.0f32 {
+        return [1.0f32, 1.0f32, 1.0f32];
+    }
+    if origin.l <= 0.0f32 {
+        return [0.0f32, 0.0f32, 0.0f32];
+    }
+
+    let rgb = Color::to_rgb_space(color, space);
+    if rgb_in_gamut(rgb) {
+        return clip(rgb);
+    }
+
+    let clipped_difference = |oklch: Oklch, clipped: [f32; 3]| {
+        Color::delta_e(Color::Oklch(oklch), Color::from_rgb_space(space, clipped), DeltaEMethod::Oklab)
+    };
+
+    let mut current = origin;
+    let mut clipped = clip(rgb);
+    if clipped_difference(current, clipped) < JND {
+        return clipped;
+    }
+
+    let (mut min, mut max) = (0.0f32, origin.c);
+    let mut min_in_gamut = true;
+
+    while max - min > CHROMA_EPSILON {
+        let chroma = (min + max) / 2.0f32;
+        current.c = chroma;
+
+        let rgb = Color::to_rgb_space(Color::Oklch(current), space);
+        if min_in_gamut && rgb_in_gamut(rgb) {
+            min = chroma;
+            continue;
+        }
+
+        clipped = clip(rgb);
+        let difference = clipped_difference(current, clipped);
+        if difference < JND {
+            if JND - difference < CHROMA_EPSILON {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+
+    clipped
+}
+
+//Coordinate descent over the RGB cube, starting from the chroma-reduced color, which is usually close already
+fn nearest_delta_e(color: Color, space: &RgbSpace) -> [f32; 3] {
+    let rgb = Color::to_rgb_space(color, space);
+    if rgb_in_gamut(rgb) {
+        return clip(rgb);
+    }
+
+    let difference = |rgb: [f32; 3]| Color::delta_e(color, Color::from_rgb_space(space, rgb), DeltaEMethod::Ciede2000);
+
+    let mut best = chroma_reduction(color, space);
+    let mut best_difference = difference(best);
+    let mut step = 0.05f32;
+
+    while step > CHROMA_EPSILON {
+        let mut improved = false;
+
+        for channel in 0..3 {
+            for direction in [-1.0f32, 1.0f32] {
+                let mut candidate = best;
+                candidate[channel] = (candidate[channel] + direction * step).clamp(0.0f32, 1.0f32);
+
+                let candidate_difference = difference(candidate);
+                if candidate_difference < best_difference {
+                    best = candidate;
+                    best_difference = candidate_difference;
+                    improved = true;
+                }
+            }
+        }
+
+        if !improved {
+            step /= 2.0f32;
+        }
+    }
+
+    best
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// Whether the color can be shown in an RGB space without any channel going below 0 or above 1
+    pub fn in_gamut(&self, space: &RgbSpace) -> bool {
+        rgb_in_gamut(Color::to_rgb_space(*self, space))
+    }
+
+    /// How far out of an RGB space the color is, as the Oklab difference between it and its clipped self.
+    /// 0 for colors in gamut- anything under about 0.02 is hard to notice.
+    pub fn gamut_distance(&self, space: &RgbSpace) -> f32 {
+        let rgb = Color::to_rgb_space(*self, space);
+        if rgb_in_gamut(rgb) {
+            0.0f32
+        } else {
+            Color::delta_e(*self, Color::from_rgb_space(space, clip(rgb)), DeltaEMethod::Oklab)
+        }
+    }
+
+    /// The stored RGB for color in an RGB space, with every channel in [0, 1]
+    pub fn map_to_gamut(color: Color, space: &RgbSpace, mapping: GamutMapping) -> [f32; 3] {
+        match mapping {
+            GamutMapping::Clip => clip(Color::to_rgb_space(color, space)),
+            GamutMapping::ChromaReduction => chroma_reduction(color, space),
+            GamutMapping::NearestDeltaE => nearest_delta_e(color, space),
+        }
+    }
+
+    /// Like `convert`, but colors outside of sRGB are gamut mapped first for the formats built on it-
+    /// everything but the CIE formats, Oklab and Oklch, which can hold any color
+    pub fn convert_mapped(from: Color, to: ColorFormatName, mapping: GamutMapping) -> Color {
+        let unbounded = matches!(to, ColorFormatName::CIEXYZ | ColorFormatName::CIELab | ColorFormatName::CIELch |
+                                     ColorFormatName::CIELuv | ColorFormatName::CIELchUv | ColorFormatName::CIExyY |
+                                     ColorFormatName::Oklab | ColorFormatName::Oklch);
+
+        let srgb_space = RgbSpace::srgb();
+        if unbounded || from.in_gamut(&srgb_space) {
+            return Color::convert(from, to);
+        }
+
+        //Gamut mapping only sees the color, so the alpha is carried across on its own
+        let alpha = match Color::convert(from, ColorFormatName::RGBAf) {
+            Color::RGBAf(rgbaf) => rgbaf.a,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        };
+
+        let srgb = Color::map_to_gamut(from, &srgb_space, mapping);
+        let mapped = SrgbF::new(srgb[0], srgb[1], srgb[2]);
+        if alpha >= 1.0f32 {
+            return Color::convert(Color::SRGBf(mapped), to);
+        }
+
+        let rgbf: RgbF = mapped.into();
+        Color::convert(Color::RGBAf(RgbaF::new(rgbf.r, rgbf.g, rgbf.b, alpha)), to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p3_red() -> Color {
+        Color::from_rgb_space(&RgbSpace::display_p3(), [1.0f32, 0.0f32, 0.0f32])
+    }
+
+    #[test]
+    fn gamut_checks() {
+        let srgb = RgbSpace::srgb();
+        let p3 = RgbSpace::display_p3();
+
+        assert!(Color::SRGB(Srgb::new(255, 0, 0)).in_gamut(&srgb));
+        assert!(Color::SRGB(Srgb::new(255, 0, 0)).in_gamut(&p3));
+        assert!(!p3_red().in_gamut(&srgb));
+        assert!(p3_red().in_gamut(&p3));
+
+        assert_eq!(Color::SRGB(Srgb::new(12, 200, 99)).gamut_distance(&srgb), 0.0f32);
+        let distance = p3_red().gamut_distance(&srgb);
+        assert!(distance > 0.02f32 && distance < 0.1f32, "{}", distance);
+        let further = Color::from_rgb_space(&RgbSpace::rec2020(), [1.0f32, 0.0f32, 0.0f32]).gamut_distance(&srgb);
+        assert!(further > distance, "{} <= {}", further, distance);
+    }
+
+    #[test]
+    fn every_strategy_lands_in_gamut() {
+        let srgb = RgbSpace::srgb();
+        let colors = [p3_red(), Color::from_rgb_space(&RgbSpace::rec2020(), [0.0f32, 1.0f32, 0.0f32]),
+                      Color::Oklch(Oklch::new(0.5f32, 0.35f32, 264.0f32)), Color::SRGB(Srgb::new(12, 200, 99))];
+
+        for color in colors {
+            for mapping in [GamutMapping::Clip, GamutMapping::ChromaReduction, GamutMapping::NearestDeltaE] {
+                let rgb = Color::map_to_gamut(color, &srgb, mapping);
+                assert!(rgb.iter().all(|c| (0.0f32..=1.0f32).contains(c)), "{:?} {:?}: {:?}", mapping, color, rgb);
+            }
+        }
+
+        //Colors already in gamut come through untouched, whatever the strategy
+        let inside = Color::SRGBf(SrgbF::new(0.2f32, 0.5f32, 0.8f32));
+        for mapping in [GamutMapping::Clip, GamutMapping::ChromaReduction, GamutMapping::NearestDeltaE] {
+            let rgb = Color::map_to_gamut(inside, &srgb, mapping);
+            for (c, expected) in rgb.iter().zip([0.2f32, 0.5f32, 0.8f32]) {
+                assert!((c - expected).abs() < 1e-4f32, "{:?}: {:?}", mapping, rgb);
+            }
+        }
+
+        //NearestDeltaE is at least as close as where it starts from
+        let mapped = |mapping| Color::from_rgb_space(&srgb, Color::map_to_gamut(p3_red(), &srgb, mapping));
+        let reduced = Color::delta_e(p3_red(), mapped(GamutMapping::ChromaReduction), DeltaEMethod::Ciede2000);
+        let nearest = Color::delta_e(p3_red(), mapped(GamutMapping::NearestDeltaE), DeltaEMethod::Ciede2000);
+        assert!(nearest <= reduced, "{} > {}", nearest, reduced);
+    }
+
+    #[test]
+    fn chroma_reduction_keeps_hue_and_lightness() {
+        let srgb = RgbSpace::srgb();
+        let origin = to_oklch(p3_red());
+        let mapped = to_oklch(Color::from_rgb_space(&srgb, Color::map_to_gamut(p3_red(), &srgb, GamutMapping::ChromaReduction)));
+
+        //Clipping what's left can move things by up to JND, so the hue holds to within a few degrees
+        assert!((mapped.h - origin.h).abs() < 3.0f32, "hue {} -> {}", origin.h, mapped.h);
+        assert!((mapped.l - origin.l).abs() < JND, "lightness {} -> {}", origin.l, mapped.l);
+        assert!(mapped.c < origin.c);
+    }
+
+    #[test]
+    fn convert_mapped_keeps_alpha() {
+        let over = Color::RGBAf(RgbaF::new(1.2f32, 0.0f32, 0.0f32, 0.5f32));
+        let half = match Color::convert(Color::RGBAf(RgbaF::new(1.0f32, 0.0f32, 0.0f32, 0.5f32)), ColorFormatName::RGBA) {
+            Color::RGBA(rgba) => rgba.a,
+            other => panic!("{:?}", other),
+        };
+
+        for mapping in [GamutMapping::Clip, GamutMapping::ChromaReduction, GamutMapping::NearestDeltaE] {
+            match Color::convert_mapped(over, ColorFormatName::RGBA, mapping) {
+                Color::RGBA(rgba) => assert_eq!(rgba.a, half, "{:?}", mapping),
+                other => panic!("{:?}", other),
+            }
+        }
+
+        assert_eq!(Color::convert_mapped(p3_red(), ColorFormatName::SRGB, GamutMapping::Clip), Color::SRGB(Srgb::new(255, 0, 0)));
+    }
+}
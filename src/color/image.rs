@@ -0,0 +1,723 @@
+use super::color::*;
+
+/*  Pixel buffers built on ColorFormat. Pixels are kept typed rather than as bytes, since not every
+ *  format's byte encoding is a fixed size (the CIE formats only write a reference white if they have one).
+ *  Rows are `stride` pixels apart, which lets a view share its parent's pixels.
+ */
+
+//From a Color to a typed pixel, through the same raw parts Color already round-trips through
+fn from_color<C: ColorFormat>(color: Color) -> C {
+    let (_, bytes) = Color::convert(color, C::format_name()).to_raw_parts();
+    C::from_bytes(&bytes).unwrap()
+}
+
+//The pixels from (x, y) to the last pixel of a width x height region.
+//An empty region has no pixels to start from- (x, y) can be past the end- so it borrows none.
+fn region_range(stride: usize, x: u32, y: u32, width: u32, height: u32) -> std::ops::Range<usize> {
+    if width == 0 || height == 0 {
+        return 0..0;
+    }
+
+    let start = y as usize * stride + x as usize;
+    start..(start + (height as usize - 1) * stride + width as usize)
+}
+
+fn fits(outer_width: u32, outer_height: u32, x: u32, y: u32, width: u32, height: u32) -> bool {
+    x.checked_add(width).is_some_and(|right| right <= outer_width) &&
+        y.checked_add(height).is_some_and(|bottom| bottom <= outer_height)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageBuffer<C: ColorFormat> {
+    width: u32,
+    height: u32,
+    stride: usize,
+    pixels: Vec<C>,
+}
+
+#[allow(dead_code)]
+impl<C: ColorFormat + Copy> ImageBuffer<C> {
+    pub fn new(width: u32, height: u32) -> ImageBuffer<C> where C: Default {
+        ImageBuffer::filled(width, height, C::default())
+    }
+
+    pub fn filled(width: u32, height: u32, pixel: C) -> ImageBuffer<C> {
+        ImageBuffer {
+            width,
+            height,
+            stride: width as usize,
+            pixels: vec![pixel; width as usize * height as usize],
+        }
+    }
+
+    /// Wraps existing pixels. Rows start every `stride` pixels- anything past `width` is padding.
+    pub fn from_pixels(width: u32, height: u32, stride: usize, pixels: Vec<C>) -> Result<ImageBuffer<C>, String> {
+        if stride < width as usize {
+            return Err(format!("Stride {} is less than the width {}", stride, width));
+        }
+
+        let needed = region_range(stride, 0, 0, width, height).end;
+        if pixels.len() < needed {
+            return Err(format!("A {}x{} image with a stride of {} needs {} pixels, {} were provided",
+                               width, height, stride, needed, pixels.len()));
+        }
+
+        Ok(ImageBuffer {
+            width,
+            height,
+            stride,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The distance between the starts of two rows, in pixels
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn format(&self) -> ColorFormatName {
+        C::format_name()
+    }
+
+    /// All the pixels, row padding included
+    pub fn pixels(&self) -> &[C] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<C> {
+        self.as_view().get_pixel(x, y)
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut C> {
+        if x < self.width && y < self.height {
+            Some(&mut self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: C) -> Result<(), String> {
+        let (width, height) = (self.width, self.height);
+        match self.pixel_mut(x, y) {
+            Some(p) => {
+                *p = pixel;
+                Ok(())
+            },
+            None => Err(format!("Pixel ({}, {}) is outside of the {}x{} image", x, y, width, height)),
+        }
+    }
+
+    pub fn fill(&mut self, pixel: C) {
+        self.as_view_mut().fill(pixel);
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[C]> {
+        let width = self.width as usize;
+        self.pixels.chunks(self.stride.max(1)).take(self.height as usize).map(move |row| &row[..width])
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [C]> {
+        let width = self.width as usize;
+        self.pixels.chunks_mut(self.stride.max(1)).take(self.height as usize).map(move |row| &mut row[..width])
+    }
+
+    pub fn as_view(&self) -> ImageView<'_, C> {
+        ImageView {
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            pixels: &self.pixels[region_range(self.stride, 0, 0, self.width, self.height)],
+        }
+    }
+
+    pub fn as_view_mut(&mut self) -> ImageViewMut<'_, C> {
+        let range = region_range(self.stride, 0, 0, self.width, self.height);
+        ImageViewMut {
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            pixels: &mut self.pixels[range],
+        }
+    }
+
+    /// A rectangle of this image, or `None` if it doesn't fit inside
+    pub fn view(&self, x: u32, y: u32, width: u32, height: u32) -> Option<ImageView<'_, C>> {
+        self.as_view().view(x, y, width, height)
+    }
+
+    pub fn view_mut(&mut self, x: u32, y: u32, width: u32, height: u32) -> Option<ImageViewMut<'_, C>> {
+        if !fits(self.width, self.height, x, y, width, height) {
+            return None;
+        }
+
+        let range = region_range(self.stride, x, y, width, height);
+        Some(ImageViewMut {
+            width,
+            height,
+            stride: self.stride,
+            pixels: &mut self.pixels[range],
+        })
+    }
+
+    /// The pixels' native-endian bytes, row by row without padding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.rows().flat_map(|row| row.iter().flat_map(|pixel| pixel.as_bytes())).collect()
+    }
+}
+
+#[allow(dead_code)]
+impl<C: BulkConvert> ImageBuffer<C> {
+    /// Converts every pixel with `convert_slice`, a row at a time. The result is tightly packed.
+    pub fn convert<D: BulkConvert>(&self) -> ImageBuffer<D> {
+        let mut converted = ImageBuffer::filled(self.width, self.height, D::from_linear_rgba([0.0f32, 0.0f32, 0.0f32, 1.0f32]));
+        for (src, dst) in self.rows().zip(converted.rows_mut()) {
+            convert_slice(src, dst).unwrap();
+        }
+
+        converted
+    }
+}
+
+/// How the planes of 4:2:0 video are laid out. Both start with a full size Y plane.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum YuvLayout {
+    /// One half size plane with Cb and Cr interleaved
+    Nv12,
+    /// A half size Cb plane, then a half size Cr plane
+    I420,
+}
+
+#[allow(dead_code)]
+impl ImageBuffer<YCbCr> {
+    /// Reads a 4:2:0 frame (as from a camera or video decoder), tightly packed with no row padding.
+    /// Odd sizes round the chroma planes up. Each chroma sample is shared by the 2x2 pixels it covers.
+    pub fn from_yuv420(width: u32, height: u32, data: &[u8], layout: YuvLayout,
+                       matrix: YCbCrMatrix, range: YCbCrRange) -> Result<ImageBuffer<YCbCr>, String> {
+        let (w, h) = (width as usize, height as usize);
+        let (chroma_w, chroma_h) = (w.div_ceil(2), h.div_ceil(2));
+        let luma_size = w * h;
+        let chroma_size = chroma_w * chroma_h;
+
+        let needed = luma_size + 2 * chroma_size;
+        if data.len() != needed {
+            return Err(format!("A {}x{} 4:2:0 frame is {} bytes, {} were provided", width, height, needed, data.len()));
+        }
+
+        let (luma, chroma) = data.split_at(luma_size);
+        let mut pixels = Vec::with_capacity(luma_size);
+        for y in 0..h {
+            for x in 0..w {
+                let sample = (y / 2) * chroma_w + x / 2;
+                let (cb, cr) = match layout {
+                    YuvLayout::Nv12 => (chroma[2 * sample], chroma[2 * sample + 1]),
+                    YuvLayout::I420 => (chroma[sample], chroma[chroma_size + sample]),
+                };
+
+                pixels.push(YCbCr::new(luma[y * w + x], cb, cr).with_matrix(matrix).with_range(range));
+            }
+        }
+
+        ImageBuffer::from_pixels(width, height, w, pixels)
+    }
+}
+
+/// A borrowed rectangle of an image
+#[derive(Copy, Clone, Debug)]
+pub struct ImageView<'a, C: ColorFormat> {
+    width: u32,
+    height: u32,
+    stride: usize,
+    pixels: &'a [C],
+}
+
+#[allow(dead_code)]
+impl<'a, C: ColorFormat + Copy> ImageView<'a, C> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<C> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [C]> {
+        let width = self.width as usize;
+        self.pixels.chunks(self.stride.max(1)).take(self.height as usize).map(move |row| &row[..width])
+    }
+
+    pub fn view(&self, x: u32, y: u32, width: u32, height: u32) -> Option<ImageView<'a, C>> {
+        if !fits(self.width, self.height, x, y, width, height) {
+            return None;
+        }
+
+        Some(ImageView {
+            width,
+            height,
+            stride: self.stride,
+            pixels: &self.pixels[region_range(self.stride, x, y, width, height)],
+        })
+    }
+
+    /// Copies the view out into an image of its own
+    pub fn to_image(self) -> ImageBuffer<C> {
+        ImageBuffer {
+            width: self.width,
+            height: self.height,
+            stride: self.width as usize,
+            pixels: self.rows().flatten().copied().collect(),
+        }
+    }
+}
+
+/// A mutably borrowed rectangle of an image
+#[derive(Debug)]
+pub struct ImageViewMut<'a, C: ColorFormat> {
+    width: u32,
+    height: u32,
+    stride: usize,
+    pixels: &'a mut [C],
+}
+
+#[allow(dead_code)]
+impl<'a, C: ColorFormat + Copy> ImageViewMut<'a, C> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn as_view(&self) -> ImageView<'_, C> {
+        ImageView {
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            pixels: self.pixels,
+        }
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<C> {
+        self.as_view().get_pixel(x, y)
+    }
+
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut C> {
+        if x < self.width && y < self.height {
+            Some(&mut self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [C]> {
+        let width = self.width as usize;
+        self.pixels.chunks_mut(self.stride.max(1)).take(self.height as usize).map(move |row| &mut row[..width])
+    }
+
+    pub fn fill(&mut self, pixel: C) {
+        for row in self.rows_mut() {
+            for p in row.iter_mut() {
+                *p = pixel;
+            }
+        }
+    }
+
+    /// Copies src into the top left of this view, clipped to whichever is smaller
+    pub fn copy_from(&mut self, src: &ImageView<C>) {
+        for (dst_row, src_row) in self.rows_mut().zip(src.rows()) {
+            let len = dst_row.len().min(src_row.len());
+            dst_row[..len].copy_from_slice(&src_row[..len]);
+        }
+    }
+}
+
+/// An image whose format is only known at runtime, one variant per `ColorFormatName`
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynImage {
+    Gray8(ImageBuffer<Gray8>),
+    Gray16(ImageBuffer<Gray16>),
+    Grayf(ImageBuffer<GrayF>),
+    RGB(ImageBuffer<Rgb>),
+    SRGB(ImageBuffer<Srgb>),
+    RGB48(ImageBuffer<Rgb48>),
+    RGBA(ImageBuffer<Rgba>),
+    RGBA64(ImageBuffer<Rgba64>),
+    RGBf(ImageBuffer<RgbF>),
+    SRGBf(ImageBuffer<SrgbF>),
+    RGBAf(ImageBuffer<RgbaF>),
+    HSV(ImageBuffer<Hsv>),
+    HSL(ImageBuffer<Hsl>),
+    CIEXYZ(ImageBuffer<CieXyz>),
+    CIELab(ImageBuffer<CieLab>),
+    CIELch(ImageBuffer<CieLch>),
+    CIELuv(ImageBuffer<CieLuv>),
+    CIELchUv(ImageBuffer<CieLchUv>),
+    CIExyY(ImageBuffer<CieXyY>),
+    Oklab(ImageBuffer<Oklab>),
+    Oklch(ImageBuffer<Oklch>),
+    CMYK(ImageBuffer<Cmyk>),
+    YCbCr(ImageBuffer<YCbCr>),
+    HWB(ImageBuffer<Hwb>)
+}
+
+//Builds a DynImage of the given format from any typed image
+fn convert_to_dyn<C: BulkConvert>(image: &ImageBuffer<C>, to: ColorFormatName) -> DynImage {
+    match to {
+        ColorFormatName::Gray8 => DynImage::Gray8(image.convert()),
+        ColorFormatName::Gray16 => DynImage::Gray16(image.convert()),
+        ColorFormatName::Grayf => DynImage::Grayf(image.convert()),
+        ColorFormatName::RGB => DynImage::RGB(image.convert()),
+        ColorFormatName::SRGB => DynImage::SRGB(image.convert()),
+        ColorFormatName::RGB48 => DynImage::RGB48(image.convert()),
+        ColorFormatName::RGBA => DynImage::RGBA(image.convert()),
+        ColorFormatName::RGBA64 => DynImage::RGBA64(image.convert()),
+        ColorFormatName::RGBf => DynImage::RGBf(image.convert()),
+        ColorFormatName::SRGBf => DynImage::SRGBf(image.convert()),
+        ColorFormatName::RGBAf => DynImage::RGBAf(image.convert()),
+        ColorFormatName::HSV => DynImage::HSV(image.convert()),
+        ColorFormatName::HSL => DynImage::HSL(image.convert()),
+        ColorFormatName::CIEXYZ => DynImage::CIEXYZ(image.convert()),
+        ColorFormatName::CIELab => DynImage::CIELab(image.convert()),
+        ColorFormatName::CIELch => DynImage::CIELch(image.convert()),
+        ColorFormatName::CIELuv => DynImage::CIELuv(image.convert()),
+        ColorFormatName::CIELchUv => DynImage::CIELchUv(image.convert()),
+        ColorFormatName::CIExyY => DynImage::CIExyY(image.convert()),
+        ColorFormatName::Oklab => DynImage::Oklab(image.convert()),
+        ColorFormatName::Oklch => DynImage::Oklch(image.convert()),
+        ColorFormatName::CMYK => DynImage::CMYK(image.convert()),
+        ColorFormatName::YCbCr => DynImage::YCbCr(image.convert()),
+        ColorFormatName::HWB => DynImage::HWB(image.convert()),
+    }
+}
+
+#[allow(dead_code)]
+impl DynImage {
+    /// An image of the given format, filled with color
+    pub fn filled(format: ColorFormatName, width: u32, height: u32, color: Color) -> DynImage {
+        match format {
+            ColorFormatName::Gray8 => DynImage::Gray8(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::Gray16 => DynImage::Gray16(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::Grayf => DynImage::Grayf(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGB => DynImage::RGB(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::SRGB => DynImage::SRGB(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGB48 => DynImage::RGB48(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGBA => DynImage::RGBA(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGBA64 => DynImage::RGBA64(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGBf => DynImage::RGBf(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::SRGBf => DynImage::SRGBf(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::RGBAf => DynImage::RGBAf(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::HSV => DynImage::HSV(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::HSL => DynImage::HSL(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIEXYZ => DynImage::CIEXYZ(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIELab => DynImage::CIELab(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIELch => DynImage::CIELch(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIELuv => DynImage::CIELuv(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIELchUv => DynImage::CIELchUv(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CIExyY => DynImage::CIExyY(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::Oklab => DynImage::Oklab(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::Oklch => DynImage::Oklch(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::CMYK => DynImage::CMYK(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::YCbCr => DynImage::YCbCr(ImageBuffer::filled(width, height, from_color(color))),
+            ColorFormatName::HWB => DynImage::HWB(ImageBuffer::filled(width, height, from_color(color))),
+        }
+    }
+
+    pub fn format(&self) -> ColorFormatName {
+        match self {
+            DynImage::Gray8(image) => image.format(),
+            DynImage::Gray16(image) => image.format(),
+            DynImage::Grayf(image) => image.format(),
+            DynImage::RGB(image) => image.format(),
+            DynImage::SRGB(image) => image.format(),
+            DynImage::RGB48(image) => image.format(),
+            DynImage::RGBA(image) => image.format(),
+            DynImage::RGBA64(image) => image.format(),
+            DynImage::RGBf(image) => image.format(),
+            DynImage::SRGBf(image) => image.format(),
+            DynImage::RGBAf(image) => image.format(),
+            DynImage::HSV(image) => image.format(),
+            DynImage::HSL(image) => image.format(),
+            DynImage::CIEXYZ(image) => image.format(),
+            DynImage::CIELab(image) => image.format(),
+            DynImage::CIELch(image) => image.format(),
+            DynImage::CIELuv(image) => image.format(),
+            DynImage::CIELchUv(image) => image.format(),
+            DynImage::CIExyY(image) => image.format(),
+            DynImage::Oklab(image) => image.format(),
+            DynImage::Oklch(image) => image.format(),
+            DynImage::CMYK(image) => image.format(),
+            DynImage::YCbCr(image) => image.format(),
+            DynImage::HWB(image) => image.format(),
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            DynImage::Gray8(image) => (image.width(), image.height()),
+            DynImage::Gray16(image) => (image.width(), image.height()),
+            DynImage::Grayf(image) => (image.width(), image.height()),
+            DynImage::RGB(image) => (image.width(), image.height()),
+            DynImage::SRGB(image) => (image.width(), image.height()),
+            DynImage::RGB48(image) => (image.width(), image.height()),
+            DynImage::RGBA(image) => (image.width(), image.height()),
+            DynImage::RGBA64(image) => (image.width(), image.height()),
+            DynImage::RGBf(image) => (image.width(), image.height()),
+            DynImage::SRGBf(image) => (image.width(), image.height()),
+            DynImage::RGBAf(image) => (image.width(), image.height()),
+            DynImage::HSV(image) => (image.width(), image.height()),
+            DynImage::HSL(image) => (image.width(), image.height()),
+            DynImage::CIEXYZ(image) => (image.width(), image.height()),
+            DynImage::CIELab(image) => (image.width(), image.height()),
+            DynImage::CIELch(image) => (image.width(), image.height()),
+            DynImage::CIELuv(image) => (image.width(), image.height()),
+            DynImage::CIELchUv(image) => (image.width(), image.height()),
+            DynImage::CIExyY(image) => (image.width(), image.height()),
+            DynImage::Oklab(image) => (image.width(), image.height()),
+            DynImage::Oklch(image) => (image.width(), image.height()),
+            DynImage::CMYK(image) => (image.width(), image.height()),
+            DynImage::YCbCr(image) => (image.width(), image.height()),
+            DynImage::HWB(image) => (image.width(), image.height()),
+        }
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        match self {
+            DynImage::Gray8(image) => image.get_pixel(x, y).map(Color::Gray8),
+            DynImage::Gray16(image) => image.get_pixel(x, y).map(Color::Gray16),
+            DynImage::Grayf(image) => image.get_pixel(x, y).map(Color::Grayf),
+            DynImage::RGB(image) => image.get_pixel(x, y).map(Color::RGB),
+            DynImage::SRGB(image) => image.get_pixel(x, y).map(Color::SRGB),
+            DynImage::RGB48(image) => image.get_pixel(x, y).map(Color::RGB48),
+            DynImage::RGBA(image) => image.get_pixel(x, y).map(Color::RGBA),
+            DynImage::RGBA64(image) => image.get_pixel(x, y).map(Color::RGBA64),
+            DynImage::RGBf(image) => image.get_pixel(x, y).map(Color::RGBf),
+            DynImage::SRGBf(image) => image.get_pixel(x, y).map(Color::SRGBf),
+            DynImage::RGBAf(image) => image.get_pixel(x, y).map(Color::RGBAf),
+            DynImage::HSV(image) => image.get_pixel(x, y).map(Color::HSV),
+            DynImage::HSL(image) => image.get_pixel(x, y).map(Color::HSL),
+            DynImage::CIEXYZ(image) => image.get_pixel(x, y).map(Color::CIEXYZ),
+            DynImage::CIELab(image) => image.get_pixel(x, y).map(Color::CIELab),
+            DynImage::CIELch(image) => image.get_pixel(x, y).map(Color::CIELch),
+            DynImage::CIELuv(image) => image.get_pixel(x, y).map(Color::CIELuv),
+            DynImage::CIELchUv(image) => image.get_pixel(x, y).map(Color::CIELchUv),
+            DynImage::CIExyY(image) => image.get_pixel(x, y).map(Color::CIExyY),
+            DynImage::Oklab(image) => image.get_pixel(x, y).map(Color::Oklab),
+            DynImage::Oklch(image) => image.get_pixel(x, y).map(Color::Oklch),
+            DynImage::CMYK(image) => image.get_pixel(x, y).map(Color::CMYK),
+            DynImage::YCbCr(image) => image.get_pixel(x, y).map(Color::YCbCr),
+            DynImage::HWB(image) => image.get_pixel(x, y).map(Color::HWB),
+        }
+    }
+
+    /// Sets a pixel, converting color to the image's format first
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) -> Result<(), String> {
+        match self {
+            DynImage::Gray8(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::Gray16(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::Grayf(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGB(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::SRGB(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGB48(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGBA(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGBA64(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGBf(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::SRGBf(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::RGBAf(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::HSV(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::HSL(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIEXYZ(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIELab(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIELch(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIELuv(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIELchUv(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CIExyY(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::Oklab(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::Oklch(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::CMYK(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::YCbCr(image) => image.set_pixel(x, y, from_color(color)),
+            DynImage::HWB(image) => image.set_pixel(x, y, from_color(color)),
+        }
+    }
+
+    pub fn convert(&self, to: ColorFormatName) -> DynImage {
+        match self {
+            DynImage::Gray8(image) => convert_to_dyn(image, to),
+            DynImage::Gray16(image) => convert_to_dyn(image, to),
+            DynImage::Grayf(image) => convert_to_dyn(image, to),
+            DynImage::RGB(image) => convert_to_dyn(image, to),
+            DynImage::SRGB(image) => convert_to_dyn(image, to),
+            DynImage::RGB48(image) => convert_to_dyn(image, to),
+            DynImage::RGBA(image) => convert_to_dyn(image, to),
+            DynImage::RGBA64(image) => convert_to_dyn(image, to),
+            DynImage::RGBf(image) => convert_to_dyn(image, to),
+            DynImage::SRGBf(image) => convert_to_dyn(image, to),
+            DynImage::RGBAf(image) => convert_to_dyn(image, to),
+            DynImage::HSV(image) => convert_to_dyn(image, to),
+            DynImage::HSL(image) => convert_to_dyn(image, to),
+            DynImage::CIEXYZ(image) => convert_to_dyn(image, to),
+            DynImage::CIELab(image) => convert_to_dyn(image, to),
+            DynImage::CIELch(image) => convert_to_dyn(image, to),
+            DynImage::CIELuv(image) => convert_to_dyn(image, to),
+            DynImage::CIELchUv(image) => convert_to_dyn(image, to),
+            DynImage::CIExyY(image) => convert_to_dyn(image, to),
+            DynImage::Oklab(image) => convert_to_dyn(image, to),
+            DynImage::Oklch(image) => convert_to_dyn(image, to),
+            DynImage::CMYK(image) => convert_to_dyn(image, to),
+            DynImage::YCbCr(image) => convert_to_dyn(image, to),
+            DynImage::HWB(image) => convert_to_dyn(image, to),
+        }
+    }
+
+    /// The pixels' native-endian bytes, row by row without padding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            DynImage::Gray8(image) => image.to_bytes(),
+            DynImage::Gray16(image) => image.to_bytes(),
+            DynImage::Grayf(image) => image.to_bytes(),
+            DynImage::RGB(image) => image.to_bytes(),
+            DynImage::SRGB(image) => image.to_bytes(),
+            DynImage::RGB48(image) => image.to_bytes(),
+            DynImage::RGBA(image) => image.to_bytes(),
+            DynImage::RGBA64(image) => image.to_bytes(),
+            DynImage::RGBf(image) => image.to_bytes(),
+            DynImage::SRGBf(image) => image.to_bytes(),
+            DynImage::RGBAf(image) => image.to_bytes(),
+            DynImage::HSV(image) => image.to_bytes(),
+            DynImage::HSL(image) => image.to_bytes(),
+            DynImage::CIEXYZ(image) => image.to_bytes(),
+            DynImage::CIELab(image) => image.to_bytes(),
+            DynImage::CIELch(image) => image.to_bytes(),
+            DynImage::CIELuv(image) => image.to_bytes(),
+            DynImage::CIELchUv(image) => image.to_bytes(),
+            DynImage::CIExyY(image) => image.to_bytes(),
+            DynImage::Oklab(image) => image.to_bytes(),
+            DynImage::Oklch(image) => image.to_bytes(),
+            DynImage::CMYK(image) => image.to_bytes(),
+            DynImage::YCbCr(image) => image.to_bytes(),
+            DynImage::HWB(image) => image.to_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(luminance: u8) -> Gray8 {
+        Gray8::new(luminance)
+    }
+
+    //A 3x2 image in a stride of 4, with 99 as the padding at the end of each row
+    fn padded() -> ImageBuffer<Gray8> {
+        let pixels = [1, 2, 3, 99, 4, 5, 6, 99].iter().map(|l| gray(*l)).collect();
+        ImageBuffer::from_pixels(3, 2, 4, pixels).unwrap()
+    }
+
+    fn luminances<'a>(rows: impl Iterator<Item = &'a [Gray8]>) -> Vec<Vec<u8>> {
+        rows.map(|row| row.iter().map(|p| p.luminance).collect()).collect()
+    }
+
+    #[test]
+    fn from_pixels_checks_the_stride() {
+        let image = padded();
+        assert_eq!((image.width(), image.height(), image.stride()), (3, 2, 4));
+        assert_eq!(image.get_pixel(0, 1), Some(gray(4)));
+        assert_eq!(image.get_pixel(3, 0), None);
+
+        //The last row doesn't need its padding
+        assert!(ImageBuffer::from_pixels(3, 2, 4, vec![gray(0); 7]).is_ok());
+        assert!(ImageBuffer::from_pixels(3, 2, 4, vec![gray(0); 6]).is_err());
+        assert!(ImageBuffer::from_pixels(3, 2, 2, vec![gray(0); 8]).is_err());
+    }
+
+    #[test]
+    fn rows_leave_out_the_padding() {
+        let image = padded();
+        assert_eq!(luminances(image.rows()), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(luminances(image.as_view().rows()), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(image.to_bytes(), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn views_nest_and_copy_out() {
+        let pixels = (0..16).map(|l| gray(l as u8)).collect();
+        let image = ImageBuffer::from_pixels(4, 4, 4, pixels).unwrap();
+
+        let outer = image.view(1, 1, 3, 3).unwrap();
+        let inner = outer.view(1, 1, 2, 2).unwrap();
+        assert_eq!(luminances(inner.rows()), vec![vec![10, 11], vec![14, 15]]);
+        assert_eq!(inner.get_pixel(1, 1), Some(gray(15)));
+        assert_eq!(inner.get_pixel(2, 0), None);
+        assert!(outer.view(2, 2, 2, 1).is_none());
+
+        let copied = inner.to_image();
+        assert_eq!(copied.stride(), 2);
+        assert_eq!(copied.pixels(), &[gray(10), gray(11), gray(14), gray(15)]);
+    }
+
+    #[test]
+    fn empty_views_fit_at_the_far_edge() {
+        let mut image: ImageBuffer<Gray8> = ImageBuffer::filled(2, 2, gray(7));
+        let empty = image.view(2, 2, 0, 0).unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(empty.rows().count(), 0);
+        assert!(image.view(2, 0, 0, 2).is_some());
+        assert!(image.view(3, 0, 0, 0).is_none());
+        assert!(image.view_mut(2, 2, 0, 0).is_some());
+
+        let empty: ImageBuffer<Gray8> = ImageBuffer::new(0, 0);
+        assert!(empty.as_view().view(0, 0, 0, 0).is_some());
+    }
+
+    #[test]
+    fn copy_from_clips_to_the_smaller() {
+        let mut image: ImageBuffer<Gray8> = ImageBuffer::filled(4, 3, gray(0));
+        let big = ImageBuffer::filled(5, 5, gray(9));
+
+        image.view_mut(2, 1, 2, 2).unwrap().copy_from(&big.as_view());
+        assert_eq!(luminances(image.rows()), vec![vec![0, 0, 0, 0], vec![0, 0, 9, 9], vec![0, 0, 9, 9]]);
+
+        let small = ImageBuffer::filled(1, 1, gray(5));
+        image.as_view_mut().copy_from(&small.as_view());
+        assert_eq!(luminances(image.rows())[0], vec![5, 0, 0, 0]);
+    }
+
+    #[test]
+    fn dyn_images_convert_through_color() {
+        let blue = Color::SRGB(Srgb::new(0x25, 0x63, 0xeb));
+        let mut image = DynImage::filled(ColorFormatName::SRGB, 2, 2, blue);
+        assert_eq!((image.format(), image.dimensions()), (ColorFormatName::SRGB, (2, 2)));
+        assert_eq!(image.get_pixel(1, 1), Some(blue));
+        assert_eq!(image.get_pixel(2, 0), None);
+
+        image.set_pixel(0, 0, Color::SRGBf(SrgbF::new(1.0f32, 1.0f32, 1.0f32))).unwrap();
+        assert_eq!(image.get_pixel(0, 0), Some(Color::SRGB(Srgb::new(255, 255, 255))));
+        assert!(image.set_pixel(0, 2, blue).is_err());
+
+        let converted = image.convert(ColorFormatName::RGBA64);
+        assert_eq!(converted.format(), ColorFormatName::RGBA64);
+        assert_eq!(converted.to_bytes().len(), 4 * 8);
+        for (x, y) in [(0, 0), (1, 1)] {
+            assert_eq!(converted.get_pixel(x, y), image.get_pixel(x, y).map(|c| Color::convert(c, ColorFormatName::RGBA64)));
+        }
+
+        assert_eq!(image.to_bytes(), [255, 255, 255, 0x25, 0x63, 0xeb, 0x25, 0x63, 0xeb, 0x25, 0x63, 0xeb]);
+    }
+}
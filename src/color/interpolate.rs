@@ -0,0 +1,239 @@
+use super::color::*;
+
+/// The color space two colors are mixed in. The same endpoints give quite different midpoints
+/// depending on the space- sRGB goes muddy, linear RGB goes bright, Lab and Oklab stay perceptually even.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum InterpolationSpace {
+    Srgb,
+    #[default]
+    LinearRgb,
+    Hsl,
+    Hsv,
+    CieLab,
+    Oklab,
+    Oklch,
+}
+
+fn alpha_of(color: Color) -> f32 {
+    match color {
+        Color::RGBA(rgba) => (rgba.a as f32) / (u8::MAX as f32),
+        Color::RGBA64(rgba_w) => (rgba_w.a as f32) / (u16::MAX as f32),
+        Color::RGBAf(rgbaf) => rgbaf.a,
+        _ => 1.0f32,
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+//Takes the short way around the hue circle. A hue with no saturation behind it is meaningless,
+//so it takes on the other color's hue instead of swinging through unrelated colors.
+fn lerp_hue(h_a: f32, sat_a: f32, h_b: f32, sat_b: f32, t: f32) -> f32 {
+    let (h_a, h_b) = if sat_a < 1e-6f32 {
+        (h_b, h_b)
+    } else if sat_b < 1e-6f32 {
+        (h_a, h_a)
+    } else {
+        (h_a, h_b)
+    };
+
+    let delta = (h_b - h_a + 540.0f32).rem_euclid(360.0f32) - 180.0f32;
+    (h_a + delta * t).rem_euclid(360.0f32)
+}
+
+/// Mixes a and b- t = 0 gives a, t = 1 gives b. Alpha is interpolated linearly.
+/// The result is always `Color::RGBAf`, since that's what renderers consume.
+pub fn interpolate(a: Color, b: Color, t: f32, space: InterpolationSpace) -> Color {
+    let alpha = lerp(alpha_of(a), alpha_of(b), t);
+
+    let rgbf: RgbF = match space {
+        InterpolationSpace::Srgb => {
+            match (Color::convert(a, ColorFormatName::SRGBf), Color::convert(b, ColorFormatName::SRGBf)) {
+                (Color::SRGBf(sa), Color::SRGBf(sb)) => {
+                    SrgbF::new(lerp(sa.r, sb.r, t), lerp(sa.g, sb.g, t), lerp(sa.b, sb.b, t)).into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::LinearRgb => {
+            match (Color::convert(a, ColorFormatName::RGBf), Color::convert(b, ColorFormatName::RGBf)) {
+                (Color::RGBf(la), Color::RGBf(lb)) => {
+                    RgbF::new(lerp(la.r, lb.r, t), lerp(la.g, lb.g, t), lerp(la.b, lb.b, t))
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::Hsl => {
+            match (Color::convert(a, ColorFormatName::HSL), Color::convert(b, ColorFormatName::HSL)) {
+                (Color::HSL(ha), Color::HSL(hb)) => {
+                    let h = lerp_hue(ha.h, ha.s, hb.h, hb.s, t);
+                    Hsl::new(h, lerp(ha.s, hb.s, t), lerp(ha.l, hb.l, t)).into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::Hsv => {
+            match (Color::convert(a, ColorFormatName::HSV), Color::convert(b, ColorFormatName::HSV)) {
+                (Color::HSV(ha), Color::HSV(hb)) => {
+                    let h = lerp_hue(ha.h, ha.s, hb.h, hb.s, t);
+                    Hsv::new(h, lerp(ha.s, hb.s, t), lerp(ha.v, hb.v, t)).into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::CieLab => {
+            match (Color::convert(a, ColorFormatName::CIELab), Color::convert(b, ColorFormatName::CIEXYZ)) {
+                (Color::CIELab(la), Color::CIEXYZ(xyz_b)) => {
+                    //a and b can be relative to different whites (lab() is D50, conversions are D65),
+                    //so b is adapted to a's white before their values can be mixed
+                    let white = la.ref_xyz.map_or(CieXyz::default(), |(x, y, z)| CieXyz::new(x, y, z));
+                    let adapted = CieXyz::chromatic_adaptation(xyz_b, white);
+                    let lb: CieLab = CieXyz::new(adapted.x, adapted.y, adapted.z).with_reference_white(white).into();
+
+                    let mut lab = CieLab::new(lerp(la.l, lb.l, t), lerp(la.a, lb.a, t), lerp(la.b, lb.b, t));
+                    lab.ref_xyz = la.ref_xyz;
+                    let xyz: CieXyz = lab.into();
+                    xyz.into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::Oklab => {
+            match (Color::convert(a, ColorFormatName::Oklab), Color::convert(b, ColorFormatName::Oklab)) {
+                (Color::Oklab(oa), Color::Oklab(ob)) => {
+                    Oklab::new(lerp(oa.l, ob.l, t), lerp(oa.a, ob.a, t), lerp(oa.b, ob.b, t)).into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+        InterpolationSpace::Oklch => {
+            match (Color::convert(a, ColorFormatName::Oklch), Color::convert(b, ColorFormatName::Oklch)) {
+                (Color::Oklch(oa), Color::Oklch(ob)) => {
+                    let h = lerp_hue(oa.h, oa.c, ob.h, ob.c, t);
+                    Oklch::new(lerp(oa.l, ob.l, t), lerp(oa.c, ob.c, t), h).into()
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            }
+        },
+    };
+
+    Color::RGBAf(RgbaF::new(rgbf.r, rgbf.g, rgbf.b, alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgbaf(color: Color) -> [f32; 4] {
+        match color {
+            Color::RGBAf(rgbaf) => [rgbaf.r, rgbaf.g, rgbaf.b, rgbaf.a],
+            other => panic!("interpolate gave {:?}", other),
+        }
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32], tolerance: f32, what: &str) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= tolerance, "{}: got {:?}, expected {:?}", what, actual, expected);
+        }
+    }
+
+    fn oklch(color: Color) -> Oklch {
+        match Color::convert(color, ColorFormatName::Oklch) {
+            Color::Oklch(oklch) => oklch,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    fn oklab(color: Color) -> Oklab {
+        match Color::convert(color, ColorFormatName::Oklab) {
+            Color::Oklab(oklab) => oklab,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    fn lab(color: Color) -> CieLab {
+        match Color::convert(color, ColorFormatName::CIELab) {
+            Color::CIELab(lab) => lab,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    const RED: Color = Color::SRGB(Srgb { r: 255, g: 0, b: 0 });
+    const BLUE: Color = Color::SRGB(Srgb { r: 0, g: 0, b: 255 });
+
+    #[test]
+    fn midpoints_in_each_space() {
+        //Halfway in the encoded values is 0.5, which is about 0.214 in linear light
+        assert_close(&rgbaf(interpolate(RED, BLUE, 0.5f32, InterpolationSpace::Srgb)), &[0.2140f32, 0.0f32, 0.2140f32, 1.0f32], 1e-3f32, "sRGB");
+        assert_close(&rgbaf(interpolate(RED, BLUE, 0.5f32, InterpolationSpace::LinearRgb)), &[0.5f32, 0.0f32, 0.5f32, 1.0f32], 1e-5f32, "linear");
+
+        //Red to blue the short way round the wheel is through magenta
+        assert_close(&rgbaf(interpolate(RED, BLUE, 0.5f32, InterpolationSpace::Hsl)), &[1.0f32, 0.0f32, 1.0f32, 1.0f32], 1e-3f32, "HSL");
+        assert_close(&rgbaf(interpolate(RED, BLUE, 0.5f32, InterpolationSpace::Hsv)), &[1.0f32, 0.0f32, 1.0f32, 1.0f32], 1e-3f32, "HSV");
+
+        //The perceptual spaces land on the average of the endpoints' own coordinates
+        let (a, b) = (Color::SRGB(Srgb::new(0x25, 0x63, 0xeb)), Color::SRGB(Srgb::new(0xe8, 0xc0, 0x60)));
+        let mid = lab(interpolate(a, b, 0.5f32, InterpolationSpace::CieLab));
+        let (la, lb) = (lab(a), lab(b));
+        assert_close(&[mid.l, mid.a, mid.b], &[(la.l + lb.l) / 2.0f32, (la.a + lb.a) / 2.0f32, (la.b + lb.b) / 2.0f32], 0.05f32, "Lab");
+
+        let mid = oklab(interpolate(a, b, 0.5f32, InterpolationSpace::Oklab));
+        let (oa, ob) = (oklab(a), oklab(b));
+        assert_close(&[mid.l, mid.a, mid.b], &[(oa.l + ob.l) / 2.0f32, (oa.a + ob.a) / 2.0f32, (oa.b + ob.b) / 2.0f32], 1e-3f32, "Oklab");
+
+        let pink = Color::Oklch(Oklch::new(0.7f32, 0.1f32, 350.0f32));
+        let orange = Color::Oklch(Oklch::new(0.8f32, 0.12f32, 50.0f32));
+        let mid = oklch(interpolate(pink, orange, 0.5f32, InterpolationSpace::Oklch));
+        assert_close(&[mid.l, mid.c, mid.h], &[0.75f32, 0.11f32, 20.0f32], 2e-3f32, "Oklch");
+
+        //Alpha is mixed linearly in every space
+        let clear = Color::RGBAf(RgbaF::new(0.0f32, 0.0f32, 1.0f32, 0.0f32));
+        assert_eq!(rgbaf(interpolate(RED, clear, 0.25f32, InterpolationSpace::Oklab))[3], 0.75f32);
+    }
+
+    #[test]
+    fn hues_take_the_short_way_round() {
+        let a = Color::Oklch(Oklch::new(0.6f32, 0.1f32, 350.0f32));
+        let b = Color::Oklch(Oklch::new(0.6f32, 0.1f32, 10.0f32));
+
+        let hue = |t: f32| oklch(interpolate(a, b, t, InterpolationSpace::Oklch)).h;
+        let to_zero = |h: f32| if h > 180.0f32 { h - 360.0f32 } else { h };
+        assert!(to_zero(hue(0.5f32)).abs() < 0.5f32, "{}", hue(0.5f32));
+        assert!((to_zero(hue(0.25f32)) + 5.0f32).abs() < 0.5f32, "{}", hue(0.25f32));
+        assert!((to_zero(hue(0.75f32)) - 5.0f32).abs() < 0.5f32, "{}", hue(0.75f32));
+    }
+
+    #[test]
+    fn grays_take_on_the_other_hue() {
+        let gray = Color::Oklch(Oklch::new(0.6f32, 0.0f32, 0.0f32));
+        let teal = Color::Oklch(Oklch::new(0.6f32, 0.1f32, 200.0f32));
+        let mid = oklch(interpolate(gray, teal, 0.5f32, InterpolationSpace::Oklch));
+        assert!((mid.h - 200.0f32).abs() < 0.5f32 && (mid.c - 0.05f32).abs() < 1e-3f32, "{:?}", mid);
+
+        //White has no saturation in HSL, so white to blue stays blue rather than passing through green
+        let white = Color::SRGB(Srgb::new(255, 255, 255));
+        match Color::convert(interpolate(white, BLUE, 0.5f32, InterpolationSpace::Hsl), ColorFormatName::HSL) {
+            Color::HSL(hsl) => assert!((hsl.h - 240.0f32).abs() < 0.5f32, "{:?}", hsl),
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    #[test]
+    fn lab_mixes_colors_relative_to_different_whites() {
+        let d65 = Color::SRGB(Srgb::new(0x25, 0x63, 0xeb));
+        let d50 = Color::convert(Color::CIEXYZ(match Color::convert(d65, ColorFormatName::CIEXYZ) {
+            Color::CIEXYZ(xyz) => {
+                let adapted = CieXyz::chromatic_adaptation(xyz, CieXyz::D50);
+                CieXyz::new(adapted.x, adapted.y, adapted.z).with_reference_white(CieXyz::D50)
+            },
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }), ColorFormatName::CIELab);
+
+        //The same color, written relative to two whites, mixes with itself to itself
+        let expected = rgbaf(Color::convert(d65, ColorFormatName::RGBAf));
+        for (a, b) in [(d65, d50), (d50, d65)] {
+            assert_close(&rgbaf(interpolate(a, b, 0.5f32, InterpolationSpace::CieLab)), &expected, 1e-3f32, "mixed whites");
+        }
+    }
+}
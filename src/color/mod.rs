@@ -1,4 +1,38 @@
 pub use self::color::*;
+#[allow(unused_imports)]
+pub use self::composite::*;
+#[allow(unused_imports)]
+pub use self::contrast::*;
+#[allow(unused_imports)]
+pub use self::cvd::*;
+#[allow(unused_imports)]
+pub use self::difference::*;
+#[allow(unused_imports)]
+pub use self::gamut::*;
+#[allow(unused_imports)]
+pub use self::image::*;
+pub use self::interpolate::*;
+#[allow(unused_imports)]
+pub use self::palette::*;
+pub use self::parse::*;
+#[allow(unused_imports)]
+pub use self::quantize::*;
 
 mod colorformat;
 pub mod color;
+pub mod composite;
+pub mod contrast;
+mod conversion;
+pub mod cvd;
+pub mod difference;
+pub mod gamut;
+pub mod image;
+pub mod interpolate;
+pub mod palette;
+pub mod parse;
+pub mod quantize;
+#[cfg(feature = "serde")]
+mod serialize;
+
+#[cfg(test)]
+mod tests;
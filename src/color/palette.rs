@@ -0,0 +1,297 @@
+use super::color::*;
+use super::gamut::*;
+use super::interpolate::*;
+
+/*  Deriving palettes from a single color: tonal scales (the 50-900 steps of a design system),
+ *  hue harmonies, and tints, shades and tones. Colors that end up outside of sRGB on the way
+ *  are brought back in by reducing chroma, so lightness and hue stay where they were asked to be.
+ */
+
+/// The steps of a tonal scale, lightest first
+pub const TONAL_STEPS: [u16; 10] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+//The lightness each step aims for, in Oklch (0-1) and CIE LCh (0-100)
+const OKLCH_TONES: [f32; TONAL_STEPS.len()] = [0.97f32, 0.93f32, 0.87f32, 0.79f32, 0.70f32, 0.61f32, 0.52f32, 0.44f32, 0.36f32, 0.28f32];
+const LCH_TONES: [f32; TONAL_STEPS.len()] = [97.0f32, 92.0f32, 84.0f32, 74.0f32, 63.0f32, 53.0f32, 43.0f32, 34.0f32, 26.0f32, 18.0f32];
+
+/// Whose lightness a tonal scale's steps are set in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ToneSpace {
+    #[default]
+    Oklch,
+    CieLch,
+}
+
+/// The color wheel harmonies are taken around. The perceptual wheels keep lightness steady;
+/// HSL and HSV are the wheels most color pickers show.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum HueWheel {
+    Hsl,
+    Hsv,
+    CieLch,
+    #[default]
+    Oklch,
+}
+
+/// Colors that go together, by where they sit on the color wheel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Harmony {
+    /// The opposite hue
+    Complementary,
+    /// Three hues evenly spaced
+    Triadic,
+    /// The hues 30° either side
+    Analogous,
+    /// The two hues either side of the complement
+    SplitComplementary,
+}
+
+impl Harmony {
+    /// How far around the wheel from the base hue each of the other colors is, in degrees
+    pub fn offsets(&self) -> &'static [f32] {
+        match self {
+            Harmony::Complementary => &[180.0f32],
+            Harmony::Triadic => &[120.0f32, 240.0f32],
+            Harmony::Analogous => &[-30.0f32, 30.0f32],
+            Harmony::SplitComplementary => &[150.0f32, 210.0f32],
+        }
+    }
+}
+
+//Back into the format the palette was made from, reducing chroma if it won't fit.
+//The hue and lightness formats have no alpha, so base's is put back on.
+fn in_format_of(base: Color, color: Color) -> Color {
+    let alpha = match Color::convert(base, ColorFormatName::RGBAf) {
+        Color::RGBAf(rgbaf) => rgbaf.a,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    };
+    if alpha >= 1.0f32 {
+        return Color::convert_mapped(color, base.get_format(), GamutMapping::ChromaReduction);
+    }
+
+    match Color::convert_mapped(color, ColorFormatName::RGBAf, GamutMapping::ChromaReduction) {
+        Color::RGBAf(rgbaf) => Color::convert(Color::RGBAf(RgbaF::new(rgbaf.r, rgbaf.g, rgbaf.b, alpha)), base.get_format()),
+        _ => unreachable!("Color::convert_mapped always returns the requested format"),
+    }
+}
+
+fn rotate_hue(h: f32, degrees: f32) -> f32 {
+    (h + degrees).rem_euclid(360.0f32)
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// A tonal scale from base, in the order of TONAL_STEPS. Every step has base's hue and chroma
+    /// (or as much of it as fits in sRGB) at a fixed lightness, with the steps closer together at the
+    /// light end. The step nearest base's own lightness is base itself, so a brand color shows up
+    /// unchanged. The colors are in base's format, with base's alpha.
+    pub fn tonal_scale(base: Color, space: ToneSpace) -> [Color; TONAL_STEPS.len()] {
+        let mut scale = [base; TONAL_STEPS.len()];
+
+        match space {
+            ToneSpace::Oklch => {
+                let oklch = match Color::convert(base, ColorFormatName::Oklch) {
+                    Color::Oklch(oklch) => oklch,
+                    _ => unreachable!("Color::convert always returns the requested format"),
+                };
+
+                let anchor = nearest_tone(&OKLCH_TONES, oklch.l);
+                for (i, tone) in OKLCH_TONES.iter().enumerate() {
+                    if i != anchor {
+                        scale[i] = in_format_of(base, Color::Oklch(Oklch::new(*tone, oklch.c, oklch.h)));
+                    }
+                }
+            },
+            ToneSpace::CieLch => {
+                let lch = match Color::convert(base, ColorFormatName::CIELch) {
+                    Color::CIELch(lch) => lch,
+                    _ => unreachable!("Color::convert always returns the requested format"),
+                };
+
+                let anchor = nearest_tone(&LCH_TONES, lch.l);
+                for (i, tone) in LCH_TONES.iter().enumerate() {
+                    if i != anchor {
+                        let mut step = lch;
+                        step.l = *tone;
+                        scale[i] = in_format_of(base, Color::CIELch(step));
+                    }
+                }
+            },
+        }
+
+        scale
+    }
+
+    /// base followed by the other colors of the harmony, in the order of `Harmony::offsets`.
+    /// Only the hue changes. The colors are in base's format, with base's alpha.
+    pub fn harmony(base: Color, harmony: Harmony, wheel: HueWheel) -> Vec<Color> {
+        let mut colors = vec![base];
+
+        for offset in harmony.offsets() {
+            let rotated = match (wheel, Color::convert(base, wheel_format(wheel))) {
+                (HueWheel::Hsl, Color::HSL(mut hsl)) => {
+                    hsl.h = rotate_hue(hsl.h, *offset);
+                    Color::HSL(hsl)
+                },
+                (HueWheel::Hsv, Color::HSV(mut hsv)) => {
+                    hsv.h = rotate_hue(hsv.h, *offset);
+                    Color::HSV(hsv)
+                },
+                (HueWheel::CieLch, Color::CIELch(mut lch)) => {
+                    lch.h = rotate_hue(lch.h, *offset);
+                    Color::CIELch(lch)
+                },
+                (HueWheel::Oklch, Color::Oklch(mut oklch)) => {
+                    oklch.h = rotate_hue(oklch.h, *offset);
+                    Color::Oklch(oklch)
+                },
+                _ => unreachable!("Color::convert always returns the requested format"),
+            };
+
+            colors.push(in_format_of(base, rotated));
+        }
+
+        colors
+    }
+
+    /// Mixes a and b- amount = 0 gives a, amount = 1 gives b. The result is `Color::RGBAf`, like `interpolate`.
+    pub fn mix(a: Color, b: Color, amount: f32, space: InterpolationSpace) -> Color {
+        interpolate(a, b, amount.clamp(0.0f32, 1.0f32), space)
+    }
+
+    /// color mixed with white- amount = 1 is white
+    pub fn tint(color: Color, amount: f32, space: InterpolationSpace) -> Color {
+        Color::mix(color, Color::SRGBf(SrgbF::new(1.0f32, 1.0f32, 1.0f32)), amount, space)
+    }
+
+    /// color mixed with black- amount = 1 is black
+    pub fn shade(color: Color, amount: f32, space: InterpolationSpace) -> Color {
+        Color::mix(color, Color::SRGBf(SrgbF::new(0.0f32, 0.0f32, 0.0f32)), amount, space)
+    }
+
+    /// color mixed with mid gray (#808080)- amount = 1 is the gray
+    pub fn tone(color: Color, amount: f32, space: InterpolationSpace) -> Color {
+        Color::mix(color, Color::SRGB(Srgb::new(128, 128, 128)), amount, space)
+    }
+}
+
+fn nearest_tone(tones: &[f32], lightness: f32) -> usize {
+    let mut nearest = 0;
+    for (i, tone) in tones.iter().enumerate() {
+        if (tone - lightness).abs() < (tones[nearest] - lightness).abs() {
+            nearest = i;
+        }
+    }
+
+    nearest
+}
+
+fn wheel_format(wheel: HueWheel) -> ColorFormatName {
+    match wheel {
+        HueWheel::Hsl => ColorFormatName::HSL,
+        HueWheel::Hsv => ColorFormatName::HSV,
+        HueWheel::CieLch => ColorFormatName::CIELch,
+        HueWheel::Oklch => ColorFormatName::Oklch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oklch(color: Color) -> Oklch {
+        match Color::convert(color, ColorFormatName::Oklch) {
+            Color::Oklch(oklch) => oklch,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    fn hue_difference(a: f32, b: f32) -> f32 {
+        (b - a + 540.0f32).rem_euclid(360.0f32) - 180.0f32
+    }
+
+    const BRAND: Color = Color::SRGB(Srgb { r: 0x25, g: 0x63, b: 0xeb });
+
+    #[test]
+    fn tonal_scales_go_from_light_to_dark_through_the_base() {
+        for space in [ToneSpace::Oklch, ToneSpace::CieLch] {
+            let scale = Color::tonal_scale(BRAND, space);
+            assert!(scale.contains(&BRAND), "{:?} lost the base color", space);
+
+            for pair in scale.windows(2) {
+                assert!(oklch(pair[0]).l > oklch(pair[1]).l, "{:?}: {:?} isn't lighter than {:?}", space, pair[0], pair[1]);
+            }
+
+            //Gamut mapping keeps Oklch hue, so an Oklch scale holds its hue apart from the 8-bit rounding
+            if space == ToneSpace::Oklch {
+                let hue = oklch(BRAND).h;
+                for step in scale[2..].iter() {
+                    assert!(hue_difference(hue, oklch(*step).h).abs() < 6.0f32, "{:?} drifted from {}", step, hue);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn harmonies_rotate_only_the_hue() {
+        let base = Color::Oklch(Oklch::new(0.7f32, 0.1f32, 40.0f32));
+
+        let triad = Color::harmony(base, Harmony::Triadic, HueWheel::Oklch);
+        assert_eq!(triad.len(), 3);
+        assert_eq!(triad[0], base);
+        for (color, expected) in triad[1..].iter().zip([160.0f32, 280.0f32]) {
+            let rotated = oklch(*color);
+            assert!((rotated.h - expected).abs() < 1e-3f32 && rotated.l == 0.7f32 && rotated.c == 0.1f32, "{:?}", rotated);
+        }
+
+        let analogous = Color::harmony(Color::HSL(Hsl::new(10.0f32, 0.5f32, 0.5f32)), Harmony::Analogous, HueWheel::Hsl);
+        let hues: Vec<f32> = analogous.iter().map(|c| match c {
+            Color::HSL(hsl) => hsl.h,
+            _ => unreachable!("harmony keeps the base's format"),
+        }).collect();
+        assert_eq!(hues, vec![10.0f32, 340.0f32, 40.0f32]);
+
+        assert_eq!(Color::harmony(BRAND, Harmony::SplitComplementary, HueWheel::Hsv).len(), 3);
+        assert!(Color::harmony(BRAND, Harmony::Complementary, HueWheel::CieLch).iter().all(|c| c.get_format() == ColorFormatName::SRGB));
+    }
+
+    #[test]
+    fn palettes_keep_the_base_alpha() {
+        let alpha = |color: Color| match color {
+            Color::RGBAf(rgbaf) => rgbaf.a,
+            Color::RGBA(rgba) => rgba.a as f32 / u8::MAX as f32,
+            other => panic!("{:?} lost the base's format", other),
+        };
+
+        let rgbf: RgbF = Srgb::new(0x25, 0x63, 0xeb).into();
+        let translucent = Color::RGBAf(RgbaF::new(rgbf.r, rgbf.g, rgbf.b, 0.5f32));
+        for space in [ToneSpace::Oklch, ToneSpace::CieLch] {
+            assert!(Color::tonal_scale(translucent, space).iter().all(|c| alpha(*c) == 0.5f32), "{:?}", space);
+        }
+        for wheel in [HueWheel::Hsl, HueWheel::Hsv, HueWheel::CieLch, HueWheel::Oklch] {
+            assert!(Color::harmony(translucent, Harmony::Triadic, wheel).iter().all(|c| alpha(*c) == 0.5f32), "{:?}", wheel);
+        }
+
+        let rgba = Color::RGBA(Rgba::new(20, 60, 200, 64));
+        assert!(Color::harmony(rgba, Harmony::Complementary, HueWheel::Oklch).iter().all(|c| alpha(*c) == 64.0f32 / 255.0f32));
+    }
+
+    #[test]
+    fn tints_shades_and_tones_end_at_white_black_and_gray() {
+        let srgbf = |color: Color| match Color::convert(color, ColorFormatName::SRGBf) {
+            Color::SRGBf(srgbf) => [srgbf.r, srgbf.g, srgbf.b],
+            _ => unreachable!("Color::convert always returns the requested format"),
+        };
+        let close = |a: [f32; 3], b: [f32; 3]| (0..3).all(|i| (a[i] - b[i]).abs() < 1e-3f32);
+
+        for space in [InterpolationSpace::Srgb, InterpolationSpace::LinearRgb, InterpolationSpace::Oklab] {
+            assert!(close(srgbf(Color::tint(BRAND, 1.0f32, space)), [1.0f32, 1.0f32, 1.0f32]));
+            assert!(close(srgbf(Color::shade(BRAND, 1.0f32, space)), [0.0f32, 0.0f32, 0.0f32]));
+            assert!(close(srgbf(Color::tone(BRAND, 1.0f32, space)), srgbf(Color::SRGB(Srgb::new(128, 128, 128)))));
+
+            //Halfway is halfway in lightness, give or take the space
+            let l = oklch(Color::tint(BRAND, 0.5f32, space)).l;
+            assert!(l > oklch(BRAND).l && l < 1.0f32);
+        }
+    }
+}
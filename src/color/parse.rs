@@ -0,0 +1,954 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::color::*;
+
+/// A failure to parse a color. `column` is 1-based and counts characters, not bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl ColorParseError {
+    fn new(column: usize, message: &str) -> ColorParseError {
+        ColorParseError {
+            column,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+/*  Parses the textual color notations the color module has a format for:
+ *      #rgb, #rrggbb           -> SRGB
+ *      #rgba, #rrggbbaa        -> RGBAf (linear, since there is no gamma-compressed format with alpha)
+ *      red, rebeccapurple...   -> SRGB, any of the CSS named colors ('transparent' is RGBAf)
+ *      rgb(r, g, b)            -> SRGB, channels 0-255 or percentages
+ *      rgba(r, g, b, a)        -> RGBAf
+ *      hsl(h, s, l)            -> SRGBf, as in CSS
+ *      hsv(h, s, v)            -> SRGBf, the same way
+ *      hwb(h, w, b)            -> HWB
+ *      lab(l, a, b)            -> CIELab, relative to D50 as in CSS
+ *      lch(l, c, h)            -> CIELch, relative to D50 as in CSS
+ *      luv(l, u, v)            -> CIELuv
+ *      lchuv(l, c, h)          -> CIELchUv
+ *      xyz(x, y, z)            -> CIEXYZ
+ *      xyy(x, y, Y)            -> CIExyY
+ *      oklab(l, a, b)          -> Oklab
+ *      oklch(l, c, h)          -> Oklch
+ *      color(srgb r g b)       -> SRGBf, or RGBf for srgb-linear and CIEXYZ for xyz/xyz-d65.
+ *                                 display-p3, rec2020, a98-rgb and prophoto-rgb also become CIEXYZ.
+ *      gray(y)                 -> Grayf
+ *      gray8(y), gray16(y)     -> Gray8, Gray16
+ *      rgb-linear(r, g, b)     -> RGB, or RGBA with a fourth argument. Channels are the stored integers.
+ *      rgb48(r, g, b)          -> RGB48, or RGBA64 as rgba64(r, g, b, a)
+ *      device-cmyk(c, m, y, k) -> CMYK, as in CSS Color 5
+ *      ycbcr(y, cb, cr)        -> YCbCr, BT.601 full range code values. Others name their matrix and range
+ *                                 after a '/', as in ycbcr(16 128 128 / bt709 limited).
+ *      hsl-linear(h, s, l)     -> HSL, and hsv-linear(h, s, v) -> HSV
+ *  hsl(), hsv() and hwb() work on gamma-encoded sRGB, like CSS, and read plain numbers as percentages
+ *  the way CSS's modern syntax does. The HSL and HSV formats are over linear RGB, so they have the
+ *  -linear notations of their own, where plain numbers are taken as-is.
+ *  lab() and lch() are relative to D50, like CSS. The other CIE notations are relative to D65.
+ *  Arguments can be separated by commas or whitespace, and any function takes an alpha after a '/',
+ *  as in rgb(255 0 0 / 50%)- since only RGBAf can hold both, the color is converted to it.
+ *  For ycbcr() the alpha goes after the matrix and range.
+ *  Hues can be given in deg, rad, grad or turn.
+ */
+pub fn parse_color(text: &str) -> Result<Color, ColorParseError> {
+    let mut cursor = Cursor::new(text);
+    cursor.skip_whitespace();
+
+    let color = if cursor.peek() == Some('#') {
+        cursor.next();
+        parse_hex(&mut cursor)?
+    } else {
+        let name_column = cursor.column();
+        let name = cursor.take_while(|c| c.is_ascii_alphanumeric() || c == '-').to_ascii_lowercase();
+        if name.is_empty() {
+            return Err(ColorParseError::new(name_column, "Expected a color"));
+        }
+
+        cursor.skip_whitespace();
+        if cursor.peek() != Some('(') {
+            return named_color(&name).ok_or_else(|| {
+                ColorParseError::new(name_column, &format!("Unknown color '{}'", name))
+            });
+        }
+        cursor.next();
+
+        if name == "color" {
+            cursor.skip_whitespace();
+            let space_column = cursor.column();
+            let space = cursor.take_while(|c| c.is_ascii_alphanumeric() || c == '-').to_ascii_lowercase();
+            let args = parse_arguments(&mut cursor)?;
+            parse_color_function(&space, space_column, &args)?
+        } else {
+            let args = parse_arguments(&mut cursor)?;
+            parse_function(&name, name_column, &args)?
+        }
+    };
+
+    cursor.skip_whitespace();
+    if let Some(c) = cursor.peek() {
+        return Err(ColorParseError::new(cursor.column(), &format!("Unexpected '{}' after color", c)));
+    }
+
+    Ok(color)
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(text: &str) -> Result<Color, ColorParseError> {
+        parse_color(text)
+    }
+}
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(text: &str) -> Cursor {
+        Cursor {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    fn column(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> String {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Unit {
+    None,
+    Percent,
+    Degrees,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Argument {
+    value: f32,
+    unit: Unit,
+    column: usize,
+}
+
+impl Argument {
+    //A fraction in [0, 1]: either a percentage, or a plain number that is already a fraction
+    fn fraction(&self) -> f32 {
+        match self.unit {
+            Unit::Percent => self.value / 100.0f32,
+            _ => self.value,
+        }
+    }
+
+    //A percentage, where a plain number counts as one too- as in CSS's modern hsl() and hwb()
+    fn percentage(&self) -> f32 {
+        self.value / 100.0f32
+    }
+
+    //An 8-bit channel: either a percentage or a plain number in [0, 255]
+    fn channel8(&self) -> f32 {
+        match self.unit {
+            Unit::Percent => self.value / 100.0f32,
+            _ => self.value / (u8::MAX as f32),
+        }
+    }
+
+    fn plain(&self) -> Result<f32, ColorParseError> {
+        match self.unit {
+            Unit::None => Ok(self.value),
+            _ => Err(ColorParseError::new(self.column, "Unexpected unit")),
+        }
+    }
+
+    //A plain number, or a percentage of whatever 100% stands for in that position
+    fn scaled(&self, hundred_percent: f32) -> Result<f32, ColorParseError> {
+        match self.unit {
+            Unit::Percent => Ok(self.value / 100.0f32 * hundred_percent),
+            _ => self.plain(),
+        }
+    }
+
+    fn hue(&self) -> Result<f32, ColorParseError> {
+        match self.unit {
+            Unit::Percent => Err(ColorParseError::new(self.column, "Hue can't be a percentage")),
+            _ => Ok(self.value.rem_euclid(360.0f32)),
+        }
+    }
+
+    //A channel stored as an integer, written out exactly as it's stored
+    fn whole(&self, max: u16) -> Result<u16, ColorParseError> {
+        let value = self.plain()?;
+        if value.fract() == 0.0f32 && value >= 0.0f32 && value <= max as f32 {
+            Ok(value as u16)
+        } else {
+            Err(ColorParseError::new(self.column, &format!("Expected a whole number from 0 to {}", max)))
+        }
+    }
+}
+
+struct Arguments {
+    values: Vec<Argument>,
+    //Words after the '/', ahead of any alpha, with their columns
+    keywords: Vec<(String, usize)>,
+    alpha: Option<Argument>,
+}
+
+fn parse_hex(cursor: &mut Cursor) -> Result<Color, ColorParseError> {
+    let start = cursor.column();
+    let digits = cursor.take_while(|c| c.is_ascii_hexdigit());
+
+    let nibble = |idx: usize| -> u8 { digits[idx..idx + 1].chars().next().unwrap().to_digit(16).unwrap() as u8 };
+    let byte = |idx: usize| -> u8 { nibble(idx) * 16 + nibble(idx + 1) };
+
+    let with_alpha = |srgb: Srgb, a: u8| -> Color {
+        let rgbf: RgbF = srgb.into();
+        Color::RGBAf(RgbaF::new(rgbf.r, rgbf.g, rgbf.b, (a as f32) / (u8::MAX as f32)))
+    };
+
+    match digits.len() {
+        3 => Ok(Color::SRGB(Srgb::new(nibble(0) * 17, nibble(1) * 17, nibble(2) * 17))),
+        4 => Ok(with_alpha(Srgb::new(nibble(0) * 17, nibble(1) * 17, nibble(2) * 17), nibble(3) * 17)),
+        6 => Ok(Color::SRGB(Srgb::new(byte(0), byte(2), byte(4)))),
+        8 => Ok(with_alpha(Srgb::new(byte(0), byte(2), byte(4)), byte(6))),
+        _ => {
+            if let Some(c) = cursor.peek().filter(|c| !c.is_whitespace()) {
+                Err(ColorParseError::new(cursor.column(), &format!("Invalid hex digit '{}'", c)))
+            } else {
+                Err(ColorParseError::new(start, "Hex colors need 3, 4, 6 or 8 digits"))
+            }
+        },
+    }
+}
+
+fn parse_number(cursor: &mut Cursor) -> Result<Argument, ColorParseError> {
+    let column = cursor.column();
+    let number = cursor.take_while(|c| c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e' || c == 'E');
+    let value = number.parse::<f32>()
+        .map_err(|_| ColorParseError::new(column, "Expected a number"))?;
+
+    //Angles all end up in degrees, since that's what every format here stores
+    let unit_column = cursor.column();
+    let unit = cursor.take_while(|c| c.is_ascii_alphabetic() || c == '%');
+    let (value, unit) = match unit.to_ascii_lowercase().as_str() {
+        "" => (value, Unit::None),
+        "%" => (value, Unit::Percent),
+        "deg" => (value, Unit::Degrees),
+        "rad" => (value.to_degrees(), Unit::Degrees),
+        "grad" => (value * 0.9f32, Unit::Degrees),
+        "turn" => (value * 360.0f32, Unit::Degrees),
+        _ => {
+            return Err(ColorParseError::new(unit_column, &format!("Unknown unit '{}'", unit)));
+        },
+    };
+
+    Ok(Argument { value, unit, column })
+}
+
+//Parses everything after the opening parenthesis, including the closing one
+fn parse_arguments(cursor: &mut Cursor) -> Result<Arguments, ColorParseError> {
+    let mut args = Arguments { values: Vec::new(), keywords: Vec::new(), alpha: None };
+
+    loop {
+        cursor.skip_whitespace();
+        match cursor.peek() {
+            Some(')') => {
+                cursor.next();
+                return Ok(args);
+            },
+            None => {
+                return Err(ColorParseError::new(cursor.column(), "Expected ')'"));
+            },
+            Some(',') if !args.values.is_empty() => {
+                cursor.next();
+                cursor.skip_whitespace();
+            },
+            Some('/') if !args.values.is_empty() => {
+                cursor.next();
+                cursor.skip_whitespace();
+                while matches!(cursor.peek(), Some(c) if c.is_ascii_alphabetic()) {
+                    let column = cursor.column();
+                    let keyword = cursor.take_while(|c| c.is_ascii_alphanumeric()).to_ascii_lowercase();
+                    args.keywords.push((keyword, column));
+                    cursor.skip_whitespace();
+                }
+
+                if cursor.peek() != Some(')') || args.keywords.is_empty() {
+                    args.alpha = Some(parse_number(cursor)?);
+                }
+
+                cursor.skip_whitespace();
+                if cursor.peek() != Some(')') {
+                    return Err(ColorParseError::new(cursor.column(), "Expected ')' after alpha"));
+                }
+                continue;
+            },
+            _ => {}
+        }
+
+        args.values.push(parse_number(cursor)?);
+    }
+}
+
+fn expect_count(name: &str, column: usize, args: &[Argument], counts: &[usize]) -> Result<(), ColorParseError> {
+    if counts.contains(&args.len()) {
+        Ok(())
+    } else {
+        let expected: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+        Err(ColorParseError::new(column, &format!("{}() takes {} arguments, got {}", name, expected.join(" or "), args.len())))
+    }
+}
+
+fn with_alpha(color: Color, alpha: Option<Argument>) -> Color {
+    match (alpha, Color::convert(color, ColorFormatName::RGBAf)) {
+        (None, _) => color,
+        (Some(alpha), Color::RGBAf(rgbaf)) => Color::RGBAf(RgbaF::new(rgbaf.r, rgbaf.g, rgbaf.b, alpha.fraction())),
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn d65() -> CieXyz {
+    CieXyz::default()
+}
+
+//Only ycbcr() takes keywords
+fn expect_no_keywords(arguments: &Arguments) -> Result<(), ColorParseError> {
+    match arguments.keywords.first() {
+        Some((keyword, column)) => Err(ColorParseError::new(*column, &format!("Unexpected '{}'", keyword))),
+        None => Ok(()),
+    }
+}
+
+fn parse_function(name: &str, column: usize, arguments: &Arguments) -> Result<Color, ColorParseError> {
+    let args = &arguments.values;
+    if name != "ycbcr" {
+        expect_no_keywords(arguments)?;
+    }
+
+    let color = match name {
+        "rgb" | "rgba" => {
+            expect_count(name, column, args, &[3, 4])?;
+            let (r, g, b) = (args[0].channel8(), args[1].channel8(), args[2].channel8());
+
+            if args.len() == 4 || arguments.alpha.is_some() {
+                let rgbf: RgbF = SrgbF::new(r, g, b).into();
+                let a = if args.len() == 4 { args[3].fraction() } else { 1.0f32 };
+                Color::RGBAf(RgbaF::new(rgbf.r, rgbf.g, rgbf.b, a))
+            } else {
+                let to_u8 = |c: f32| (c.clamp(0.0f32, 1.0f32) * (u8::MAX as f32)).round() as u8;
+                Color::SRGB(Srgb::new(to_u8(r), to_u8(g), to_u8(b)))
+            }
+        },
+        "hsv" | "hsl" => {
+            expect_count(name, column, args, &[3])?;
+            let h = args[0].hue()?;
+            let (s, v_or_l) = (args[1].percentage(), args[2].percentage());
+
+            //The formats' own math, on the gamma-encoded channels rather than linear ones
+            let encoded: RgbF = if name == "hsv" {
+                Hsv::new(h, s, v_or_l).into()
+            } else {
+                Hsl::new(h, s, v_or_l).into()
+            };
+            Color::SRGBf(SrgbF::new(encoded.r, encoded.g, encoded.b))
+        },
+        "hsv-linear" | "hsl-linear" => {
+            expect_count(name, column, args, &[3])?;
+            let h = args[0].hue()?;
+            let (s, v_or_l) = (args[1].fraction(), args[2].fraction());
+
+            if name == "hsv-linear" {
+                Color::HSV(Hsv::new(h, s, v_or_l))
+            } else {
+                Color::HSL(Hsl::new(h, s, v_or_l))
+            }
+        },
+        "hwb" => {
+            expect_count(name, column, args, &[3])?;
+            let h = args[0].hue()?;
+            Color::HWB(Hwb::new(h, args[1].percentage(), args[2].percentage()))
+        },
+        "device-cmyk" => {
+            expect_count(name, column, args, &[4])?;
+            Color::CMYK(Cmyk::new(args[0].fraction(), args[1].fraction(), args[2].fraction(), args[3].fraction()))
+        },
+        "ycbcr" => {
+            expect_count(name, column, args, &[3])?;
+            let max = u8::MAX as u16;
+            let mut ycbcr = YCbCr::new(args[0].whole(max)? as u8, args[1].whole(max)? as u8, args[2].whole(max)? as u8);
+
+            for (keyword, column) in &arguments.keywords {
+                ycbcr = match keyword.as_str() {
+                    "bt601" => ycbcr.with_matrix(YCbCrMatrix::Bt601),
+                    "bt709" => ycbcr.with_matrix(YCbCrMatrix::Bt709),
+                    "bt2020" => ycbcr.with_matrix(YCbCrMatrix::Bt2020),
+                    "full" => ycbcr.with_range(YCbCrRange::Full),
+                    "limited" => ycbcr.with_range(YCbCrRange::Limited),
+                    _ => {
+                        return Err(ColorParseError::new(*column, &format!("Unknown matrix or range '{}'", keyword)));
+                    },
+                };
+            }
+            Color::YCbCr(ycbcr)
+        },
+        "lab" => {
+            expect_count(name, column, args, &[3])?;
+            //CSS allows lightness as a percentage of 100, and a and b as percentages of 125
+            let lab = CieLab::new(args[0].scaled(100.0f32)?, args[1].scaled(125.0f32)?, args[2].scaled(125.0f32)?);
+            Color::CIELab(lab.with_reference_white(CieXyz::D50))
+        },
+        "lch" => {
+            expect_count(name, column, args, &[3])?;
+            let lch = CieLch::new(args[0].scaled(100.0f32)?, args[1].scaled(150.0f32)?, args[2].hue()?);
+            Color::CIELch(lch.with_reference_white(CieXyz::D50))
+        },
+        "luv" => {
+            expect_count(name, column, args, &[3])?;
+            let luv = CieLuv::new(args[0].scaled(100.0f32)?, args[1].plain()?, args[2].plain()?);
+            Color::CIELuv(luv.with_reference_white(d65()))
+        },
+        "lchuv" => {
+            expect_count(name, column, args, &[3])?;
+            let lch = CieLchUv::new(args[0].scaled(100.0f32)?, args[1].plain()?, args[2].hue()?);
+            Color::CIELchUv(lch.with_reference_white(d65()))
+        },
+        "xyz" => {
+            expect_count(name, column, args, &[3])?;
+            let xyz = CieXyz::new(args[0].fraction(), args[1].fraction(), args[2].fraction());
+            Color::CIEXYZ(xyz.with_reference_white(d65()))
+        },
+        "xyy" => {
+            expect_count(name, column, args, &[3])?;
+            let xyy = CieXyY::new(args[0].plain()?, args[1].plain()?, args[2].fraction());
+            Color::CIExyY(xyy.with_reference_white(d65()))
+        },
+        "oklab" => {
+            expect_count(name, column, args, &[3])?;
+            //100% is 1 for lightness and 0.4 for a and b, as in CSS
+            Color::Oklab(Oklab::new(args[0].scaled(1.0f32)?, args[1].scaled(0.4f32)?, args[2].scaled(0.4f32)?))
+        },
+        "oklch" => {
+            expect_count(name, column, args, &[3])?;
+            Color::Oklch(Oklch::new(args[0].scaled(1.0f32)?, args[1].scaled(0.4f32)?, args[2].hue()?))
+        },
+        "gray" => {
+            expect_count(name, column, args, &[1])?;
+            Color::Grayf(GrayF::new(args[0].fraction()))
+        },
+        "gray8" => {
+            expect_count(name, column, args, &[1])?;
+            Color::Gray8(Gray8::new(args[0].whole(u8::MAX as u16)? as u8))
+        },
+        "gray16" => {
+            expect_count(name, column, args, &[1])?;
+            Color::Gray16(Gray16::new(args[0].whole(u16::MAX)?))
+        },
+        "rgb-linear" | "rgba-linear" => {
+            expect_count(name, column, args, &[3, 4])?;
+            let max = u8::MAX as u16;
+            let (r, g, b) = (args[0].whole(max)? as u8, args[1].whole(max)? as u8, args[2].whole(max)? as u8);
+
+            if args.len() == 4 {
+                Color::RGBA(Rgba::new(r, g, b, args[3].whole(max)? as u8))
+            } else {
+                Color::RGB(Rgb::new(r, g, b))
+            }
+        },
+        "rgb48" | "rgba64" => {
+            expect_count(name, column, args, &[3, 4])?;
+            let max = u16::MAX;
+            let (r, g, b) = (args[0].whole(max)?, args[1].whole(max)?, args[2].whole(max)?);
+
+            if args.len() == 4 {
+                Color::RGBA64(Rgba64::new(r, g, b, args[3].whole(max)?))
+            } else {
+                Color::RGB48(Rgb48::new(r, g, b))
+            }
+        },
+        _ => {
+            return Err(ColorParseError::new(column, &format!("Unknown color function '{}'", name)));
+        },
+    };
+
+    Ok(with_alpha(color, arguments.alpha))
+}
+
+//color(space c1 c2 c3), for the predefined CSS spaces there's a format for
+fn parse_color_function(space: &str, column: usize, arguments: &Arguments) -> Result<Color, ColorParseError> {
+    let args = &arguments.values;
+    expect_no_keywords(arguments)?;
+    expect_count("color", column, args, &[3])?;
+    let (c1, c2, c3) = (args[0].fraction(), args[1].fraction(), args[2].fraction());
+
+    let color = match space {
+        "srgb" => Color::SRGBf(SrgbF::new(c1, c2, c3)),
+        "srgb-linear" => {
+            if let Some(alpha) = arguments.alpha {
+                //Build it directly rather than converting, so linear channels come back exactly
+                return Ok(Color::RGBAf(RgbaF::new(c1, c2, c3, alpha.fraction())));
+            }
+            Color::RGBf(RgbF::new(c1, c2, c3))
+        },
+        "xyz" | "xyz-d65" => Color::CIEXYZ(CieXyz::new(c1, c2, c3).with_reference_white(d65())),
+        "display-p3" => Color::from_rgb_space(&RgbSpace::display_p3(), [c1, c2, c3]),
+        "rec2020" => Color::from_rgb_space(&RgbSpace::rec2020(), [c1, c2, c3]),
+        "a98-rgb" => Color::from_rgb_space(&RgbSpace::adobe_rgb(), [c1, c2, c3]),
+        "prophoto-rgb" => Color::from_rgb_space(&RgbSpace::prophoto(), [c1, c2, c3]),
+        "" => {
+            return Err(ColorParseError::new(column, "Expected a color space"));
+        },
+        _ => {
+            return Err(ColorParseError::new(column, &format!("Unsupported color space '{}'", space)));
+        },
+    };
+
+    Ok(with_alpha(color, arguments.alpha))
+}
+
+fn named_color(name: &str) -> Option<Color> {
+    if name == "transparent" {
+        return Some(Color::RGBAf(RgbaF::new(0.0f32, 0.0f32, 0.0f32, 0.0f32)));
+    }
+
+    NAMED_COLORS.iter()
+        .find(|(named, _)| *named == name)
+        .map(|(_, hex)| Color::SRGB(Srgb::new((hex >> 16) as u8, (hex >> 8) as u8, *hex as u8)))
+}
+
+//No reference white means D65
+fn is_white(ref_xyz: Option<(f32, f32, f32)>, white: CieXyz) -> bool {
+    let (x, y, z) = ref_xyz.unwrap_or((d65().x, d65().y, d65().z));
+    x == white.x && y == white.y && z == white.z
+}
+
+//The white the parser reads a CIE notation as relative to
+fn notation_white(color: &Color) -> CieXyz {
+    match color {
+        Color::CIELab(_) | Color::CIELch(_) => CieXyz::D50,
+        _ => d65(),
+    }
+}
+
+fn adapted_to(color: Color, white: CieXyz) -> Color {
+    let xyz = match Color::convert(color, ColorFormatName::CIEXYZ) {
+        Color::CIEXYZ(xyz) => xyz,
+        _ => unreachable!("Color::convert always returns the requested format"),
+    };
+
+    let adapted = CieXyz::chromatic_adaptation(xyz, white);
+    let adapted = CieXyz::new(adapted.x, adapted.y, adapted.z).with_reference_white(white);
+    Color::convert(Color::CIEXYZ(adapted), color.get_format())
+}
+
+/*  Writes a color in the notation parse_color reads back into the same format with the same values-
+ *  CSS notation where there is one, and the extensions above for formats CSS doesn't have.
+ *  Floats are written with as many digits as it takes to read back exactly- apart from hwb()'s
+ *  percentages, which can come back a rounding step off.
+ */
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        //CIE colors relative to a white other than the one their notation is read with are adapted to it first
+        let white = notation_white(self);
+        let other_white = match self {
+            Color::CIEXYZ(xyz) => !is_white(xyz.ref_xyz, white),
+            Color::CIELab(lab) => !is_white(lab.ref_xyz, white),
+            Color::CIELch(lch) => !is_white(lch.ref_xyz, white),
+            Color::CIELuv(luv) => !is_white(luv.ref_xyz, white),
+            Color::CIELchUv(lch) => !is_white(lch.ref_xyz, white),
+            Color::CIExyY(xyy) => !is_white(xyy.ref_xyz, white),
+            _ => false,
+        };
+        if other_white {
+            return write!(f, "{}", adapted_to(*self, white));
+        }
+
+        //ycbcr() without a matrix and range is BT.601 full range, so anything else names both
+        if let Color::YCbCr(ycbcr) = self {
+            if ycbcr.matrix != YCbCrMatrix::Bt601 || ycbcr.range != YCbCrRange::Full {
+                let matrix = match ycbcr.matrix {
+                    YCbCrMatrix::Bt601 => "bt601",
+                    YCbCrMatrix::Bt709 => "bt709",
+                    YCbCrMatrix::Bt2020 => "bt2020",
+                };
+                let range = match ycbcr.range {
+                    YCbCrRange::Full => "full",
+                    YCbCrRange::Limited => "limited",
+                };
+                return write!(f, "ycbcr({} {} {} / {} {})", ycbcr.y, ycbcr.cb, ycbcr.cr, matrix, range);
+            }
+        }
+
+        match self {
+            Color::Gray8(gray) => write!(f, "gray8({})", gray.luminance),
+            Color::Gray16(gray) => write!(f, "gray16({})", gray.luminance),
+            Color::Grayf(gray) => write!(f, "gray({})", gray.luminance),
+            Color::RGB(rgb) => write!(f, "rgb-linear({} {} {})", rgb.r, rgb.g, rgb.b),
+            Color::SRGB(srgb) => write!(f, "#{:02x}{:02x}{:02x}", srgb.r, srgb.g, srgb.b),
+            Color::RGB48(rgb_w) => write!(f, "rgb48({} {} {})", rgb_w.r, rgb_w.g, rgb_w.b),
+            Color::RGBA(rgba) => write!(f, "rgba-linear({} {} {} {})", rgba.r, rgba.g, rgba.b, rgba.a),
+            Color::RGBA64(rgba_w) => write!(f, "rgba64({} {} {} {})", rgba_w.r, rgba_w.g, rgba_w.b, rgba_w.a),
+            Color::RGBf(rgbf) => write!(f, "color(srgb-linear {} {} {})", rgbf.r, rgbf.g, rgbf.b),
+            Color::SRGBf(srgbf) => write!(f, "color(srgb {} {} {})", srgbf.r, srgbf.g, srgbf.b),
+            Color::RGBAf(rgbaf) => write!(f, "color(srgb-linear {} {} {} / {})", rgbaf.r, rgbaf.g, rgbaf.b, rgbaf.a),
+            Color::HSV(hsv) => write!(f, "hsv-linear({} {} {})", hsv.h, hsv.s, hsv.v),
+            Color::HSL(hsl) => write!(f, "hsl-linear({} {} {})", hsl.h, hsl.s, hsl.l),
+            Color::CIEXYZ(xyz) => write!(f, "xyz({} {} {})", xyz.x, xyz.y, xyz.z),
+            Color::CIELab(lab) => write!(f, "lab({} {} {})", lab.l, lab.a, lab.b),
+            Color::CIELch(lch) => write!(f, "lch({} {} {})", lch.l, lch.c, lch.h),
+            Color::CIELuv(luv) => write!(f, "luv({} {} {})", luv.l, luv.u, luv.v),
+            Color::CIELchUv(lch) => write!(f, "lchuv({} {} {})", lch.l, lch.c, lch.h),
+            Color::CIExyY(xyy) => write!(f, "xyy({} {} {})", xyy.x, xyy.y, xyy.luminance),
+            Color::Oklab(oklab) => write!(f, "oklab({} {} {})", oklab.l, oklab.a, oklab.b),
+            Color::Oklch(oklch) => write!(f, "oklch({} {} {})", oklch.l, oklch.c, oklch.h),
+            Color::CMYK(cmyk) => write!(f, "device-cmyk({} {} {} {})", cmyk.c, cmyk.m, cmyk.y, cmyk.k),
+            Color::YCbCr(ycbcr) => write!(f, "ycbcr({} {} {})", ycbcr.y, ycbcr.cb, ycbcr.cr),
+            Color::HWB(hwb) => write!(f, "hwb({} {}% {}%)", hwb.h, hwb.w * 100.0f32, hwb.b * 100.0f32),
+        }
+    }
+}
+
+//The CSS named colors (CSS Color Module Level 4, section 6.1), as 0xRRGGBB in sRGB
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::conversion::FORMATS;
+
+    fn parse(text: &str) -> Color {
+        text.parse::<Color>().unwrap_or_else(|err| panic!("{}: {}", text, err))
+    }
+
+    fn srgbf(color: Color) -> [f32; 3] {
+        match Color::convert(color, ColorFormatName::SRGBf) {
+            Color::SRGBf(srgbf) => [srgbf.r, srgbf.g, srgbf.b],
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    }
+
+    fn assert_srgb(color: Color, expected: [u8; 3], what: &str) {
+        let actual = srgbf(color);
+        for i in 0..3 {
+            assert!((actual[i] * 255.0f32 - expected[i] as f32).abs() < 1.0f32, "{}: got {:?}, expected {:?}", what, actual, expected);
+        }
+    }
+
+    fn error(text: &str) -> ColorParseError {
+        text.parse::<Color>().expect_err(text)
+    }
+
+    #[test]
+    fn display_reads_back_for_every_format() {
+        let mut samples: Vec<Color> = FORMATS.iter()
+            .map(|format| Color::convert(Color::SRGB(Srgb::new(0x25, 0x63, 0xeb)), *format))
+            .collect();
+        samples.push(Color::CIELab(CieLab::new(52.0f32, -20.0f32, 31.5f32).with_reference_white(CieXyz::D50)));
+        samples.push(Color::CIELch(CieLch::new(52.0f32, 37.3f32, 122.4f32).with_reference_white(CieXyz::D50)));
+        samples.push(Color::CIEXYZ(CieXyz::new(0.3f32, 0.2f32, 0.1f32).with_reference_white(CieXyz::D65)));
+        samples.push(Color::RGBAf(RgbaF::new(0.25f32, 0.5f32, 0.75f32, 0.4f32)));
+        samples.push(Color::YCbCr(YCbCr::new(16, 128, 128).with_matrix(YCbCrMatrix::Bt709).with_range(YCbCrRange::Limited)));
+        samples.push(Color::YCbCr(YCbCr::new(90, 60, 200).with_matrix(YCbCrMatrix::Bt2020)));
+
+        for color in samples {
+            let text = color.to_string();
+            let parsed = parse(&text);
+            assert_eq!(parsed.get_format(), color.get_format(), "{}", text);
+
+            //lab() and lch() are D50, so colors relative to any other white come back adapted to it
+            let adapted = match color {
+                Color::CIELab(lab) => !is_white(lab.ref_xyz, CieXyz::D50),
+                Color::CIELch(lch) => !is_white(lch.ref_xyz, CieXyz::D50),
+                _ => false,
+            };
+            if let (Color::HWB(hwb), Color::HWB(back)) = (color, parsed) {
+                assert_eq!(back.h, hwb.h, "{}", text);
+                assert!((back.w - hwb.w).abs() < 1e-6f32 && (back.b - hwb.b).abs() < 1e-6f32, "{}", text);
+            } else if adapted {
+                let (expected, actual) = (srgbf(color), srgbf(parsed));
+                for i in 0..3 {
+                    assert!((actual[i] - expected[i]).abs() < 1e-3f32, "{}: got {:?}, expected {:?}", text, actual, expected);
+                }
+            } else {
+                assert_eq!(parsed, color, "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn every_named_color_reads_back() {
+        for (name, hex) in NAMED_COLORS.iter() {
+            let color = parse(name);
+            assert_eq!(color, Color::SRGB(Srgb::new((hex >> 16) as u8, (hex >> 8) as u8, *hex as u8)), "{}", name);
+            assert_eq!(parse(&color.to_string()), color, "{}", name);
+            assert_eq!(parse(&name.to_ascii_uppercase()), color, "{}", name);
+        }
+
+        let transparent = parse("transparent");
+        assert_eq!(parse(&transparent.to_string()), transparent);
+    }
+
+    #[test]
+    fn css_notations_mean_what_they_do_in_css() {
+        let red = [255, 0, 0];
+        assert_srgb(parse("#f00"), red, "#f00");
+        assert_srgb(parse("#FF0000"), red, "#FF0000");
+        assert_srgb(parse("rgb(255 0 0)"), red, "rgb()");
+        assert_srgb(parse("rgb(100%, 0%, 0%)"), red, "rgb() with percentages");
+        assert_srgb(parse("color(srgb 1 0 0)"), red, "color(srgb)");
+        assert_srgb(parse("color(srgb-linear 1 0 0)"), red, "color(srgb-linear)");
+        assert_srgb(parse("color(xyz-d65 0.4124 0.2126 0.0193)"), red, "color(xyz-d65)");
+        assert_srgb(parse("oklab(0.628 0.2249 0.1258)"), red, "oklab()");
+        assert_srgb(parse("oklch(62.8% 0.2577 29.23deg)"), red, "oklch()");
+
+        //HSL, HSV and HWB work on the gamma-encoded channels, so green is #008000
+        let green = [0, 128, 0];
+        assert_srgb(parse("hsl(120 100% 25%)"), green, "hsl()");
+        assert_srgb(parse("hsl(0.3333turn, 100%, 25%)"), green, "hsl() in turns");
+        assert_srgb(parse("hsv(120 100% 50%)"), green, "hsv()");
+        assert_srgb(parse("hwb(120 0% 50%)"), green, "hwb()");
+        assert_srgb(parse("hwb(0 60% 60%)"), [128, 128, 128], "hwb() past 100%");
+
+        //Plain numbers are percentages in the modern syntax
+        assert_eq!(parse("hsl(120 100 25)"), parse("hsl(120 100% 25%)"));
+        assert_eq!(parse("hsv(120 100 50)"), parse("hsv(120 100% 50%)"));
+        assert_eq!(parse("hwb(120 20 30)"), parse("hwb(120 20% 30%)"));
+        assert_srgb(parse("hwb(120 0 50)"), green, "hwb() without units");
+
+        //lab() and lch() are relative to D50- these are CSS's own values for red
+        assert_srgb(parse("lab(54.29% 80.82 69.88)"), red, "lab()");
+        assert_srgb(parse("lch(54.29 106.84 40.85)"), red, "lch()");
+
+        let half = parse("rgb(255 0 0 / 50%)");
+        assert_eq!(half, parse("rgba(255, 0, 0, 0.5)"));
+        match (parse("#ff000080"), parse("hsl(120 100% 25% / 0.25)")) {
+            (Color::RGBAf(hex), Color::RGBAf(hsl)) => assert_eq!((hex.a, hsl.a), (128.0f32 / 255.0f32, 0.25f32)),
+            other => panic!("alpha gave {:?}", other),
+        }
+
+        assert_eq!(parse("hsl-linear(120 100% 25%)"), Color::HSL(Hsl::new(120.0f32, 1.0f32, 0.25f32)));
+        assert_eq!(parse("device-cmyk(0 100% 100% 0)"), Color::CMYK(Cmyk::new(0.0f32, 1.0f32, 1.0f32, 0.0f32)));
+
+        let ycbcr = YCbCr::new(16, 128, 128).with_matrix(YCbCrMatrix::Bt709).with_range(YCbCrRange::Limited);
+        assert_eq!(parse("ycbcr(16 128 128 / bt709 limited)"), Color::YCbCr(ycbcr));
+        assert_srgb(Color::YCbCr(ycbcr), [0, 0, 0], "limited range black");
+        match parse("ycbcr(235 128 128 / bt709 limited 50%)") {
+            Color::RGBAf(rgbaf) => assert_eq!(rgbaf.a, 0.5f32),
+            other => panic!("ycbcr() with alpha gave {:?}", other),
+        }
+        assert_srgb(parse("ycbcr(235 128 128 / limited 50%)"), [255, 255, 255], "limited range white");
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        let err = error("  hsx(1 2 3)");
+        assert_eq!((err.column, err.message.as_str()), (3, "Unknown color function 'hsx'"));
+
+        let err = error("rgb(1 2)");
+        assert_eq!((err.column, err.message.as_str()), (1, "rgb() takes 3 or 4 arguments, got 2"));
+        assert_eq!(error("color(srgb 1 0)").column, 7);
+
+        let err = error("#12345");
+        assert_eq!((err.column, err.message.as_str()), (2, "Hex colors need 3, 4, 6 or 8 digits"));
+        let err = error("#12g");
+        assert_eq!((err.column, err.message.as_str()), (4, "Invalid hex digit 'g'"));
+
+        let err = error("rgb(1 2 3");
+        assert_eq!((err.column, err.message.as_str()), (10, "Expected ')'"));
+        let err = error("lab(50 0 0 / 1 2)");
+        assert_eq!((err.column, err.message.as_str()), (16, "Expected ')' after alpha"));
+
+        assert_eq!(error("hsl(10% 0 0)").column, 5);
+        assert_eq!(error("rgb(1 2 3) red").column, 12);
+        assert_eq!(error("notacolor").message, "Unknown color 'notacolor'");
+
+        let err = error("ycbcr(16 128 128 / bt709 narrow)");
+        assert_eq!((err.column, err.message.as_str()), (26, "Unknown matrix or range 'narrow'"));
+        assert_eq!(error("rgb(1 2 3 / bt709)").column, 13);
+    }
+}
@@ -0,0 +1,484 @@
+use super::color::*;
+use super::difference::*;
+use super::image::*;
+
+/*  Color quantization and dithering: cutting an image down to a small palette (for indexed images),
+ *  or down to fewer bits per channel (for low bit depth displays), without banding.
+ *  Palettes are found in a perceptual space. Dithering spreads what each pixel loses over its
+ *  neighbours (error diffusion), or trades it against a fixed pattern (ordered dithering).
+ */
+
+/// The space palette colors are clustered in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum QuantizeSpace {
+    CieLab,
+    #[default]
+    Oklab,
+}
+
+/// How a palette is found
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum QuantizeMethod {
+    /// Splits the colors at the median of their widest channel until there are enough groups. Fast.
+    MedianCut,
+    /// Refines median cut's palette with k-means, moving each color to the middle of the pixels nearest it
+    #[default]
+    KMeans,
+}
+
+/// How to hide the steps between the colors that can be shown
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Dither {
+    /// Each pixel takes the nearest color. Gradients band.
+    #[default]
+    None,
+    /// Error diffusion to the four neighbours ahead. Keeps the average color exactly.
+    FloydSteinberg,
+    /// Error diffusion that only passes on 3/4 of the error, for more contrast and less noise
+    Atkinson,
+    /// Ordered dithering with a 4x4 Bayer matrix. Doesn't crawl when neighbouring pixels change.
+    Bayer4,
+    /// Ordered dithering with an 8x8 Bayer matrix, for smoother gradients
+    Bayer8,
+}
+
+//Error diffusion kernels, as (dx, dy, share of the error)
+const FLOYD_STEINBERG: [(i32, usize, f32); 4] = [
+    (1, 0, 7.0f32 / 16.0f32), (-1, 1, 3.0f32 / 16.0f32), (0, 1, 5.0f32 / 16.0f32), (1, 1, 1.0f32 / 16.0f32),
+];
+const ATKINSON: [(i32, usize, f32); 6] = [
+    (1, 0, 0.125f32), (2, 0, 0.125f32), (-1, 1, 0.125f32), (0, 1, 0.125f32), (1, 1, 0.125f32), (0, 2, 0.125f32),
+];
+
+//Palettes are found from at most this many pixels, evenly spread over the image, to keep k-means quick
+const MAX_SAMPLES: usize = 1 << 16;
+const KMEANS_ITERATIONS: usize = 16;
+
+//The threshold in (0, 1) at (x, y) of a 2^bits square Bayer matrix
+fn bayer(x: usize, y: usize, bits: u32) -> f32 {
+    let mut index = 0;
+    for bit in (0..bits).rev() {
+        index = (index << 2) | ((((x ^ y) >> bit) & 1) << 1) | ((y >> bit) & 1);
+    }
+
+    (index as f32 + 0.5f32) / (1usize << (2 * bits)) as f32
+}
+
+/*  Runs every pixel through pick, which is given the pixel (with any diffused error added) and the Bayer
+ *  threshold when dithering is ordered. It returns what it chose and what that looks like, in the same
+ *  space as the pixels- the difference is what gets diffused.
+ */
+fn dither_pixels<T, F: FnMut([f32; 4], Option<f32>) -> (T, [f32; 4])>(width: usize, mut values: Vec<[f32; 4]>,
+                                                                        dither: Dither, mut pick: F) -> Vec<T> {
+    let height = values.len().checked_div(width).unwrap_or(0);
+    let kernel: &[(i32, usize, f32)] = match dither {
+        Dither::FloydSteinberg => &FLOYD_STEINBERG,
+        Dither::Atkinson => &ATKINSON,
+        _ => &[],
+    };
+
+    let mut chosen = Vec::with_capacity(values.len());
+    for y in 0..height {
+        for x in 0..width {
+            let threshold = match dither {
+                Dither::Bayer4 => Some(bayer(x, y, 2)),
+                Dither::Bayer8 => Some(bayer(x, y, 3)),
+                _ => None,
+            };
+
+            let value = values[y * width + x];
+            let (choice, shown) = pick(value, threshold);
+            chosen.push(choice);
+
+            //Error from past the ends of the range can't be shown anywhere, so it isn't passed on
+            for &(dx, dy, share) in kernel {
+                let (nx, ny) = (x as i32 + dx, y + dy);
+                if nx >= 0 && (nx as usize) < width && ny < height {
+                    let neighbour = &mut values[ny * width + nx as usize];
+                    for c in 0..4 {
+                        neighbour[c] += (value[c].clamp(0.0f32, 1.0f32) - shown[c]) * share;
+                    }
+                }
+            }
+        }
+    }
+
+    chosen
+}
+
+//The step between adjacent levels of formats with whole number RGB or gray channels,
+//and whether the levels are evenly spaced in sRGB rather than linear light
+fn channel_levels(format: ColorFormatName) -> Option<(f32, bool)> {
+    match format {
+        ColorFormatName::Gray8 | ColorFormatName::RGB | ColorFormatName::RGBA => Some((1.0f32 / u8::MAX as f32, false)),
+        ColorFormatName::SRGB => Some((1.0f32 / u8::MAX as f32, true)),
+        ColorFormatName::Gray16 | ColorFormatName::RGB48 | ColorFormatName::RGBA64 => Some((1.0f32 / u16::MAX as f32, false)),
+        _ => None,
+    }
+}
+
+fn srgb_encode(rgba: [f32; 4]) -> [f32; 4] {
+    let srgbf: SrgbF = RgbF::new(rgba[0], rgba[1], rgba[2]).into();
+    [srgbf.r, srgbf.g, srgbf.b, rgba[3]]
+}
+
+fn srgb_decode(rgba: [f32; 4]) -> [f32; 4] {
+    let rgbf: RgbF = SrgbF::new(rgba[0], rgba[1], rgba[2]).into();
+    [rgbf.r, rgbf.g, rgbf.b, rgba[3]]
+}
+
+//ΔE is measured in CIELab for the CIE methods and Oklab for Oklab
+fn difference_space(method: DeltaEMethod) -> ColorFormatName {
+    match method {
+        DeltaEMethod::Oklab => ColorFormatName::Oklab,
+        _ => ColorFormatName::CIELab,
+    }
+}
+
+//Both color and palette already in difference_space, so delta_e doesn't convert
+fn nearest(color: Color, palette: &[Color], method: DeltaEMethod) -> usize {
+    let mut nearest = 0;
+    let mut nearest_difference = f32::MAX;
+    for (i, entry) in palette.iter().enumerate() {
+        let difference = Color::delta_e(*entry, color, method);
+        if difference < nearest_difference {
+            nearest = i;
+            nearest_difference = difference;
+        }
+    }
+
+    nearest
+}
+
+fn linear_color(rgba: [f32; 4]) -> Color {
+    Color::RGBf(RgbF::new(rgba[0], rgba[1], rgba[2]))
+}
+
+fn to_point(rgba: [f32; 4], space: QuantizeSpace) -> [f32; 3] {
+    match (space, Color::convert(linear_color(rgba), quantize_format(space))) {
+        (QuantizeSpace::CieLab, Color::CIELab(lab)) => [lab.l, lab.a, lab.b],
+        (QuantizeSpace::Oklab, Color::Oklab(oklab)) => [oklab.l, oklab.a, oklab.b],
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn from_point(point: [f32; 3], space: QuantizeSpace) -> [f32; 4] {
+    let color = match space {
+        QuantizeSpace::CieLab => Color::CIELab(CieLab::new(point[0], point[1], point[2])),
+        QuantizeSpace::Oklab => Color::Oklab(Oklab::new(point[0], point[1], point[2])),
+    };
+
+    match Color::convert(color, ColorFormatName::RGBf) {
+        Color::RGBf(rgbf) => [rgbf.r, rgbf.g, rgbf.b, 1.0f32],
+        _ => unreachable!("Color::convert always returns the requested format"),
+    }
+}
+
+fn quantize_format(space: QuantizeSpace) -> ColorFormatName {
+    match space {
+        QuantizeSpace::CieLab => ColorFormatName::CIELab,
+        QuantizeSpace::Oklab => ColorFormatName::Oklab,
+    }
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn mean(points: &[[f32; 3]]) -> [f32; 3] {
+    let mut sum = [0.0f64; 3];
+    for point in points {
+        for c in 0..3 {
+            sum[c] += point[c] as f64;
+        }
+    }
+
+    let count = points.len() as f64;
+    [(sum[0] / count) as f32, (sum[1] / count) as f32, (sum[2] / count) as f32]
+}
+
+//The channel with the widest range in a box, and how wide it is
+fn widest_channel(points: &[[f32; 3]]) -> (usize, f32) {
+    let mut widest = (0, 0.0f32);
+    for c in 0..3 {
+        let min = points.iter().map(|p| p[c]).fold(f32::MAX, f32::min);
+        let max = points.iter().map(|p| p[c]).fold(f32::MIN, f32::max);
+        if max - min > widest.1 {
+            widest = (c, max - min);
+        }
+    }
+
+    widest
+}
+
+fn median_cut(points: Vec<[f32; 3]>, size: usize) -> Vec<[f32; 3]> {
+    if points.is_empty() || size == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![points];
+    while boxes.len() < size {
+        //Split the box that spans the most, until every box is a single color
+        let widest = boxes.iter().enumerate()
+            .map(|(i, points)| (i, widest_channel(points)))
+            .filter(|(_, (_, range))| *range > 0.0f32)
+            .max_by(|a, b| a.1.1.total_cmp(&b.1.1));
+
+        let (i, (channel, _)) = match widest {
+            Some(widest) => widest,
+            None => break,
+        };
+
+        let mut split = boxes.swap_remove(i);
+        split.sort_by(|a, b| a[channel].total_cmp(&b[channel]));
+        let upper = split.split_off(split.len() / 2);
+        boxes.push(split);
+        boxes.push(upper);
+    }
+
+    boxes.iter().map(|points| mean(points)).collect()
+}
+
+fn k_means(points: &[[f32; 3]], mut centroids: Vec<[f32; 3]>) -> Vec<[f32; 3]> {
+    let mut assignments = vec![usize::MAX; points.len()];
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (point, assignment) in points.iter().zip(assignments.iter_mut()) {
+            let mut closest = 0;
+            for (i, centroid) in centroids.iter().enumerate() {
+                if distance_squared(*point, *centroid) < distance_squared(*point, centroids[closest]) {
+                    closest = i;
+                }
+            }
+
+            if *assignment != closest {
+                *assignment = closest;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+
+        //A centroid nothing is nearest to stays where it is
+        for (i, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<[f32; 3]> = points.iter().zip(assignments.iter())
+                .filter(|(_, assignment)| **assignment == i)
+                .map(|(point, _)| *point)
+                .collect();
+            if !members.is_empty() {
+                *centroid = mean(&members);
+            }
+        }
+    }
+
+    centroids
+}
+
+#[allow(dead_code)]
+impl Color {
+    /// The index of the palette color with the smallest ΔE to color. None if the palette is empty.
+    pub fn nearest_in_palette(color: Color, palette: &[Color], method: DeltaEMethod) -> Option<usize> {
+        if palette.is_empty() {
+            return None;
+        }
+
+        let space = difference_space(method);
+        let palette: Vec<Color> = palette.iter().map(|entry| Color::convert(*entry, space)).collect();
+        Some(nearest(Color::convert(color, space), &palette, method))
+    }
+}
+
+#[allow(dead_code)]
+impl<C: BulkConvert> ImageBuffer<C> {
+    /// Up to size colors that best stand for the image, clustered in space. Fewer if the image has fewer colors.
+    /// Large images are sampled. Alpha is ignored, so the colors are opaque.
+    pub fn extract_palette(&self, size: usize, method: QuantizeMethod, space: QuantizeSpace) -> Vec<C> {
+        let pixel_count = self.width() as usize * self.height() as usize;
+        let every = pixel_count.div_ceil(MAX_SAMPLES).max(1);
+
+        let points: Vec<[f32; 3]> = self.rows()
+            .flat_map(|row| row.iter())
+            .step_by(every)
+            .map(|pixel| to_point(pixel.to_linear_rgba(), space))
+            .collect();
+
+        let mut centroids = median_cut(points.clone(), size);
+        if method == QuantizeMethod::KMeans {
+            centroids = k_means(&points, centroids);
+        }
+
+        centroids.iter().map(|point| C::from_linear_rgba(from_point(*point, space))).collect()
+    }
+
+    /// The index into palette of the color for each pixel, row by row, for an indexed image.
+    /// Colors are matched by ΔE, and alpha is ignored. The palette can have at most 256 colors.
+    pub fn map_to_palette(&self, palette: &[C], method: DeltaEMethod, dither: Dither) -> Result<Vec<u8>, String> {
+        if palette.is_empty() || palette.len() > 256 {
+            return Err(format!("A palette needs between 1 and 256 colors, {} were provided", palette.len()));
+        }
+
+        let space = difference_space(method);
+        let shown: Vec<[f32; 4]> = palette.iter().map(|entry| entry.to_linear_rgba()).collect();
+        let targets: Vec<Color> = shown.iter().map(|rgba| Color::convert(linear_color(*rgba), space)).collect();
+
+        //Ordered dithering spreads each channel by about the distance between palette colors-
+        //what it would be if they were evenly spread over the RGB cube
+        let spread = 1.0f32 / (palette.len() as f32).cbrt();
+
+        let values: Vec<[f32; 4]> = self.rows().flat_map(|row| row.iter().map(|pixel| pixel.to_linear_rgba())).collect();
+        Ok(dither_pixels(self.width() as usize, values, dither, |value, threshold| {
+            let mut value = value;
+            if let Some(threshold) = threshold {
+                for channel in value.iter_mut().take(3) {
+                    *channel += (threshold - 0.5f32) * spread;
+                }
+            }
+
+            let index = nearest(Color::convert(linear_color(value), space), &targets, method);
+            (index as u8, shown[index])
+        }))
+    }
+
+    /// The image redrawn in only the palette's colors. See `map_to_palette`.
+    pub fn quantize(&self, palette: &[C], method: DeltaEMethod, dither: Dither) -> Result<ImageBuffer<C>, String> {
+        let indices = self.map_to_palette(palette, method, dither)?;
+        let pixels = indices.iter().map(|index| palette[*index as usize]).collect();
+        ImageBuffer::from_pixels(self.width(), self.height(), self.width() as usize, pixels)
+    }
+
+    /// Like `convert`, but dithered, so going down to fewer bits (RgbF to Rgb, Gray16 to Gray8) doesn't band.
+    /// Diffusion works with any target. Ordered dithering only applies to whole number RGB and gray formats.
+    pub fn convert_dithered<D: BulkConvert>(&self, dither: Dither) -> ImageBuffer<D> {
+        let levels = channel_levels(D::format_name());
+        let gamma = levels.is_some_and(|(_, gamma)| gamma);
+
+        //Dither where the target's levels are evenly spaced
+        let values: Vec<[f32; 4]> = self.rows().flat_map(|row| row.iter().map(|pixel| {
+            let rgba = pixel.to_linear_rgba();
+            if gamma { srgb_encode(rgba) } else { rgba }
+        })).collect();
+
+        let pixels = dither_pixels(self.width() as usize, values, dither, |value, threshold| {
+            let mut value = value;
+            if let (Some(threshold), Some((step, _))) = (threshold, levels) {
+                //Pick the level below or above by the threshold, then aim a quarter step into it,
+                //so formats that truncate and formats that round both land on it
+                for channel in value.iter_mut() {
+                    let scaled = channel.clamp(0.0f32, 1.0f32) / step;
+                    let level = if scaled - scaled.floor() > threshold { scaled.floor() + 1.0f32 } else { scaled.floor() };
+                    *channel = ((level + 0.25f32) * step).min(1.0f32);
+                }
+            }
+
+            let pixel = D::from_linear_rgba(if gamma { srgb_decode(value) } else { value });
+            let shown = pixel.to_linear_rgba();
+            (pixel, if gamma { srgb_encode(shown) } else { shown })
+        });
+
+        ImageBuffer::from_pixels(self.width(), self.height(), self.width() as usize, pixels).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks() -> ImageBuffer<RgbF> {
+        let colors = [RgbF::new(0.8f32, 0.1f32, 0.1f32), RgbF::new(0.1f32, 0.6f32, 0.2f32),
+                      RgbF::new(0.1f32, 0.2f32, 0.9f32), RgbF::new(0.9f32, 0.9f32, 0.8f32)];
+        let pixels = (0..64).map(|i| colors[(i % 8) / 4 + 2 * (i / 32)]).collect();
+        ImageBuffer::from_pixels(8, 8, 8, pixels).unwrap()
+    }
+
+    fn mean_luminance<C: BulkConvert>(image: &ImageBuffer<C>) -> f32 {
+        let sum: f32 = image.pixels().iter().map(|pixel| pixel.to_linear_rgba()[0]).sum();
+        sum / image.pixels().len() as f32
+    }
+
+    #[test]
+    fn bayer_thresholds_are_evenly_spread() {
+        for bits in [2, 3] {
+            let size = 1usize << bits;
+            let mut thresholds: Vec<f32> = (0..size * size).map(|i| bayer(i % size, i / size, bits)).collect();
+            thresholds.sort_by(|a, b| a.total_cmp(b));
+            for (i, threshold) in thresholds.iter().enumerate() {
+                assert_eq!(*threshold, (i as f32 + 0.5f32) / (size * size) as f32);
+            }
+        }
+
+        //The 2x2 matrix is the classic [[0, 2], [3, 1]]
+        assert_eq!([bayer(0, 0, 1), bayer(1, 0, 1), bayer(0, 1, 1), bayer(1, 1, 1)], [0.125f32, 0.625f32, 0.875f32, 0.375f32]);
+    }
+
+    #[test]
+    fn palettes_find_the_colors_of_an_image() {
+        let image = blocks();
+
+        for method in [QuantizeMethod::MedianCut, QuantizeMethod::KMeans] {
+            for space in [QuantizeSpace::CieLab, QuantizeSpace::Oklab] {
+                let palette = image.extract_palette(4, method, space);
+                assert_eq!(palette.len(), 4);
+                for pixel in image.pixels() {
+                    assert!(palette.iter().any(|entry| (entry.r - pixel.r).abs() < 1e-3f32 &&
+                                                       (entry.g - pixel.g).abs() < 1e-3f32 &&
+                                                       (entry.b - pixel.b).abs() < 1e-3f32),
+                            "{:?} {:?} missed {:?}: {:?}", method, space, pixel, palette);
+                }
+
+                //An image can't give more colors than it has
+                assert_eq!(image.extract_palette(16, method, space).len(), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn mapping_to_a_palette_picks_the_nearest_color() {
+        let image = blocks();
+        let palette = [RgbF::new(1.0f32, 1.0f32, 1.0f32), RgbF::new(1.0f32, 0.0f32, 0.0f32),
+                       RgbF::new(0.0f32, 1.0f32, 0.0f32), RgbF::new(0.0f32, 0.0f32, 1.0f32)];
+
+        let indices = image.map_to_palette(&palette, DeltaEMethod::Ciede2000, Dither::None).unwrap();
+        assert_eq!(&indices[0..8], &[1, 1, 1, 1, 2, 2, 2, 2]);
+        assert_eq!(&indices[56..64], &[3, 3, 3, 3, 0, 0, 0, 0]);
+
+        let quantized = image.quantize(&palette, DeltaEMethod::Oklab, Dither::FloydSteinberg).unwrap();
+        assert!(quantized.pixels().iter().all(|pixel| palette.contains(pixel)));
+
+        assert_eq!(Color::nearest_in_palette(Color::SRGB(Srgb::new(250, 10, 10)),
+                                             &palette.map(Color::RGBf), DeltaEMethod::Cie76), Some(1));
+        assert_eq!(Color::nearest_in_palette(Color::SRGB(Srgb::new(250, 10, 10)), &[], DeltaEMethod::Cie76), None);
+
+        assert!(image.map_to_palette(&[], DeltaEMethod::Oklab, Dither::None).is_err());
+        assert!(image.map_to_palette(&[palette[0]; 257], DeltaEMethod::Oklab, Dither::None).is_err());
+    }
+
+    #[test]
+    fn dithering_keeps_the_average_between_levels() {
+        //A flat gray 30% of the way from one 8-bit level to the next
+        let level = (100.3f32 / 255.0f32 * 65535.0f32) as u16;
+        let gray = ImageBuffer::filled(32, 32, Gray16::new(level));
+        let expected = level as f32 / 65535.0f32;
+
+        let banded: ImageBuffer<Gray8> = gray.convert_dithered(Dither::None);
+        assert!((mean_luminance(&banded) - expected).abs() > 0.25f32 / 255.0f32);
+
+        for dither in [Dither::FloydSteinberg, Dither::Bayer4, Dither::Bayer8] {
+            let dithered: ImageBuffer<Gray8> = gray.convert_dithered(dither);
+            assert!((mean_luminance(&dithered) - expected).abs() < 0.05f32 / 255.0f32, "{:?}", dither);
+            assert!(dithered.pixels().iter().all(|pixel| pixel.luminance == 100 || pixel.luminance == 101), "{:?}", dither);
+        }
+
+        //The same for float RGB down to 8 bits, linear and sRGB
+        let rgbf = ImageBuffer::filled(32, 32, RgbF::new(expected, expected, expected));
+        for dither in [Dither::FloydSteinberg, Dither::Bayer8] {
+            let rgb: ImageBuffer<Rgb> = rgbf.convert_dithered(dither);
+            assert!((mean_luminance(&rgb) - expected).abs() < 0.05f32 / 255.0f32, "{:?}", dither);
+
+            let srgb: ImageBuffer<Srgb> = rgbf.convert_dithered(dither);
+            assert!((mean_luminance(&srgb) - expected).abs() < 0.5f32 / 255.0f32, "{:?}", dither);
+        }
+    }
+}
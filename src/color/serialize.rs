@@ -0,0 +1,151 @@
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::color::*;
+
+/*  serde support for Color. Human-readable formats (JSON, TOML, RON...) get the format's name and
+ *  its fields, like {"format": "HSL", "h": 210.0, "s": 0.5, "l": 0.25}, and also accept any string
+ *  parse_color reads, so hand-written themes can say "#2563eb". Binary formats get the tagged byte
+ *  encoding (Color::to_tagged_bytes), whose format tags never change between versions.
+ *  The format structs themselves derive Serialize/Deserialize where they are declared.
+ */
+
+//Mirrors Color for the derive- the variant names are the ColorFormatName names
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color", tag = "format")]
+enum ColorFields {
+    Gray8(Gray8),
+    Gray16(Gray16),
+    Grayf(GrayF),
+    RGB(Rgb),
+    SRGB(Srgb),
+    RGB48(Rgb48),
+    RGBA(Rgba),
+    RGBA64(Rgba64),
+    RGBf(RgbF),
+    SRGBf(SrgbF),
+    RGBAf(RgbaF),
+    HSV(Hsv),
+    HSL(Hsl),
+    CIEXYZ(CieXyz),
+    CIELab(CieLab),
+    CIELch(CieLch),
+    CIELuv(CieLuv),
+    CIELchUv(CieLchUv),
+    CIExyY(CieXyY),
+    Oklab(Oklab),
+    Oklch(Oklch),
+    CMYK(Cmyk),
+    YCbCr(YCbCr),
+    HWB(Hwb),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Readable {
+    Css(String),
+    Fields(#[serde(with = "ColorFields")] Color),
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            ColorFields::serialize(self, serializer)
+        } else {
+            serializer.serialize_bytes(&self.to_tagged_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        if deserializer.is_human_readable() {
+            match Readable::deserialize(deserializer)? {
+                Readable::Css(css) => css.parse::<Color>().map_err(de::Error::custom),
+                Readable::Fields(color) => Ok(color),
+            }
+        } else {
+            deserializer.deserialize_bytes(TaggedBytes)
+        }
+    }
+}
+
+struct TaggedBytes;
+
+impl<'de> Visitor<'de> for TaggedBytes {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a tagged color")
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Color, E> {
+        Color::from_tagged_bytes(bytes).map_err(E::custom)
+    }
+
+    //Some binary formats hand byte strings over as a sequence of u8
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Color, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::conversion::FORMATS;
+
+    fn samples() -> Vec<Color> {
+        let mut samples: Vec<Color> = FORMATS.iter()
+            .map(|format| Color::convert(Color::SRGB(Srgb::new(0x25, 0x63, 0xeb)), *format))
+            .collect();
+
+        //Relative to a white other than D65, and encoded other than BT.601 full range
+        samples.push(Color::CIELab(CieLab::new(52.0f32, -20.0f32, 31.5f32).with_reference_white(CieXyz::new(0.9642f32, 1.0f32, 0.8251f32))));
+        samples.push(Color::YCbCr(YCbCr::new(16, 128, 128).with_matrix(YCbCrMatrix::Bt709).with_range(YCbCrRange::Limited)));
+        samples
+    }
+
+    #[test]
+    fn json_names_the_format_and_its_fields() {
+        let hsl = Color::HSL(Hsl::new(210.0f32, 0.5f32, 0.25f32));
+        let json = serde_json::to_string(&hsl).unwrap();
+        assert_eq!(json, r#"{"format":"HSL","h":210.0,"s":0.5,"l":0.25}"#);
+        assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), hsl);
+
+        for color in samples() {
+            let json = serde_json::to_string(&color).unwrap();
+            assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color, "{}", json);
+        }
+    }
+
+    #[test]
+    fn json_accepts_css_strings() {
+        assert_eq!(serde_json::from_str::<Color>(r##""#2563eb""##).unwrap(), Color::SRGB(Srgb::new(0x25, 0x63, 0xeb)));
+        assert_eq!(serde_json::from_str::<Color>(r#""hsl(210 50% 25%)""#).unwrap(), "hsl(210 50% 25%)".parse::<Color>().unwrap());
+
+        assert!(serde_json::from_str::<Color>(r#""hsl(210 50%""#).is_err());
+        assert!(serde_json::from_str::<Color>(r#"{"format":"HSL","h":210.0}"#).is_err());
+        assert!(serde_json::from_str::<Color>(r#"{"format":"Pantone","h":210.0}"#).is_err());
+    }
+
+    #[test]
+    fn binary_formats_use_the_tagged_encoding() {
+        for color in samples() {
+            let bytes = bincode::serialize(&color).unwrap();
+
+            //bincode writes a u64 length ahead of the byte string
+            assert_eq!(bytes[8..], color.to_tagged_bytes()[..]);
+            assert_eq!(bincode::deserialize::<Color>(&bytes).unwrap(), color);
+        }
+
+        assert!(bincode::deserialize::<Color>(&[2, 0, 0, 0, 0, 0, 0, 0, 99, 0]).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use super::*;
+use super::conversion::FORMATS;
+
+/*  Tests for the color formats as a whole: published reference values, round trips between every
+ *  pair of formats through Color::convert, and byte round trips. Tests of a single feature sit next to it.
+ *  Reference values are from Bruce Lindbloom's calculator (sRGB, D65) and Björn Ottosson's Oklab post.
+ */
+
+macro_rules! convert_to {
+    ($color:expr, $format:ident) => {
+        match Color::convert($color, ColorFormatName::$format) {
+            Color::$format(converted) => converted,
+            _ => unreachable!("Color::convert always returns the requested format"),
+        }
+    };
+}
+
+//xorshift32- no dependencies, and the same colors every run so failures reproduce
+struct Rng(u32);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    //Kept away from 0 and 1, where hue and the gamma curves are at their least stable
+    fn channel(&mut self) -> f32 {
+        0.02f32 + 0.96f32 * self.next_f32()
+    }
+}
+
+fn assert_close(actual: [f32; 3], expected: [f32; 3], tolerance: f32, what: &str) {
+    for i in 0..3 {
+        assert!((actual[i] - expected[i]).abs() <= tolerance,
+                "{}: got {:?}, expected {:?} (within {})", what, actual, expected, tolerance);
+    }
+}
+
+fn srgb(r: u8, g: u8, b: u8) -> Color {
+    Color::SRGB(Srgb::new(r, g, b))
+}
+
+fn d65_xyz(x: f32, y: f32, z: f32) -> Color {
+    Color::CIEXYZ(CieXyz::new(x, y, z).with_reference_white(CieXyz::D65))
+}
+
+fn is_gray(format: ColorFormatName) -> bool {
+    matches!(format, ColorFormatName::Gray8 | ColorFormatName::Gray16 | ColorFormatName::Grayf)
+}
+
+fn has_alpha(format: ColorFormatName) -> bool {
+    matches!(format, ColorFormatName::RGBA | ColorFormatName::RGBA64 | ColorFormatName::RGBAf)
+}
+
+//How far a round trip through a pair of formats may drift, in linear RGBA. The 8-bit conversions
+//truncate rather than round, and the 8-bit linear Rgb is coarse in the darks, so they get a few steps.
+//YCbCr rounds three mixed channels in gamma space, and the steps are wider once linear near white.
+fn round_trip_tolerance(a: ColorFormatName, b: ColorFormatName) -> f32 {
+    let tolerance = |format: ColorFormatName| match format {
+        ColorFormatName::Gray8 | ColorFormatName::RGB | ColorFormatName::SRGB | ColorFormatName::RGBA => 3.0f32 / 255.0f32,
+        ColorFormatName::YCbCr => 4.0f32 / 255.0f32,
+        ColorFormatName::Gray16 | ColorFormatName::RGB48 | ColorFormatName::RGBA64 => 1e-4f32,
+        _ => 2e-5f32,
+    };
+
+    tolerance(a).max(tolerance(b))
+}
+
+//A random color that both formats can hold: gray if either is, opaque unless both have alpha
+fn random_color(rng: &mut Rng, a: ColorFormatName, b: ColorFormatName) -> RgbaF {
+    let (r, mut g, mut b_) = (rng.channel(), rng.channel(), rng.channel());
+    if is_gray(a) || is_gray(b) {
+        g = r;
+        b_ = r;
+    }
+    let alpha = if has_alpha(a) && has_alpha(b) { rng.next_f32() } else { 1.0f32 };
+
+    let linear = convert_to!(Color::SRGBf(SrgbF::new(r, g, b_)), RGBAf);
+    RgbaF::new(linear.r, linear.g, linear.b, alpha)
+}
+
+#[test]
+fn srgb_to_xyz_reference_values() {
+    let table = [
+        ((255, 0, 0), [0.4124f32, 0.2126f32, 0.0193f32]),
+        ((0, 255, 0), [0.3576f32, 0.7152f32, 0.1192f32]),
+        ((0, 0, 255), [0.1805f32, 0.0722f32, 0.9505f32]),
+        ((255, 255, 255), [0.9505f32, 1.0000f32, 1.0890f32]),
+        ((128, 128, 128), [0.2052f32, 0.2159f32, 0.2350f32]),
+        ((0, 0, 0), [0.0f32, 0.0f32, 0.0f32]),
+    ];
+
+    for ((r, g, b), expected) in table {
+        let xyz = convert_to!(srgb(r, g, b), CIEXYZ);
+        assert_close([xyz.x, xyz.y, xyz.z], expected, 0.001f32, &format!("sRGB ({}, {}, {}) -> XYZ", r, g, b));
+    }
+}
+
+#[test]
+fn xyz_to_srgb_reference_values() {
+    let table = [
+        ([0.4124f32, 0.2126f32, 0.0193f32], [1.0f32, 0.0f32, 0.0f32]),
+        ([0.3576f32, 0.7152f32, 0.1192f32], [0.0f32, 1.0f32, 0.0f32]),
+        ([0.1805f32, 0.0722f32, 0.9505f32], [0.0f32, 0.0f32, 1.0f32]),
+        ([0.9505f32, 1.0000f32, 1.0890f32], [1.0f32, 1.0f32, 1.0f32]),
+    ];
+
+    for (xyz, expected) in table {
+        let srgbf = convert_to!(d65_xyz(xyz[0], xyz[1], xyz[2]), SRGBf);
+        assert_close([srgbf.r, srgbf.g, srgbf.b], expected, 0.002f32, &format!("XYZ {:?} -> sRGB", xyz));
+    }
+}
+
+#[test]
+fn srgb_to_lab_reference_values() {
+    let table = [
+        ((255, 0, 0), [53.24f32, 80.09f32, 67.20f32]),
+        ((0, 255, 0), [87.73f32, -86.18f32, 83.18f32]),
+        ((0, 0, 255), [32.30f32, 79.19f32, -107.86f32]),
+        ((255, 255, 0), [97.14f32, -21.55f32, 94.48f32]),
+        ((0, 255, 255), [91.11f32, -48.09f32, -14.13f32]),
+        ((255, 0, 255), [60.32f32, 98.23f32, -60.82f32]),
+        ((128, 128, 128), [53.59f32, 0.0f32, 0.0f32]),
+        ((255, 255, 255), [100.0f32, 0.0f32, 0.0f32]),
+        ((0, 0, 0), [0.0f32, 0.0f32, 0.0f32]),
+    ];
+
+    for ((r, g, b), expected) in table {
+        let lab = convert_to!(srgb(r, g, b), CIELab);
+        assert_close([lab.l, lab.a, lab.b], expected, 0.1f32, &format!("sRGB ({}, {}, {}) -> Lab", r, g, b));
+
+        //And back, to the same 8-bit value
+        let back = convert_to!(Color::CIELab(lab), SRGBf);
+        let expected = [r as f32 / 255.0f32, g as f32 / 255.0f32, b as f32 / 255.0f32];
+        assert_close([back.r, back.g, back.b], expected, 0.5f32 / 255.0f32, &format!("Lab -> sRGB ({}, {}, {})", r, g, b));
+    }
+}
+
+#[test]
+fn srgb_to_lch_reference_values() {
+    let lch = convert_to!(srgb(255, 0, 0), CIELch);
+    assert_close([lch.l, lch.c, lch.h], [53.24f32, 104.55f32, 40.00f32], 0.1f32, "sRGB red -> LCh");
+
+    let lch = convert_to!(srgb(0, 0, 255), CIELch);
+    assert_close([lch.l, lch.c, lch.h], [32.30f32, 133.81f32, 306.29f32], 0.1f32, "sRGB blue -> LCh");
+}
+
+#[test]
+fn srgb_to_luv_reference_values() {
+    let table = [
+        ((255, 0, 0), [53.24f32, 175.01f32, 37.76f32]),
+        ((0, 255, 0), [87.73f32, -83.07f32, 107.40f32]),
+        ((0, 0, 255), [32.30f32, -9.40f32, -130.35f32]),
+        ((255, 255, 255), [100.0f32, 0.0f32, 0.0f32]),
+    ];
+
+    for ((r, g, b), expected) in table {
+        let luv = convert_to!(srgb(r, g, b), CIELuv);
+        assert_close([luv.l, luv.u, luv.v], expected, 0.1f32, &format!("sRGB ({}, {}, {}) -> Luv", r, g, b));
+    }
+}
+
+#[test]
+fn d65_white_point_in_xyy() {
+    let xyy = convert_to!(srgb(255, 255, 255), CIExyY);
+    assert_close([xyy.x, xyy.y, xyy.luminance], [0.3127f32, 0.3290f32, 1.0f32], 0.0005f32, "sRGB white -> xyY");
+}
+
+#[test]
+fn xyz_to_oklab_reference_values() {
+    let table = [
+        ([0.950f32, 1.000f32, 1.089f32], [1.000f32, 0.000f32, 0.000f32]),
+        ([1.000f32, 0.000f32, 0.000f32], [0.450f32, 1.236f32, -0.019f32]),
+        ([0.000f32, 1.000f32, 0.000f32], [0.922f32, -0.671f32, 0.263f32]),
+        ([0.000f32, 0.000f32, 1.000f32], [0.153f32, -1.415f32, -0.449f32]),
+    ];
+
+    for (xyz, expected) in table {
+        let oklab = convert_to!(d65_xyz(xyz[0], xyz[1], xyz[2]), Oklab);
+        assert_close([oklab.l, oklab.a, oklab.b], expected, 0.002f32, &format!("XYZ {:?} -> Oklab", xyz));
+    }
+
+    let oklab = convert_to!(srgb(255, 0, 0), Oklab);
+    assert_close([oklab.l, oklab.a, oklab.b], [0.6280f32, 0.2249f32, 0.1258f32], 0.001f32, "sRGB red -> Oklab");
+}
+
+#[test]
+fn hsv_and_hsl_of_primaries() {
+    let table = [
+        (Rgb::new(255, 0, 0), [0.0f32, 1.0f32, 1.0f32], [0.0f32, 1.0f32, 0.5f32]),
+        (Rgb::new(0, 255, 0), [120.0f32, 1.0f32, 1.0f32], [120.0f32, 1.0f32, 0.5f32]),
+        (Rgb::new(0, 0, 255), [240.0f32, 1.0f32, 1.0f32], [240.0f32, 1.0f32, 0.5f32]),
+        (Rgb::new(255, 0, 255), [300.0f32, 1.0f32, 1.0f32], [300.0f32, 1.0f32, 0.5f32]),
+        (Rgb::new(255, 255, 255), [0.0f32, 0.0f32, 1.0f32], [0.0f32, 0.0f32, 1.0f32]),
+    ];
+
+    for (rgb, expected_hsv, expected_hsl) in table {
+        let hsv = convert_to!(Color::RGB(rgb), HSV);
+        assert_close([hsv.h, hsv.s, hsv.v], expected_hsv, 0.01f32, &format!("{:?} -> HSV", rgb));
+
+        let hsl = convert_to!(Color::RGB(rgb), HSL);
+        assert_close([hsl.h, hsl.s, hsl.l], expected_hsl, 0.01f32, &format!("{:?} -> HSL", rgb));
+    }
+
+    //Hues stay in [0, 360), and ones outside of it wrap around
+    let hsv = convert_to!(Color::RGBf(RgbF::new(1.0f32, 0.0f32, 0.5f32)), HSV);
+    assert_close([hsv.h, hsv.s, hsv.v], [330.0f32, 1.0f32, 1.0f32], 0.01f32, "rose -> HSV");
+
+    let rgbf = convert_to!(Color::HSV(Hsv::new(-30.0f32, 1.0f32, 1.0f32)), RGBf);
+    assert_close([rgbf.r, rgbf.g, rgbf.b], [1.0f32, 0.0f32, 0.5f32], 0.001f32, "HSV at -30 degrees -> RGB");
+}
+
+#[test]
+fn device_format_reference_values() {
+    let cmyk = convert_to!(srgb(255, 128, 0), CMYK);
+    assert_close([cmyk.c, cmyk.m, cmyk.y], [0.0f32, 0.498f32, 1.0f32], 0.001f32, "orange -> CMYK");
+    assert_eq!(cmyk.k, 0.0f32);
+    assert_eq!(convert_to!(srgb(0, 0, 0), CMYK), Cmyk::new(0.0f32, 0.0f32, 0.0f32, 1.0f32));
+    assert_eq!(Cmyk::new(1.0f32, 1.0f32, 1.0f32, 1.0f32).with_ink_limit(3.0f32).total_ink(), 3.0f32);
+
+    //JPEG's encoding of pure red
+    let ycbcr = convert_to!(srgb(255, 0, 0), YCbCr);
+    assert_eq!((ycbcr.y, ycbcr.cb, ycbcr.cr), (76, 85, 255));
+
+    let white = YCbCr::from_srgbf(SrgbF::new(1.0f32, 1.0f32, 1.0f32), YCbCrMatrix::Bt709, YCbCrRange::Limited);
+    assert_eq!((white.y, white.cb, white.cr), (235, 128, 128));
+    assert_eq!(convert_to!(Color::YCbCr(white), SRGB), Srgb::new(255, 255, 255));
+
+    //HWB is over the gamma-encoded channels, like CSS's hwb()
+    let hwb = convert_to!(Color::SRGBf(SrgbF::new(0.5f32, 0.5f32, 0.5f32)), HWB);
+    assert_close([hwb.h, hwb.w, hwb.b], [0.0f32, 0.5f32, 0.5f32], 0.001f32, "gray -> HWB");
+
+    let srgbf = convert_to!(Color::HWB(Hwb::new(90.0f32, 0.2f32, 0.3f32)), SRGBf);
+    assert_close([srgbf.r, srgbf.g, srgbf.b], [0.45f32, 0.7f32, 0.2f32], 0.001f32, "HWB -> sRGB");
+
+    //Whiteness and blackness past 100% between them are scaled down to a gray
+    let srgbf = convert_to!(Color::HWB(Hwb::new(120.0f32, 0.6f32, 0.6f32)), SRGBf);
+    assert_close([srgbf.r, srgbf.g, srgbf.b], [0.5f32, 0.5f32, 0.5f32], 0.001f32, "HWB past 100% -> sRGB");
+}
+
+#[test]
+fn yuv420_frames_share_chroma_between_2x2_pixels() {
+    //3x3, so the chroma planes are 2x2 and the last column and row share theirs with nobody
+    let luma = [10u8, 20, 30, 40, 50, 60, 70, 80, 90];
+    let (cb, cr) = ([1u8, 2, 3, 4], [5u8, 6, 7, 8]);
+
+    let mut nv12 = luma.to_vec();
+    nv12.extend(cb.iter().zip(cr.iter()).flat_map(|(&cb, &cr)| [cb, cr]));
+    let mut i420 = luma.to_vec();
+    i420.extend(cb);
+    i420.extend(cr);
+
+    for (data, layout) in [(nv12, YuvLayout::Nv12), (i420, YuvLayout::I420)] {
+        let image = ImageBuffer::from_yuv420(3, 3, &data, layout, YCbCrMatrix::Bt709, YCbCrRange::Limited).unwrap();
+
+        let pixels: Vec<(u8, u8, u8)> = image.pixels().iter().map(|p| (p.y, p.cb, p.cr)).collect();
+        assert_eq!(pixels, vec![(10, 1, 5), (20, 1, 5), (30, 2, 6),
+                                (40, 1, 5), (50, 1, 5), (60, 2, 6),
+                                (70, 3, 7), (80, 3, 7), (90, 4, 8)]);
+        assert!(image.pixels().iter().all(|p| p.matrix == YCbCrMatrix::Bt709 && p.range == YCbCrRange::Limited));
+
+        assert!(ImageBuffer::from_yuv420(3, 3, &data[1..], layout, YCbCrMatrix::Bt709, YCbCrRange::Limited).is_err());
+    }
+}
+
+#[test]
+fn round_trips_between_every_pair_of_formats() {
+    let mut rng = Rng(0x9e3779b9);
+
+    for from in FORMATS {
+        for to in FORMATS {
+            let tolerance = round_trip_tolerance(from, to);
+
+            for _ in 0..100 {
+                let source = Color::convert(Color::RGBAf(random_color(&mut rng, from, to)), from);
+                let there = Color::convert(source, to);
+                let back = Color::convert(there, from);
+                assert_eq!(there.get_format(), to);
+                assert_eq!(back.get_format(), from);
+
+                let (expected, actual) = (convert_to!(source, RGBAf), convert_to!(back, RGBAf));
+                let difference = (expected.r - actual.r).abs()
+                    .max((expected.g - actual.g).abs())
+                    .max((expected.b - actual.b).abs())
+                    .max((expected.a - actual.a).abs());
+
+                assert!(difference <= tolerance,
+                        "{:?} -> {:?} -> {:?}: {:?} came back as {:?}", from, to, from, source, back);
+            }
+        }
+    }
+}
+
+#[test]
+fn raw_parts_round_trip_for_every_format() {
+    let mut rng = Rng(0x2545f491);
+
+    for format in FORMATS {
+        for _ in 0..20 {
+            let color = Color::convert(Color::RGBAf(random_color(&mut rng, format, format)), format);
+
+            let (name, bytes) = color.to_raw_parts();
+            assert_eq!(name, format);
+            assert_eq!(Color::from_raw_parts(name, bytes), Ok(color));
+
+            let (name, bytes) = color.to_raw_parts_le();
+            assert_eq!(Color::from_raw_parts_le(name, bytes), Ok(color));
+
+            let (name, bytes) = color.to_raw_parts_be();
+            assert_eq!(Color::from_raw_parts_be(name, bytes), Ok(color));
+
+            assert_eq!(Color::from_tagged_bytes(&color.to_tagged_bytes()), Ok(color));
+        }
+    }
+
+    //The fixed byte orders have to come out the same on every machine
+    let gray16 = Color::Gray16(Gray16::new(0x0102));
+    assert_eq!(gray16.to_raw_parts_le(), (ColorFormatName::Gray16, vec![0x02, 0x01]));
+    assert_eq!(gray16.to_raw_parts_be(), (ColorFormatName::Gray16, vec![0x01, 0x02]));
+
+    let grayf = Color::Grayf(GrayF::new(1.0f32));
+    assert_eq!(grayf.to_raw_parts_le(), (ColorFormatName::Grayf, vec![0x00, 0x00, 0x80, 0x3f]));
+    assert_eq!(grayf.to_raw_parts_be(), (ColorFormatName::Grayf, vec![0x3f, 0x80, 0x00, 0x00]));
+
+    //Tag, length, then the channels little-endian
+    let rgba64 = Color::RGBA64(Rgba64::new(0x0102, 0x0304, 0x0506, 0x0708));
+    assert_eq!(rgba64.to_tagged_bytes(), vec![7, 8, 0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]);
+}
+
+#[test]
+fn raw_parts_reject_the_wrong_number_of_bytes() {
+    for format in FORMATS {
+        let (name, mut bytes) = Color::convert(srgb(10, 20, 30), format).to_raw_parts();
+
+        assert!(Color::from_raw_parts(name, Vec::new()).is_err(), "{:?} accepted no bytes", format);
+
+        bytes.push(0u8);
+        assert!(Color::from_raw_parts(name, bytes).is_err(), "{:?} accepted a byte too many", format);
+    }
+}
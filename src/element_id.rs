@@ -2,6 +2,7 @@ use std::hash::{Hash, Hasher};
 use std::cmp::{PartialEq, PartialOrd, Eq, Ord, Ordering};
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct ElementId {
     pub id : u32,
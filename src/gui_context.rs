@@ -1,17 +1,41 @@
 use crate::ElementId;
 use crate::GuiTreeNode;
-use crate::{Position, Size, Bounds};
+use crate::{Position, Size, Bounds, LayoutRect};
+use crate::{ComputedStyle, ElementState, StyleVariants, Theme};
 
 use std::vec::*;
 use std::collections::{VecDeque};
 use std::option::Option;
 use std::str::FromStr;
 
+/// What needs to be redone for an element before the next frame.
+/// A layout change always implies a repaint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DirtyFlags {
+    pub layout: bool,
+    pub paint: bool
+}
+
+impl DirtyFlags {
+    pub fn is_clean(&self) -> bool {
+        !self.layout && !self.paint
+    }
+}
+
 pub struct GuiContext {
     elements: Vec<ElementId>,
     free_id_queue: VecDeque<ElementId>,
     hierarchy: Vec<GuiTreeNode>,
-    bounds: Vec<Bounds>
+    bounds: Vec<Bounds>,
+    dirty: Vec<DirtyFlags>,
+    layout: Vec<LayoutRect>,
+    styles: Vec<StyleVariants>,
+    states: Vec<ElementState>,
+    widget_types: Vec<Option<String>>,
+    classes: Vec<Vec<String>>,
+    theme: Theme,
+    viewport: LayoutRect,
+    pending_damage: Vec<LayoutRect>
 }
 
 impl GuiContext {
@@ -25,7 +49,16 @@ impl GuiContext {
             elements: elem,
             free_id_queue: q,
             hierarchy: Vec::new(),
-            bounds: Vec::new()
+            bounds: Vec::new(),
+            dirty: Vec::new(),
+            layout: Vec::new(),
+            styles: Vec::new(),
+            states: Vec::new(),
+            widget_types: Vec::new(),
+            classes: Vec::new(),
+            theme: Theme::default(),
+            viewport: LayoutRect::default(),
+            pending_damage: Vec::new()
         }
     }
 
@@ -40,14 +73,28 @@ impl GuiContext {
             None => {}
         };
 
+        let dirty = DirtyFlags { layout: true, paint: true };
+
         if self.elements.len() > id.id as usize {
             self.elements[id.id as usize] = id;
             self.hierarchy[id.id as usize] = node;
             self.bounds[id.id as usize] = b;
+            self.dirty[id.id as usize] = dirty;
+            self.layout[id.id as usize] = LayoutRect::default();
+            self.styles[id.id as usize] = StyleVariants::default();
+            self.states[id.id as usize] = ElementState::Normal;
+            self.widget_types[id.id as usize] = None;
+            self.classes[id.id as usize] = Vec::new();
         } else {
             self.elements.push(id);
             self.hierarchy.push(node);
             self.bounds.push(b);
+            self.dirty.push(dirty);
+            self.layout.push(LayoutRect::default());
+            self.styles.push(StyleVariants::default());
+            self.states.push(ElementState::Normal);
+            self.widget_types.push(None);
+            self.classes.push(Vec::new());
         }
 
         if self.free_id_queue.len() == 0 {
@@ -57,34 +104,433 @@ impl GuiContext {
         id
     }
 
+    /// Deletes an element along with everything under it
     pub fn delete(&mut self, id: ElementId) -> Result<(), String> {
-        if (id.id as usize) < self.elements.len() && id.gen == self.elements[id.id as usize].gen {
-
-            if id.gen == std::u32::MAX {
-                self.free_id_queue.push_front(ElementId::new(id.id, 1));
-            } else {
-                self.free_id_queue.push_front(ElementId::new(id.id, id.gen + 1));
-            }
-
-            self.elements[id.id as usize].gen = 0;
+        if self.is_valid(id) {
             let parent = self.hierarchy[id.id as usize].parent();
-            match parent {
+            match *parent {
                 Some(p) => {
-                    let mut parent_node = self.hierarchy[p.id as usize].clone();
-                    parent_node.remove_child(id);
+                    self.hierarchy[p.id as usize].remove_child(id);
                 },
                 None => {}
             };
-            self.hierarchy[id.id as usize] = GuiTreeNode::new();
-            self.bounds[id.id as usize] = Bounds::new(Position::Absolute((0, 0)), Size::Absolute((0, 0)));
 
+            self.release_subtree(id);
             Ok(())
         } else {
             Err(String::from_str("Not a valid Element ID").unwrap())
         }
     }
 
+    //Frees the id and slot of an element and its descendants, children first
+    fn release_subtree(&mut self, id: ElementId) {
+        let children = self.hierarchy[id.id as usize].children().clone();
+        for child in children {
+            if self.is_valid(child) {
+                self.release_subtree(child);
+            }
+        }
+
+        if id.gen == std::u32::MAX {
+            self.free_id_queue.push_front(ElementId::new(id.id, 1));
+        } else {
+            self.free_id_queue.push_front(ElementId::new(id.id, id.gen + 1));
+        }
+
+        self.elements[id.id as usize].gen = 0;
+        self.hierarchy[id.id as usize] = GuiTreeNode::new();
+        self.bounds[id.id as usize] = Bounds::new(Position::Absolute((0, 0)), Size::Absolute((0, 0)));
+
+        //Whatever was under the element needs to be drawn again
+        self.pending_damage.push(self.layout[id.id as usize]);
+        self.layout[id.id as usize] = LayoutRect::default();
+        self.dirty[id.id as usize] = DirtyFlags::default();
+        self.styles[id.id as usize] = StyleVariants::default();
+        self.states[id.id as usize] = ElementState::Normal;
+        self.widget_types[id.id as usize] = None;
+        self.classes[id.id as usize] = Vec::new();
+    }
+
+    pub fn is_valid(&self, id: ElementId) -> bool {
+        (id.id as usize) < self.elements.len() && id.gen == self.elements[id.id as usize].gen
+    }
+
+    pub fn bounds(&self, id: ElementId) -> Option<Bounds> {
+        if self.is_valid(id) {
+            Some(self.bounds[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn set_bounds(&mut self, id: ElementId, b: Bounds) -> Result<(), String> {
+        if self.is_valid(id) {
+            self.bounds[id.id as usize] = b;
+            self.mark_layout_dirty(id)
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    /// Flags an element whose bounds changed. Its subtree is laid out again on the next `update_layout`.
+    pub fn mark_layout_dirty(&mut self, id: ElementId) -> Result<(), String> {
+        if self.is_valid(id) {
+            self.dirty[id.id as usize].layout = true;
+            self.dirty[id.id as usize].paint = true;
+            Ok(())
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    /// Flags an element whose appearance (style, content) changed but whose bounds did not.
+    pub fn mark_paint_dirty(&mut self, id: ElementId) -> Result<(), String> {
+        if self.is_valid(id) {
+            self.dirty[id.id as usize].paint = true;
+            Ok(())
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn style(&self, id: ElementId) -> Option<&StyleVariants> {
+        if self.is_valid(id) {
+            Some(&self.styles[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    //Padding feeds into the layout of children, so a style change re-lays out the subtree
+    pub fn set_style(&mut self, id: ElementId, style: StyleVariants) -> Result<(), String> {
+        if self.is_valid(id) {
+            self.styles[id.id as usize] = style;
+            self.mark_layout_dirty(id)
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn state(&self, id: ElementId) -> Option<ElementState> {
+        if self.is_valid(id) {
+            Some(self.states[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn set_state(&mut self, id: ElementId, state: ElementState) -> Result<(), String> {
+        if self.is_valid(id) {
+            if self.states[id.id as usize] != state {
+                self.states[id.id as usize] = state;
+                self.mark_layout_dirty(id)?;
+            }
+            Ok(())
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn widget_type(&self, id: ElementId) -> Option<&str> {
+        if self.is_valid(id) {
+            self.widget_types[id.id as usize].as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Sets the widget type stylesheet rules select on, like `button`
+    pub fn set_widget_type(&mut self, id: ElementId, widget_type: &str) -> Result<(), String> {
+        if self.is_valid(id) {
+            self.widget_types[id.id as usize] = Some(widget_type.to_owned());
+            self.mark_layout_dirty(id)
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn classes(&self, id: ElementId) -> Option<&Vec<String>> {
+        if self.is_valid(id) {
+            Some(&self.classes[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn add_class(&mut self, id: ElementId, class: &str) -> Result<(), String> {
+        if self.is_valid(id) {
+            let classes = &mut self.classes[id.id as usize];
+            if !classes.iter().any(|c| c == class) {
+                classes.push(class.to_owned());
+                self.mark_layout_dirty(id)?;
+            }
+            Ok(())
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn remove_class(&mut self, id: ElementId, class: &str) -> Result<(), String> {
+        if self.is_valid(id) {
+            let classes = &mut self.classes[id.id as usize];
+            let count = classes.len();
+            classes.retain(|c| c != class);
+            if classes.len() != count {
+                self.mark_layout_dirty(id)?;
+            }
+            Ok(())
+        } else {
+            Err(String::from_str("Not a valid Element ID").unwrap())
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Swaps the theme at runtime. Every element is restyled on the next `update_layout`.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for idx in 0..self.elements.len() {
+            if self.elements[idx].gen != 0 {
+                self.dirty[idx] = DirtyFlags { layout: true, paint: true };
+            }
+        }
+    }
+
+    /// Resolves the style of an element by inheriting down from the root of its tree
+    pub fn computed_style(&self, id: ElementId) -> Option<ComputedStyle> {
+        if !self.is_valid(id) {
+            return None;
+        }
+
+        let mut chain = vec![id];
+        while let Some(parent) = *self.hierarchy[chain[chain.len() - 1].id as usize].parent() {
+            chain.push(parent);
+        }
+
+        let mut computed = self.theme.defaults.clone();
+        for elem in chain.iter().rev() {
+            let idx = elem.id as usize;
+            computed = self.element_style(idx, &computed);
+        }
+
+        Some(computed)
+    }
+
+    pub fn dirty_flags(&self, id: ElementId) -> Option<DirtyFlags> {
+        if self.is_valid(id) {
+            Some(self.dirty[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.pending_damage.is_empty() || self.dirty.iter().any(|d| !d.is_clean())
+    }
+
+    /// The rectangle an element occupied after the last `update_layout`
+    pub fn layout_rect(&self, id: ElementId) -> Option<LayoutRect> {
+        if self.is_valid(id) {
+            Some(self.layout[id.id as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Recomputes layout for every dirty subtree and clears the dirty flags.
+    /// Returns the damaged rectangles that need to be redrawn, with overlapping ones merged so
+    /// nothing is drawn twice. An empty list means nothing changed.
+    pub fn update_layout(&mut self, viewport: LayoutRect) -> Vec<LayoutRect> {
+        if viewport == self.viewport && !self.is_dirty() {
+            return Vec::new();
+        }
+
+        let mut damage = std::mem::take(&mut self.pending_damage);
+
+        let viewport_changed = viewport != self.viewport;
+        if viewport_changed {
+            self.viewport = viewport;
+            damage.push(viewport);
+        }
+
+        for idx in 0..self.elements.len() {
+            let id = self.elements[idx];
+            if id.gen != 0 && self.hierarchy[idx].parent().is_none() {
+                let theme_style = self.theme.defaults.clone();
+                self.update_subtree(id, viewport, &theme_style, viewport_changed, &mut damage);
+            }
+        }
+
+        damage.retain(|rect| !rect.is_empty());
+        coalesce(damage)
+    }
+
+    fn update_subtree(&mut self, id: ElementId, parent_rect: LayoutRect, parent_style: &ComputedStyle,
+                      parent_moved: bool, damage: &mut Vec<LayoutRect>) {
+        let idx = id.id as usize;
+        let style = self.element_style(idx, parent_style);
+        let flags = self.dirty[idx];
+        let mut moved = false;
+
+        if flags.layout || parent_moved {
+            let old_rect = self.layout[idx];
+            let new_rect = self.bounds[idx].resolve(parent_rect);
+
+            if new_rect != old_rect {
+                self.layout[idx] = new_rect;
+                damage.push(old_rect);
+                damage.push(new_rect);
+                moved = true;
+            } else if flags.paint {
+                damage.push(new_rect);
+            }
+        } else if flags.paint {
+            damage.push(self.layout[idx]);
+        }
+
+        self.dirty[idx] = DirtyFlags::default();
+
+        //Clean subtrees under a parent that didn't move can still have dirty descendants, so keep walking
+        //Children are laid out inside the padding
+        let content_rect = style.padding.shrink(self.layout[idx]);
+        let children = self.hierarchy[idx].children().clone();
+        for child in children {
+            if self.is_valid(child) {
+                self.update_subtree(child, content_rect, &style, moved || flags.layout, damage);
+            }
+        }
+    }
+
+    fn element_style(&self, idx: usize, parent: &ComputedStyle) -> ComputedStyle {
+        self.theme.compute_style(&self.styles[idx], self.widget_types[idx].as_deref(), &self.classes[idx],
+                                 parent, self.states[idx])
+    }
+
     pub fn get_data(&self) -> (&Vec<ElementId>, &Vec<GuiTreeNode>, &Vec<Bounds>) {
         (&self.elements, &self.hierarchy, &self.bounds)
     }
 }
+
+//Merges overlapping rectangles until none overlap. A merged rectangle can overlap one that was
+//already kept, so keep going until a pass merges nothing.
+fn coalesce(mut damage: Vec<LayoutRect>) -> Vec<LayoutRect> {
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut kept: Vec<LayoutRect> = Vec::with_capacity(damage.len());
+        for rect in damage {
+            match kept.iter().position(|k| k.intersects(&rect)) {
+                Some(i) => {
+                    kept[i] = kept[i].union(&rect);
+                    merged = true;
+                },
+                None => kept.push(rect)
+            }
+        }
+        damage = kept;
+    }
+
+    damage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Padding, Style, StyleVariants};
+    use crate::color::{Color, Srgb};
+
+    const VIEWPORT: LayoutRect = LayoutRect { x: 0, y: 0, w: 200, h: 200 };
+
+    fn square(cx: i32, cy: i32, size: u32) -> Bounds {
+        Bounds::new(Position::Absolute((cx, cy)), Size::Absolute((size, size)))
+    }
+
+    fn laid_out(elements: &[(Option<usize>, Bounds)]) -> (GuiContext, Vec<ElementId>) {
+        let mut gui = GuiContext::new();
+        let mut ids: Vec<ElementId> = Vec::new();
+        for (parent, bounds) in elements {
+            let id = gui.add_elem(parent.map(|p| ids[p]), *bounds);
+            ids.push(id);
+        }
+
+        //The first layout damages the whole viewport, and nothing else
+        assert_eq!(gui.update_layout(VIEWPORT), vec![VIEWPORT]);
+        assert!(!gui.is_dirty());
+        (gui, ids)
+    }
+
+    #[test]
+    fn moving_an_element_damages_where_it_was_and_where_it_is() {
+        let (mut gui, ids) = laid_out(&[(None, square(50, 50, 20))]);
+        let old = LayoutRect::new(40, 40, 20, 20);
+        assert_eq!(gui.layout_rect(ids[0]), Some(old));
+
+        gui.set_bounds(ids[0], square(150, 150, 20)).unwrap();
+        assert_eq!(gui.dirty_flags(ids[0]), Some(DirtyFlags { layout: true, paint: true }));
+        assert_eq!(gui.update_layout(VIEWPORT), vec![old, LayoutRect::new(140, 140, 20, 20)]);
+
+        //A small move overlaps the old position, so the two are drawn as one
+        gui.set_bounds(ids[0], square(155, 150, 20)).unwrap();
+        assert_eq!(gui.update_layout(VIEWPORT), vec![LayoutRect::new(140, 140, 25, 20)]);
+        assert_eq!(gui.update_layout(VIEWPORT), Vec::new());
+    }
+
+    #[test]
+    fn a_paint_only_change_damages_just_the_element() {
+        let (mut gui, ids) = laid_out(&[(None, square(100, 100, 100)), (Some(0), Bounds::new(Position::Relative((-20, -20)), Size::Absolute((10, 10)))), (None, square(180, 180, 10))]);
+
+        gui.mark_paint_dirty(ids[2]).unwrap();
+        assert_eq!(gui.dirty_flags(ids[2]), Some(DirtyFlags { layout: false, paint: true }));
+        assert_eq!(gui.update_layout(VIEWPORT), vec![LayoutRect::new(175, 175, 10, 10)]);
+        assert!(!gui.is_dirty());
+
+        //A child inside its parent is covered by the parent's rectangle
+        gui.mark_paint_dirty(ids[0]).unwrap();
+        gui.mark_paint_dirty(ids[1]).unwrap();
+        assert_eq!(gui.update_layout(VIEWPORT), vec![LayoutRect::new(50, 50, 100, 100)]);
+    }
+
+    #[test]
+    fn deleting_an_element_deletes_its_subtree() {
+        let (mut gui, ids) = laid_out(&[
+            (None, square(100, 100, 100)),
+            (Some(0), Bounds::new(Position::Relative((0, 0)), Size::Relative((-20, -20)))),
+            (Some(1), Bounds::new(Position::Relative((10, 10)), Size::Absolute((10, 10)))),
+            (None, square(10, 10, 10)),
+        ]);
+
+        gui.delete(ids[1]).unwrap();
+        assert!(gui.is_valid(ids[0]) && gui.is_valid(ids[3]));
+        assert!(!gui.is_valid(ids[1]) && !gui.is_valid(ids[2]));
+        assert_eq!(gui.delete(ids[2]), Err(String::from("Not a valid Element ID")));
+        assert!(gui.get_data().1[ids[0].id as usize].children().is_empty());
+
+        //The grandchild sat inside the child, so one rectangle covers both
+        assert_eq!(gui.update_layout(VIEWPORT), vec![LayoutRect::new(60, 60, 80, 80)]);
+        assert_eq!(gui.update_layout(VIEWPORT), Vec::new());
+
+        //Both slots are free again
+        let reused = [gui.add_elem(None, square(0, 0, 1)), gui.add_elem(None, square(0, 0, 1))];
+        assert!(reused.iter().all(|id| id.id == ids[1].id || id.id == ids[2].id));
+    }
+
+    #[test]
+    fn padding_applies_to_direct_children_only() {
+        let fill = Bounds::new(Position::Relative((0, 0)), Size::Fill);
+        let (mut gui, ids) = laid_out(&[(None, square(100, 100, 100)), (Some(0), fill), (Some(1), fill)]);
+
+        let red = Color::SRGB(Srgb::new(255, 0, 0));
+        gui.set_style(ids[0], StyleVariants::new(Style::new().with_padding(Padding::uniform(10)).with_text_color(red))).unwrap();
+        gui.update_layout(VIEWPORT);
+
+        assert_eq!(gui.layout_rect(ids[1]), Some(LayoutRect::new(60, 60, 80, 80)));
+        assert_eq!(gui.layout_rect(ids[2]), Some(LayoutRect::new(60, 60, 80, 80)));
+
+        //Text color carries down, box properties don't
+        let grandchild = gui.computed_style(ids[2]).unwrap();
+        assert_eq!(grandchild.text_color, red);
+        assert_eq!(grandchild.padding, gui.theme().defaults.padding);
+    }
+}
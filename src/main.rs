@@ -4,33 +4,45 @@ mod gui_context;
 mod element_id;
 mod gui_tree_node;
 mod bounds;
+mod style;
+mod stylesheet;
 mod color;
+mod raster;
 mod backend_sdl2;
+mod backend_software;
 
 pub use element_id::*;
 pub use gui_context::*;
 pub use gui_tree_node::*;
 pub use bounds::*;
+pub use style::*;
+pub use stylesheet::*;
 use color::*;
+pub use raster::*;
 use backend_sdl2::*;
+pub use backend_software::*;
 
-use sdl2::pixels;
 use sdl2::rect::*;
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
 
+//Child regions take on part of their parent's color, mixed in linear light
+fn tint(base: Color, addition: Color) -> Color {
+	interpolate(base, addition, 0.5f32, InterpolationSpace::LinearRgb)
+}
+
 #[derive(Clone, Debug)]
 struct GuiRegion {
     top_bound: f32,
     bottom_bound: f32,
     left_bound: f32,
     right_bound: f32,
-	color: pixels::Color,
+	color: Color,
     child_regions: Option<Vec<GuiRegion>>,
 }
 
 impl GuiRegion {
-	pub fn new(left: f32, right: f32, bottom: f32, top: f32, col: pixels::Color) -> GuiRegion {
+	pub fn new(left: f32, right: f32, bottom: f32, top: f32, col: Color) -> GuiRegion {
 		let (bottom, top) = if top > bottom {
 			(top, bottom)
 		} else {
@@ -52,7 +64,7 @@ impl GuiRegion {
 			child_regions: None
 		}
 	}
-	pub fn from_rect(x: f32, y: f32, w: f32, h: f32, col: pixels::Color) -> GuiRegion {
+	pub fn from_rect(x: f32, y: f32, w: f32, h: f32, col: Color) -> GuiRegion {
 		let (x, w) = if w < 0.0f32 {
 			(x + w, -w)
 		} else {
@@ -75,7 +87,7 @@ impl GuiRegion {
 		}
 	}
 	
-	pub fn split_at_relative_x(&mut self, x: f32, left_color: pixels::Color, right_color: pixels::Color) {
+	pub fn split_at_relative_x(&mut self, x: f32, left_color: Color, right_color: Color) {
 		match &mut self.child_regions {
 			None => {
 				if x > 0.0f32 && x <= (self.right_bound - self.left_bound) {
@@ -83,8 +95,8 @@ impl GuiRegion {
 					let left_child_left = self.left_bound;
 					let right_child_right = self.right_bound;
 					
-					let left_color = blend_colors(self.color, left_color, 0.5f32);
-					let right_color = blend_colors(self.color, right_color, 0.5f32);
+					let left_color = tint(self.color, left_color);
+					let right_color = tint(self.color, right_color);
 					
 					let left_child = GuiRegion::new(left_child_left, split_x, self.bottom_bound, self.top_bound, left_color);
 					let right_child = GuiRegion::new(split_x, right_child_right, self.bottom_bound, self.top_bound, right_color);
@@ -100,8 +112,8 @@ impl GuiRegion {
 						if children[i].left_bound < absolute_x && children[i].right_bound > absolute_x {
 							let child_relative_x = absolute_x - children[i].left_bound;
 							
-							let left_color = blend_colors(self.color, left_color, 0.5f32);
-							let right_color = blend_colors(self.color, right_color, 0.5f32);
+							let left_color = tint(self.color, left_color);
+							let right_color = tint(self.color, right_color);
 							
 							children[i].split_at_relative_x(child_relative_x, left_color, right_color);
 						}
@@ -111,7 +123,7 @@ impl GuiRegion {
 		}
 	}
 	
-	pub fn split_at_relative_y(&mut self, y: f32, top_color: pixels::Color, bottom_color: pixels::Color) {
+	pub fn split_at_relative_y(&mut self, y: f32, top_color: Color, bottom_color: Color) {
 		match &mut self.child_regions {
 			None => {
 				if y > 0.0f32 && y <= (self.bottom_bound - self.top_bound) {
@@ -119,8 +131,8 @@ impl GuiRegion {
 					let top_child_top = self.top_bound;
 					let bottom_child_bottom = self.bottom_bound;
 					
-					let top_color = blend_colors(self.color, top_color, 0.5f32);
-					let bottom_color = blend_colors(self.color, bottom_color, 0.5f32);
+					let top_color = tint(self.color, top_color);
+					let bottom_color = tint(self.color, bottom_color);
 					
 					let top_child = GuiRegion::new(self.left_bound, self.right_bound, split_y, top_child_top, top_color);
 					let bottom_child = GuiRegion::new(self.left_bound, self.right_bound, bottom_child_bottom, split_y, bottom_color);
@@ -136,8 +148,8 @@ impl GuiRegion {
 						if children[i].top_bound < absolute_y && children[i].bottom_bound > absolute_y {
 							let child_relative_y = absolute_y - children[i].top_bound;
 							
-							let top_color = blend_colors(self.color, top_color, 0.5f32);
-							let bottom_color = blend_colors(self.color, bottom_color, 0.5f32);
+							let top_color = tint(self.color, top_color);
+							let bottom_color = tint(self.color, bottom_color);
 					
 							children[i].split_at_relative_y(child_relative_y, top_color, bottom_color);
 						}
@@ -147,7 +159,7 @@ impl GuiRegion {
 		}
 	}
 	
-	pub fn split_at_relative_point(&mut self, x: f32, y: f32, top_left_color: pixels::Color, top_right_color: pixels::Color, bottom_left_color: pixels::Color, bottom_right_color: pixels::Color) {
+	pub fn split_at_relative_point(&mut self, x: f32, y: f32, top_left_color: Color, top_right_color: Color, bottom_left_color: Color, bottom_right_color: Color) {
 		match &mut self.child_regions {
 			None => {
 				if x > 0.0f32 && x <= (self.right_bound - self.left_bound) && 
@@ -161,10 +173,10 @@ impl GuiRegion {
 					let top_child_top = self.top_bound;
 					let bottom_child_bottom = self.bottom_bound;
 					
-					let top_left_color = blend_colors(self.color, top_left_color, 0.5f32);
-					let top_right_color = blend_colors(self.color, top_right_color, 0.5f32);
-					let bottom_left_color = blend_colors(self.color, bottom_left_color, 0.5f32);
-					let bottom_right_color = blend_colors(self.color, bottom_right_color, 0.5f32);
+					let top_left_color = tint(self.color, top_left_color);
+					let top_right_color = tint(self.color, top_right_color);
+					let bottom_left_color = tint(self.color, bottom_left_color);
+					let bottom_right_color = tint(self.color, bottom_right_color);
 					
 					let top_left_child = GuiRegion::new(left_child_left, split_x, split_y, top_child_top, top_left_color);
 					let top_right_child = GuiRegion::new(split_x, right_child_right, split_y, top_child_top, top_right_color);
@@ -189,10 +201,10 @@ impl GuiRegion {
 							let child_relative_x = absolute_x - children[i].left_bound;
 							let child_relative_y = absolute_y - children[i].top_bound;
 							
-							let top_left_color = blend_colors(self.color, top_left_color, 0.5f32);
-							let top_right_color = blend_colors(self.color, top_right_color, 0.5f32);
-							let bottom_left_color = blend_colors(self.color, bottom_left_color, 0.5f32);
-							let bottom_right_color = blend_colors(self.color, bottom_right_color, 0.5f32);
+							let top_left_color = tint(self.color, top_left_color);
+							let top_right_color = tint(self.color, top_right_color);
+							let bottom_left_color = tint(self.color, bottom_left_color);
+							let bottom_right_color = tint(self.color, bottom_right_color);
 							
 							children[i].split_at_relative_point(child_relative_x, child_relative_y, top_left_color, top_right_color, bottom_left_color, bottom_right_color);
 							break;
@@ -205,7 +217,7 @@ impl GuiRegion {
 	
 }
 
-fn region_to_colored_rect(region: &GuiRegion) -> (Rect, pixels::Color) {
+fn region_to_colored_rect(region: &GuiRegion) -> (Rect, Color) {
 	let width = region.right_bound - region.left_bound;
 	let height = region.bottom_bound - region.top_bound;
 	let rect = Rect::new(region.left_bound as i32, region.top_bound as i32, width as u32, height as u32);
@@ -214,7 +226,7 @@ fn region_to_colored_rect(region: &GuiRegion) -> (Rect, pixels::Color) {
 
 fn draw_region<'a, T: sdl2::render::RenderTarget>(canvas: &mut sdl2::render::Canvas<T>, 
 												  region: &'a GuiRegion, 
-												  border_color: sdl2::pixels::Color, 
+												  border_color: Color, 
 												  border_size: u32)
 {
 	let region_rect = region_to_colored_rect(region);
@@ -243,14 +255,23 @@ fn main() {
         .build()
         .unwrap();
 
-    let mut canvas = window.into_canvas().build().unwrap();
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+
+    //The window's back buffer is undefined after a present, so the frame is kept in a texture
+    //and only the damaged parts of it are redrawn
+    let texture_creator = canvas.texture_creator();
+    let mut frame = texture_creator.create_texture_target(None, 800, 600).unwrap();
 
-    canvas.set_draw_color(pixels::Color::RGB(232, 230, 224));
+    let mut gui = GuiContext::new();
+
+    canvas.set_draw_color(sdl_color(gui.theme().defaults.background));
     canvas.clear();
     canvas.present();
 	
 	let mut color_idx: usize = 0;
-	let colors = [pixels::Color::WHITE, pixels::Color::BLACK, pixels::Color::RED, pixels::Color::GREEN, pixels::Color::BLUE, pixels::Color::GRAY, pixels::Color::CYAN, pixels::Color::YELLOW, pixels::Color::MAGENTA];
+	let colors = [Color::SRGB(Srgb::new(255, 255, 255)), Color::SRGB(Srgb::new(0, 0, 0)), Color::SRGB(Srgb::new(255, 0, 0)),
+				  Color::SRGB(Srgb::new(0, 255, 0)), Color::SRGB(Srgb::new(0, 0, 255)), Color::SRGB(Srgb::new(128, 128, 128)),
+				  Color::SRGB(Srgb::new(0, 255, 255)), Color::SRGB(Srgb::new(255, 255, 0)), Color::SRGB(Srgb::new(255, 0, 255))];
 	let mut next_color = || { let col = colors[color_idx]; color_idx = (color_idx + 1) % colors.len(); return col; };
 
 	let mut window_region = GuiRegion::from_rect(0.0f32, 0.0f32, 800.0f32, 600.0f32, next_color());
@@ -259,20 +280,62 @@ fn main() {
 	window_region.split_at_relative_point(200.0f32, 200.0f32, next_color(), next_color(), next_color(), next_color());
 	window_region.split_at_relative_point(600.0f32, 500.0f32, next_color(), next_color(), next_color(), next_color());
 
+    let viewport = LayoutRect::new(0, 0, 800, 600);
+    let root = gui.add_elem(None, Bounds::new(Position::Align(HorizAlign::LeftOffset(0), VertAlign::TopOffset(0)), Size::Fill));
+
+    //A stylesheet passed on the command line is loaded as the theme, and reloaded whenever it changes.
+    //T switches the theme it's laid over between light and dark, and a reload keeps that choice.
+    let sheet_path = std::env::args().nth(1);
+    let mut watcher = sheet_path.as_ref().map(StylesheetWatcher::new);
+    let mut sheet: Option<Stylesheet> = None;
+    let mut dark = false;
+    let themed = |sheet: &Option<Stylesheet>, dark: bool| {
+        let base = if dark { Theme::dark() } else { Theme::light() };
+        match (sheet, &sheet_path) {
+            (Some(sheet), Some(path)) => sheet.to_theme(&format!("{} ({})", path, base.name), &base),
+            _ => base
+        }
+    };
+
     let mut event_pump = sdl_context.event_pump().unwrap();
 
     'running: loop {
-        canvas.set_draw_color(pixels::Color::RGB(232, 230, 224));
-        canvas.clear();
+        //Block until something happens instead of spinning- with nothing dirty there is nothing to draw.
+        //When watching a stylesheet, wake up every so often to check it.
+        let first_event = match watcher {
+            Some(_) => event_pump.wait_event_timeout(250),
+            None => Some(event_pump.wait_event())
+        };
 
-        draw_region(&mut canvas, &window_region, pixels::Color::RGB(232, 230, 224), 32);
+        if let Some(watcher) = watcher.as_mut() {
+            match watcher.poll() {
+                Some(Ok(loaded)) => {
+                    sheet = Some(loaded);
+                    gui.set_theme(themed(&sheet, dark));
+                },
+                Some(Err(e @ StylesheetError::Parse { .. })) => {
+                    eprintln!("{}:{}", watcher.path().display(), e);
+                },
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                },
+                None => {}
+            }
+        }
 
-        for event in event_pump.poll_iter() {
+        for event in first_event.into_iter().chain(event_pump.poll_iter()) {
             match event {
                 Event::Quit {..} |
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                Event::KeyDown { keycode: Some(Keycode::T), .. } => {
+                    dark = !dark;
+                    gui.set_theme(themed(&sheet, dark));
+                },
+                Event::Window { win_event: WindowEvent::Exposed, .. } => {
+                    gui.mark_paint_dirty(root).unwrap();
+                },
                 Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, .. } => {
 
                 },
@@ -283,6 +346,23 @@ fn main() {
             }
         }
 
+        let damage = gui.update_layout(viewport);
+        if damage.is_empty() {
+            continue;
+        }
+
+        let root_style = gui.computed_style(root).unwrap();
+        let background = root_style.background;
+        let border_color = root_style.border_color;
+
+        canvas.with_texture_canvas(&mut frame, |frame_canvas| {
+            redraw_damaged(frame_canvas, &damage, |damaged_canvas, rect| {
+                draw_filled_rect(damaged_canvas, rect, background);
+                draw_region(damaged_canvas, &window_region, border_color, 32);
+            });
+        }).unwrap();
+
+        canvas.copy(&frame, None, None).unwrap();
         canvas.present();
     }
 }
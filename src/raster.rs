@@ -0,0 +1,524 @@
+use crate::color::*;
+use crate::LayoutRect;
+
+/*  Backend-independent rasterization of the shapes the GUI draws.
+ *  Shapes are described by signed distance functions and each pixel's coverage is taken from the distance
+ *  at its center, which gives anti-aliased edges for free. Backends only need to implement `RasterTarget`.
+ */
+
+/// Something shapes can be rasterized onto. Colors are linear, straight (not premultiplied) alpha.
+pub trait RasterTarget {
+    /// Fills pixels [x_start, x_end) of row y
+    fn fill_span(&mut self, x_start: i32, x_end: i32, y: i32, color: RgbaF);
+
+    /// Blends color over a single pixel, with its alpha scaled by coverage
+    fn blend_pixel(&mut self, x: i32, y: i32, color: RgbaF, coverage: f32);
+
+    /// Called once a shape has been rasterized. A shape touches each pixel at most once, so targets
+    /// can batch its spans and pixels in any order and draw them here.
+    fn end_primitive(&mut self) {}
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32
+}
+
+impl CornerRadii {
+    pub fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> CornerRadii {
+        CornerRadii {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left
+        }
+    }
+
+    pub fn uniform(radius: f32) -> CornerRadii {
+        CornerRadii::new(radius, radius, radius, radius)
+    }
+
+    //Radii of a rect grown (or shrunk, for negative amounts) by amount on every side
+    fn grow(&self, amount: f32) -> CornerRadii {
+        CornerRadii::new((self.top_left + amount).max(0.0f32),
+                         (self.top_right + amount).max(0.0f32),
+                         (self.bottom_right + amount).max(0.0f32),
+                         (self.bottom_left + amount).max(0.0f32))
+    }
+}
+
+/// Where a border sits relative to the rectangle it outlines
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BorderPlacement {
+    Inset,
+    Outset
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Shadow {
+    pub offset: (f32, f32),
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color
+}
+
+/// Where a gradient runs, in the same pixel coordinates as the rects it fills
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GradientShape {
+    /// Offset 0 at start, 1 at end. Lines perpendicular to start-end share a color.
+    Linear { start: (f32, f32), end: (f32, f32) },
+    /// Offset 0 at the center, 1 at radius
+    Radial { center: (f32, f32), radius: f32 }
+}
+
+/// A multi-stop gradient. Past the first and last stop the end colors continue,
+/// and two stops at the same offset make a hard edge.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<GradientStop>,
+    pub space: InterpolationSpace
+}
+
+//Resolution of the precomputed color ramp. Interpolating in Lab or HSL per pixel is far too slow,
+//and 256 steps is already finer than 8-bit output can resolve.
+const GRADIENT_RAMP_SIZE: usize = 256;
+
+impl Gradient {
+    pub fn linear(start: (f32, f32), end: (f32, f32), space: InterpolationSpace) -> Gradient {
+        Gradient {
+            shape: GradientShape::Linear { start, end },
+            stops: Vec::new(),
+            space
+        }
+    }
+
+    pub fn radial(center: (f32, f32), radius: f32, space: InterpolationSpace) -> Gradient {
+        Gradient {
+            shape: GradientShape::Radial { center, radius },
+            stops: Vec::new(),
+            space
+        }
+    }
+
+    /// Adds a stop, keeping stops sorted. A stop at an existing offset goes after the ones already there.
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Gradient {
+        let idx = self.stops.iter().position(|stop| stop.offset > offset).unwrap_or(self.stops.len());
+        self.stops.insert(idx, GradientStop { offset, color });
+
+        self
+    }
+
+    /// The color at offset along the gradient. Transparent if there are no stops.
+    pub fn color_at(&self, offset: f32) -> Color {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Color::RGBAf(RgbaF::new(0.0f32, 0.0f32, 0.0f32, 0.0f32))
+        };
+
+        if offset <= first.offset {
+            return first.color;
+        }
+        if offset >= last.offset {
+            return last.color;
+        }
+
+        //Strictly less, so at a hard edge the color comes from the pair starting at the later stop.
+        //a.offset <= offset < b.offset here, so the span is never zero.
+        for pair in self.stops.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if offset < b.offset {
+                return interpolate(a.color, b.color, (offset - a.offset) / (b.offset - a.offset), self.space);
+            }
+        }
+
+        last.color
+    }
+
+    /// The offset along the gradient at pixel position (x, y)
+    pub fn offset_at(&self, x: f32, y: f32) -> f32 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let length_sq = dx * dx + dy * dy;
+                if length_sq == 0.0f32 {
+                    return 0.0f32;
+                }
+                ((x - start.0) * dx + (y - start.1) * dy) / length_sq
+            },
+            GradientShape::Radial { center, radius } => {
+                if radius <= 0.0f32 {
+                    return 1.0f32;
+                }
+                ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt() / radius
+            }
+        }
+    }
+
+    //Colors sampled evenly over the range the stops cover
+    fn ramp(&self) -> (f32, f32, Vec<RgbaF>) {
+        let start = self.stops.first().map_or(0.0f32, |stop| stop.offset);
+        let end = self.stops.last().map_or(1.0f32, |stop| stop.offset);
+
+        let colors = (0..GRADIENT_RAMP_SIZE).map(|i| {
+            let t = i as f32 / (GRADIENT_RAMP_SIZE - 1) as f32;
+            linear_rgba(self.color_at(start + (end - start) * t))
+        }).collect();
+
+        (start, end, colors)
+    }
+}
+
+//A rect with float edges, so shapes can be grown and shrunk by fractional amounts
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct RoundedRect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    radii: CornerRadii
+}
+
+impl RoundedRect {
+    fn new(rect: LayoutRect, radii: CornerRadii) -> RoundedRect {
+        let rounded = RoundedRect {
+            left: rect.x as f32,
+            top: rect.y as f32,
+            right: rect.right() as f32,
+            bottom: rect.bottom() as f32,
+            radii
+        };
+
+        rounded.with_clamped_radii()
+    }
+
+    fn grow(&self, amount: f32) -> RoundedRect {
+        let grown = RoundedRect {
+            left: self.left - amount,
+            top: self.top - amount,
+            right: self.right + amount,
+            bottom: self.bottom + amount,
+            radii: self.radii.grow(amount)
+        };
+
+        grown.with_clamped_radii()
+    }
+
+    fn offset(&self, dx: f32, dy: f32) -> RoundedRect {
+        RoundedRect {
+            left: self.left + dx,
+            top: self.top + dy,
+            right: self.right + dx,
+            bottom: self.bottom + dy,
+            radii: self.radii
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.right <= self.left || self.bottom <= self.top
+    }
+
+    //Radii can't be more than half of the side they're on, or the corners overlap
+    fn with_clamped_radii(mut self) -> RoundedRect {
+        let max_radius = ((self.right - self.left).min(self.bottom - self.top) / 2.0f32).max(0.0f32);
+        self.radii = CornerRadii::new(self.radii.top_left.min(max_radius),
+                                      self.radii.top_right.min(max_radius),
+                                      self.radii.bottom_right.min(max_radius),
+                                      self.radii.bottom_left.min(max_radius));
+        self
+    }
+
+    //Negative inside, positive outside, in pixels
+    fn distance(&self, x: f32, y: f32) -> f32 {
+        let center_x = (self.left + self.right) / 2.0f32;
+        let center_y = (self.top + self.bottom) / 2.0f32;
+        let half_w = (self.right - self.left) / 2.0f32;
+        let half_h = (self.bottom - self.top) / 2.0f32;
+
+        let radius = match (x < center_x, y < center_y) {
+            (true, true) => self.radii.top_left,
+            (false, true) => self.radii.top_right,
+            (false, false) => self.radii.bottom_right,
+            (true, false) => self.radii.bottom_left
+        };
+
+        let q_x = (x - center_x).abs() - (half_w - radius);
+        let q_y = (y - center_y).abs() - (half_h - radius);
+
+        let outside = (q_x.max(0.0f32).powi(2) + q_y.max(0.0f32).powi(2)).sqrt();
+        let inside = q_x.max(q_y).min(0.0f32);
+
+        outside + inside - radius
+    }
+
+    fn coverage(&self, x: f32, y: f32) -> f32 {
+        (0.5f32 - self.distance(x, y)).clamp(0.0f32, 1.0f32)
+    }
+
+    //Pixel rows and columns the shape can touch
+    fn pixel_bounds(&self, margin: f32) -> (i32, i32, i32, i32) {
+        ((self.left - margin).floor() as i32, (self.top - margin).floor() as i32,
+         (self.right + margin).ceil() as i32, (self.bottom + margin).ceil() as i32)
+    }
+}
+
+fn linear_rgba(color: Color) -> RgbaF {
+    match Color::convert(color, ColorFormatName::RGBAf) {
+        Color::RGBAf(rgbaf) => rgbaf,
+        _ => unreachable!("Color::convert always returns the requested format")
+    }
+}
+
+//Rasterizes any shape given as a coverage function, collapsing fully covered runs into spans
+fn rasterize<T: RasterTarget, F: Fn(f32, f32) -> f32>(target: &mut T, bounds: (i32, i32, i32, i32), color: RgbaF, coverage: F) {
+    let (left, top, right, bottom) = bounds;
+
+    for y in top..bottom {
+        let mut run_start: Option<i32> = None;
+
+        for x in left..right {
+            let cov = coverage(x as f32 + 0.5f32, y as f32 + 0.5f32);
+
+            if cov >= 1.0f32 {
+                if run_start.is_none() {
+                    run_start = Some(x);
+                }
+                continue;
+            }
+
+            if let Some(start) = run_start.take() {
+                target.fill_span(start, x, y, color);
+            }
+            if cov > 0.0f32 {
+                target.blend_pixel(x, y, color, cov);
+            }
+        }
+
+        if let Some(start) = run_start {
+            target.fill_span(start, right, y, color);
+        }
+    }
+
+    target.end_primitive();
+}
+
+pub fn fill_rounded_rect<T: RasterTarget>(target: &mut T, rect: LayoutRect, radii: CornerRadii, color: Color) {
+    fill_shape_with(target, RoundedRect::new(rect, radii), color);
+}
+
+fn fill_shape_with<T: RasterTarget>(target: &mut T, shape: RoundedRect, color: Color) {
+    if shape.is_empty() {
+        return;
+    }
+
+    rasterize(target, shape.pixel_bounds(1.0f32), linear_rgba(color), |x, y| shape.coverage(x, y));
+}
+
+/// Fills a rounded rect with a gradient
+pub fn fill_rounded_rect_gradient<T: RasterTarget>(target: &mut T, rect: LayoutRect, radii: CornerRadii, gradient: &Gradient) {
+    let shape = RoundedRect::new(rect, radii);
+    if shape.is_empty() {
+        return;
+    }
+
+    let (ramp_start, ramp_end, ramp) = gradient.ramp();
+    let ramp_span = ramp_end - ramp_start;
+    let ramp_index = |x: f32, y: f32| -> usize {
+        if ramp_span <= 0.0f32 {
+            return if gradient.offset_at(x, y) < ramp_start { 0 } else { GRADIENT_RAMP_SIZE - 1 };
+        }
+        let t = ((gradient.offset_at(x, y) - ramp_start) / ramp_span).clamp(0.0f32, 1.0f32);
+        (t * (GRADIENT_RAMP_SIZE - 1) as f32).round() as usize
+    };
+
+    //Like rasterize, but runs also end where the color changes
+    let (left, top, right, bottom) = shape.pixel_bounds(1.0f32);
+    for y in top..bottom {
+        let mut run: Option<(i32, usize)> = None;
+
+        for x in left..right {
+            let (px, py) = (x as f32 + 0.5f32, y as f32 + 0.5f32);
+            let cov = shape.coverage(px, py);
+            let idx = ramp_index(px, py);
+
+            if let Some((start, run_idx)) = run {
+                if cov < 1.0f32 || run_idx != idx {
+                    target.fill_span(start, x, y, ramp[run_idx]);
+                    run = None;
+                }
+            }
+
+            if cov >= 1.0f32 {
+                if run.is_none() {
+                    run = Some((x, idx));
+                }
+            } else if cov > 0.0f32 {
+                target.blend_pixel(x, y, ramp[idx], cov);
+            }
+        }
+
+        if let Some((start, run_idx)) = run {
+            target.fill_span(start, right, y, ramp[run_idx]);
+        }
+    }
+
+    target.end_primitive();
+}
+
+/// Strokes the outline of a rounded rect. An inset border stays inside rect, an outset one surrounds it.
+pub fn stroke_rounded_rect<T: RasterTarget>(target: &mut T, rect: LayoutRect, radii: CornerRadii, width: f32,
+                                            placement: BorderPlacement, color: Color) {
+    if width <= 0.0f32 {
+        return;
+    }
+
+    let shape = RoundedRect::new(rect, radii);
+    let (outer, inner) = match placement {
+        BorderPlacement::Inset => (shape, shape.grow(-width)),
+        BorderPlacement::Outset => (shape.grow(width), shape)
+    };
+    if outer.is_empty() {
+        return;
+    }
+
+    rasterize(target, outer.pixel_bounds(1.0f32), linear_rgba(color), |x, y| {
+        let inner_cov = if inner.is_empty() { 0.0f32 } else { inner.coverage(x, y) };
+        (outer.coverage(x, y) - inner_cov).max(0.0f32)
+    });
+}
+
+/// Fills a rounded rect with a border around it in one go, without a seam between the two
+pub fn draw_bordered_rounded_rect<T: RasterTarget>(target: &mut T, rect: LayoutRect, radii: CornerRadii,
+                                                   fill: Color, border_width: f32, placement: BorderPlacement,
+                                                   border_color: Color) {
+    //An inset fill ends exactly where the inside edge of the stroke starts, fractional widths included
+    let shape = RoundedRect::new(rect, radii);
+    let fill_shape = match placement {
+        BorderPlacement::Inset => shape.grow(-border_width.max(0.0f32)),
+        BorderPlacement::Outset => shape
+    };
+
+    fill_shape_with(target, fill_shape, fill);
+    stroke_rounded_rect(target, rect, radii, border_width, placement, border_color);
+}
+
+/// Draws the soft shadow of a rounded rect. Draw it before the rect itself.
+pub fn draw_shadow<T: RasterTarget>(target: &mut T, rect: LayoutRect, radii: CornerRadii, shadow: Shadow) {
+    let shape = RoundedRect::new(rect, radii)
+        .grow(shadow.spread)
+        .offset(shadow.offset.0, shadow.offset.1);
+    if shape.is_empty() {
+        return;
+    }
+
+    //The blur radius is treated as two standard deviations of a gaussian, like CSS box-shadow
+    let sigma = (shadow.blur / 2.0f32).max(0.0f32);
+    let color = linear_rgba(shadow.color);
+
+    if sigma == 0.0f32 {
+        rasterize(target, shape.pixel_bounds(1.0f32), color, |x, y| shape.coverage(x, y));
+        return;
+    }
+
+    let bounds = shape.pixel_bounds(sigma * 3.0f32 + 1.0f32);
+    rasterize(target, bounds, color, |x, y| {
+        let d = shape.distance(x, y);
+        0.5f32 * (1.0f32 - erf(d / (sigma * std::f32::consts::SQRT_2)))
+    });
+}
+
+//Abramowitz and Stegun 7.1.26, good to about 1.5e-7- far past what 8-bit output can show
+fn erf(x: f32) -> f32 {
+    let sign = if x < 0.0f32 { -1.0f32 } else { 1.0f32 };
+    let x = x.abs();
+
+    let t = 1.0f32 / (1.0f32 + 0.3275911f32 * x);
+    let poly = t * (0.2548296f32 + t * (-0.28449672f32 + t * (1.4214138f32 + t * (-1.4531521f32 + t * 1.0614054f32))));
+
+    sign * (1.0f32 - poly * (-x * x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Color = Color::SRGB(Srgb { r: 255, g: 255, b: 255 });
+    const RED: Color = Color::SRGB(Srgb { r: 255, g: 0, b: 0 });
+    const BLUE: Color = Color::SRGB(Srgb { r: 0, g: 0, b: 255 });
+    const BLACK: Color = Color::SRGB(Srgb { r: 0, g: 0, b: 0 });
+
+    fn assert_rgba(actual: Color, expected: Color) {
+        let (a, e) = (linear_rgba(actual), linear_rgba(expected));
+        for (x, y) in [(a.r, e.r), (a.g, e.g), (a.b, e.b), (a.a, e.a)] {
+            assert!((x - y).abs() < 1e-3f32, "got {:?}, expected {:?}", a, e);
+        }
+    }
+
+    #[test]
+    fn stops_are_sorted() {
+        let gradient = Gradient::linear((0.0f32, 0.0f32), (1.0f32, 0.0f32), InterpolationSpace::LinearRgb)
+            .with_stop(1.0f32, BLACK)
+            .with_stop(0.0f32, WHITE)
+            .with_stop(0.5f32, RED);
+
+        let offsets: Vec<f32> = gradient.stops.iter().map(|stop| stop.offset).collect();
+        assert_eq!(offsets, vec![0.0f32, 0.5f32, 1.0f32]);
+        assert_rgba(gradient.color_at(0.5f32), RED);
+        assert_rgba(gradient.color_at(0.25f32), Color::RGBAf(RgbaF::new(1.0f32, 0.5f32, 0.5f32, 1.0f32)));
+    }
+
+    #[test]
+    fn end_colors_continue_past_the_stops() {
+        let gradient = Gradient::linear((0.0f32, 0.0f32), (1.0f32, 0.0f32), InterpolationSpace::Oklab)
+            .with_stop(0.2f32, RED)
+            .with_stop(0.8f32, BLUE);
+
+        assert_eq!(gradient.color_at(-1.0f32), RED);
+        assert_eq!(gradient.color_at(0.0f32), RED);
+        assert_eq!(gradient.color_at(1.0f32), BLUE);
+        assert_eq!(gradient.color_at(2.0f32), BLUE);
+
+        let empty = Gradient::radial((0.0f32, 0.0f32), 1.0f32, InterpolationSpace::Oklab);
+        assert_eq!(linear_rgba(empty.color_at(0.5f32)).a, 0.0f32);
+    }
+
+    #[test]
+    fn stops_at_the_same_offset_make_a_hard_edge() {
+        let gradient = Gradient::linear((0.0f32, 0.0f32), (1.0f32, 0.0f32), InterpolationSpace::LinearRgb)
+            .with_stop(0.0f32, WHITE)
+            .with_stop(0.5f32, RED)
+            .with_stop(0.5f32, BLUE)
+            .with_stop(1.0f32, BLACK);
+
+        //The later stop wins at the edge itself
+        assert_eq!(gradient.stops[1].color, RED);
+        assert_eq!(gradient.stops[2].color, BLUE);
+        assert_rgba(gradient.color_at(0.5f32), BLUE);
+
+        let before = linear_rgba(gradient.color_at(0.499f32));
+        let after = linear_rgba(gradient.color_at(0.501f32));
+        assert!(before.r > 0.99f32 && before.b < 0.01f32, "{:?}", before);
+        assert!(after.r < 0.01f32 && after.b > 0.99f32, "{:?}", after);
+    }
+
+    #[test]
+    fn offsets_follow_the_shape() {
+        let linear = Gradient::linear((10.0f32, 10.0f32), (10.0f32, 30.0f32), InterpolationSpace::LinearRgb);
+        assert_eq!(linear.offset_at(50.0f32, 10.0f32), 0.0f32);
+        assert_eq!(linear.offset_at(0.0f32, 20.0f32), 0.5f32);
+        assert_eq!(linear.offset_at(10.0f32, 40.0f32), 1.5f32);
+
+        let radial = Gradient::radial((10.0f32, 10.0f32), 10.0f32, InterpolationSpace::LinearRgb);
+        assert_eq!(radial.offset_at(10.0f32, 10.0f32), 0.0f32);
+        assert_eq!(radial.offset_at(16.0f32, 18.0f32), 1.0f32);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::color::*;
+use crate::LayoutRect;
+
+/// Space kept clear between the edge of an element and its children
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Padding {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32
+}
+
+impl Padding {
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Padding {
+        Padding {
+            left,
+            right,
+            top,
+            bottom
+        }
+    }
+
+    pub fn uniform(amount: u32) -> Padding {
+        Padding::new(amount, amount, amount, amount)
+    }
+
+    /// Shrinks rect by the padding. Never produces a negative size.
+    pub fn shrink(&self, rect: LayoutRect) -> LayoutRect {
+        let w = rect.w.saturating_sub(self.left + self.right);
+        let h = rect.h.saturating_sub(self.top + self.bottom);
+
+        LayoutRect::new(rect.x + self.left as i32, rect.y + self.top as i32, w, h)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Font {
+    pub family: String,
+    pub size: u32
+}
+
+impl Font {
+    pub fn new(family: &str, size: u32) -> Font {
+        Font {
+            family: family.to_owned(),
+            size
+        }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Font {
+        Font::new("sans-serif", 14)
+    }
+}
+
+/// The interaction state of an element. Each state can override parts of the element's style.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ElementState {
+    #[default]
+    Normal,
+    Hovered,
+    Pressed,
+    Focused,
+    Disabled
+}
+
+/// A set of style overrides. `None` means the theme's default, except for the font and text color,
+/// which are inherited from the parent element like they are in CSS.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    pub background: Option<Color>,
+    pub border_color: Option<Color>,
+    pub border_width: Option<u32>,
+    pub corner_radius: Option<f32>,
+    pub padding: Option<Padding>,
+    pub font: Option<Font>,
+    pub text_color: Option<Color>
+}
+
+impl Style {
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    pub fn with_background(mut self, c: Color) -> Style {
+        self.background = Some(c);
+
+        self
+    }
+
+    pub fn with_border(mut self, c: Color, width: u32) -> Style {
+        self.border_color = Some(c);
+        self.border_width = Some(width);
+
+        self
+    }
+
+    pub fn with_corner_radius(mut self, radius: f32) -> Style {
+        self.corner_radius = Some(radius);
+
+        self
+    }
+
+    pub fn with_padding(mut self, p: Padding) -> Style {
+        self.padding = Some(p);
+
+        self
+    }
+
+    pub fn with_font(mut self, f: Font) -> Style {
+        self.font = Some(f);
+
+        self
+    }
+
+    pub fn with_text_color(mut self, c: Color) -> Style {
+        self.text_color = Some(c);
+
+        self
+    }
+
+    /// Layers `other` on top of this style. Values set in `other` win.
+    pub fn merge(&mut self, other: &Style) {
+        if other.background.is_some() {
+            self.background = other.background;
+        }
+        if other.border_color.is_some() {
+            self.border_color = other.border_color;
+        }
+        if other.border_width.is_some() {
+            self.border_width = other.border_width;
+        }
+        if other.corner_radius.is_some() {
+            self.corner_radius = other.corner_radius;
+        }
+        if other.padding.is_some() {
+            self.padding = other.padding;
+        }
+        if other.font.is_some() {
+            self.font.clone_from(&other.font);
+        }
+        if other.text_color.is_some() {
+            self.text_color = other.text_color;
+        }
+    }
+}
+
+/// A style with every value filled in- what an element is actually drawn with
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComputedStyle {
+    pub background: Color,
+    pub border_color: Color,
+    pub border_width: u32,
+    pub corner_radius: f32,
+    pub padding: Padding,
+    pub font: Font,
+    pub text_color: Color
+}
+
+impl ComputedStyle {
+    /// What a child of an element with this style starts from: the inherited values (font and
+    /// text color) from this style, and the box values (background, border, padding...) from `defaults`
+    pub fn inherited(&self, defaults: &ComputedStyle) -> ComputedStyle {
+        ComputedStyle {
+            font: self.font.clone(),
+            text_color: self.text_color,
+            ..defaults.clone()
+        }
+    }
+
+    /// Applies the overrides in `style`, keeping this style's values for everything it leaves unset
+    pub fn apply(&self, style: &Style) -> ComputedStyle {
+        ComputedStyle {
+            background: style.background.unwrap_or(self.background),
+            border_color: style.border_color.unwrap_or(self.border_color),
+            border_width: style.border_width.unwrap_or(self.border_width),
+            corner_radius: style.corner_radius.unwrap_or(self.corner_radius),
+            padding: style.padding.unwrap_or(self.padding),
+            font: style.font.clone().unwrap_or_else(|| self.font.clone()),
+            text_color: style.text_color.unwrap_or(self.text_color)
+        }
+    }
+}
+
+/// The base style of an element plus the overrides for each interaction state
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StyleVariants {
+    pub normal: Style,
+    pub hovered: Style,
+    pub pressed: Style,
+    pub focused: Style,
+    pub disabled: Style
+}
+
+impl StyleVariants {
+    pub fn new(normal: Style) -> StyleVariants {
+        StyleVariants {
+            normal,
+            ..StyleVariants::default()
+        }
+    }
+
+    pub fn with_state(mut self, state: ElementState, style: Style) -> StyleVariants {
+        match state {
+            ElementState::Normal => self.normal = style,
+            ElementState::Hovered => self.hovered = style,
+            ElementState::Pressed => self.pressed = style,
+            ElementState::Focused => self.focused = style,
+            ElementState::Disabled => self.disabled = style
+        };
+
+        self
+    }
+
+    pub fn for_state_mut(&mut self, state: ElementState) -> &mut Style {
+        match state {
+            ElementState::Normal => &mut self.normal,
+            ElementState::Hovered => &mut self.hovered,
+            ElementState::Pressed => &mut self.pressed,
+            ElementState::Focused => &mut self.focused,
+            ElementState::Disabled => &mut self.disabled
+        }
+    }
+
+    pub fn for_state(&self, state: ElementState) -> &Style {
+        match state {
+            ElementState::Normal => &self.normal,
+            ElementState::Hovered => &self.hovered,
+            ElementState::Pressed => &self.pressed,
+            ElementState::Focused => &self.focused,
+            ElementState::Disabled => &self.disabled
+        }
+    }
+}
+
+/// Picks out elements by widget type, class names and state, like `button.primary:hover`.
+/// Parts that are `None`/empty match anything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selector {
+    pub widget_type: Option<String>,
+    pub classes: Vec<String>,
+    pub state: Option<ElementState>
+}
+
+impl Selector {
+    pub fn is_universal(&self) -> bool {
+        self.widget_type.is_none() && self.classes.is_empty()
+    }
+
+    //State isn't checked here- it picks which variant the rule's style goes into
+    pub fn matches(&self, widget_type: Option<&str>, classes: &[String]) -> bool {
+        let type_matches = match &self.widget_type {
+            Some(t) => widget_type == Some(t.as_str()),
+            None => true
+        };
+
+        type_matches && self.classes.iter().all(|c| classes.contains(c))
+    }
+
+    /// (class count, has widget type). Rules with higher specificity win; ties go to the later rule.
+    pub fn specificity(&self) -> (usize, usize) {
+        (self.classes.len(), if self.widget_type.is_some() { 1 } else { 0 })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub style: Style
+}
+
+/// The defaults every element starts from, plus theme-wide state overrides.
+/// Swapping the theme on a `GuiContext` restyles everything that doesn't override a value itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub defaults: ComputedStyle,
+    pub states: StyleVariants,
+    pub rules: Vec<StyleRule>
+}
+
+impl Theme {
+    pub fn light() -> Theme {
+        let defaults = ComputedStyle {
+            background: Color::SRGB(Srgb::new(232, 230, 224)),
+            border_color: Color::SRGB(Srgb::new(196, 193, 184)),
+            border_width: 1,
+            corner_radius: 0.0f32,
+            padding: Padding::default(),
+            font: Font::default(),
+            text_color: Color::SRGB(Srgb::new(32, 32, 32))
+        };
+
+        let states = StyleVariants::default()
+            .with_state(ElementState::Hovered, Style::new().with_background(Color::SRGB(Srgb::new(242, 241, 236))))
+            .with_state(ElementState::Pressed, Style::new().with_background(Color::SRGB(Srgb::new(212, 209, 200))))
+            .with_state(ElementState::Focused, Style::new().with_border(Color::SRGB(Srgb::new(38, 110, 212)), 2))
+            .with_state(ElementState::Disabled, Style::new().with_text_color(Color::SRGB(Srgb::new(150, 148, 142))));
+
+        Theme {
+            name: String::from("light"),
+            defaults,
+            states,
+            rules: Vec::new()
+        }
+    }
+
+    pub fn dark() -> Theme {
+        let defaults = ComputedStyle {
+            background: Color::SRGB(Srgb::new(36, 37, 40)),
+            border_color: Color::SRGB(Srgb::new(70, 72, 78)),
+            border_width: 1,
+            corner_radius: 0.0f32,
+            padding: Padding::default(),
+            font: Font::default(),
+            text_color: Color::SRGB(Srgb::new(226, 226, 228))
+        };
+
+        let states = StyleVariants::default()
+            .with_state(ElementState::Hovered, Style::new().with_background(Color::SRGB(Srgb::new(50, 52, 56))))
+            .with_state(ElementState::Pressed, Style::new().with_background(Color::SRGB(Srgb::new(26, 27, 29))))
+            .with_state(ElementState::Focused, Style::new().with_border(Color::SRGB(Srgb::new(94, 156, 255)), 2))
+            .with_state(ElementState::Disabled, Style::new().with_text_color(Color::SRGB(Srgb::new(110, 111, 116))));
+
+        Theme {
+            name: String::from("dark"),
+            defaults,
+            states,
+            rules: Vec::new()
+        }
+    }
+}
+
+impl Theme {
+    /// Resolves the style of an element in `state` whose parent resolved to `parent`.
+    /// Precedence, lowest first: the theme defaults and inherited values, matching theme rules, the element's normal style,
+    /// the theme's override for the state, matching theme rules for the state, the element's override for the state.
+    pub fn compute_style(&self, element: &StyleVariants, widget_type: Option<&str>, classes: &[String],
+                         parent: &ComputedStyle, state: ElementState) -> ComputedStyle {
+        let mut matched: Vec<&StyleRule> = self.rules.iter()
+            .filter(|rule| rule.selector.matches(widget_type, classes))
+            .collect();
+        matched.sort_by_key(|rule| rule.selector.specificity());
+
+        let mut style = Style::new();
+        for rule in matched.iter().filter(|rule| rule.selector.state.unwrap_or_default() == ElementState::Normal) {
+            style.merge(&rule.style);
+        }
+        style.merge(&element.normal);
+
+        if state != ElementState::Normal {
+            style.merge(self.states.for_state(state));
+            for rule in matched.iter().filter(|rule| rule.selector.state == Some(state)) {
+                style.merge(&rule.style);
+            }
+            style.merge(element.for_state(state));
+        }
+
+        parent.inherited(&self.defaults).apply(&style)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::light()
+    }
+}
@@ -0,0 +1,570 @@
+use crate::color::*;
+use crate::{ElementState, Font, Padding, Selector, Style, StyleRule, Theme};
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/*  A small CSS-like format for themes:
+ *
+ *      /* Universal rules set the theme defaults */
+ *      * { background: #e8e6e0; text-color: rgb(32, 32, 32); }
+ *      *:hover { background: hsl(45 10% 90%); }
+ *
+ *      button { padding: 4 8; corner-radius: 3; border: #c4c1b8 1; }
+ *      button.primary:pressed, .danger { background: lab(40, 50, 30); }
+ *
+ *  Selectors are `*`, or an optional widget type followed by any number of `.class`es,
+ *  with an optional `:state` (hover, pressed, focus, disabled) at the end.
+ *  Colors take anything `parse_color` does.
+ */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StylesheetError {
+    Io(String),
+    Parse { line: usize, column: usize, message: String }
+}
+
+impl fmt::Display for StylesheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StylesheetError::Io(message) => write!(f, "{}", message),
+            StylesheetError::Parse { line, column, message } => write!(f, "{}:{}: {}", line, column, message)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stylesheet {
+    pub rules: Vec<StyleRule>
+}
+
+impl Stylesheet {
+    pub fn parse(text: &str) -> Result<Stylesheet, StylesheetError> {
+        let mut parser = Parser::new(text);
+        let mut rules = Vec::new();
+
+        loop {
+            parser.skip_trivia()?;
+            if parser.peek().is_none() {
+                break;
+            }
+
+            let selectors = parser.parse_selectors()?;
+            let style = parser.parse_block()?;
+            for selector in selectors {
+                rules.push(StyleRule {
+                    selector,
+                    style: style.clone()
+                });
+            }
+        }
+
+        Ok(Stylesheet { rules })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Stylesheet, StylesheetError> {
+        let text = fs::read_to_string(path.as_ref())
+            .map_err(|e| StylesheetError::Io(format!("Couldn't read {}: {}", path.as_ref().display(), e)))?;
+
+        Stylesheet::parse(&text)
+    }
+
+    /// Builds a theme on top of `base`. Universal rules replace the base defaults and state overrides,
+    /// everything else becomes a rule of the theme.
+    pub fn to_theme(&self, name: &str, base: &Theme) -> Theme {
+        let mut theme = base.clone();
+        theme.name = name.to_owned();
+
+        for rule in &self.rules {
+            if rule.selector.is_universal() {
+                match rule.selector.state {
+                    None | Some(ElementState::Normal) => {
+                        theme.defaults = theme.defaults.apply(&rule.style);
+                    },
+                    Some(state) => {
+                        theme.states.for_state_mut(state).merge(&rule.style);
+                    }
+                }
+            } else {
+                theme.rules.push(rule.clone());
+            }
+        }
+
+        theme
+    }
+}
+
+/// Polls a stylesheet file for changes, for reloading a theme while it's being edited
+pub struct StylesheetWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    reported_missing: bool
+}
+
+impl StylesheetWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> StylesheetWatcher {
+        StylesheetWatcher {
+            path: path.as_ref().to_path_buf(),
+            last_modified: None,
+            reported_missing: false
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the freshly parsed stylesheet if the file changed since the last poll (or on the first poll),
+    /// `None` otherwise. A file that can't be read is an error once, then `None` until it's back.
+    pub fn poll(&mut self) -> Option<Result<Stylesheet, StylesheetError>> {
+        let modified = match fs::metadata(&self.path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(e) => {
+                //Only report a missing file once, rather than on every poll
+                self.last_modified = None;
+                if !self.reported_missing {
+                    self.reported_missing = true;
+                    return Some(Err(StylesheetError::Io(format!("Couldn't read {}: {}", self.path.display(), e))));
+                }
+                return None;
+            }
+        };
+        self.reported_missing = false;
+
+        if self.last_modified == Some(modified) {
+            return None;
+        }
+
+        self.last_modified = Some(modified);
+        Some(Stylesheet::load(&self.path))
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize
+}
+
+impl Parser {
+    fn new(text: &str) -> Parser {
+        Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, StylesheetError> {
+        Err(StylesheetError::Parse {
+            line: self.line,
+            column: self.column,
+            message: message.to_owned()
+        })
+    }
+
+    //Whitespace and /* comments */
+    fn skip_trivia(&mut self) -> Result<(), StylesheetError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.next();
+                },
+                Some('/') if self.peek_at(1) == Some('*') => {
+                    self.skip_comment()?;
+                },
+                _ => return Ok(())
+            }
+        }
+    }
+
+    //Skips the comment starting here. Returns it blanked out- spaces, keeping the line breaks.
+    fn skip_comment(&mut self) -> Result<String, StylesheetError> {
+        let (line, column) = (self.line, self.column);
+        let mut blank = String::from("  ");
+        self.next();
+        self.next();
+        loop {
+            match self.next() {
+                Some('*') if self.peek() == Some('/') => {
+                    self.next();
+                    blank.push_str("  ");
+                    return Ok(blank);
+                },
+                Some('\n') => blank.push('\n'),
+                Some(_) => blank.push(' '),
+                None => {
+                    return Err(StylesheetError::Parse {
+                        line,
+                        column,
+                        message: String::from("Unterminated comment")
+                    });
+                }
+            }
+        }
+    }
+
+    fn parse_ident(&mut self) -> Result<String, StylesheetError> {
+        let mut ident = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_') {
+            ident.push(c);
+            self.next();
+        }
+
+        if ident.is_empty() {
+            match self.peek() {
+                Some(c) => self.error(&format!("Expected a name, found '{}'", c)),
+                None => self.error("Expected a name, found end of file")
+            }
+        } else {
+            Ok(ident)
+        }
+    }
+
+    fn parse_selectors(&mut self) -> Result<Vec<Selector>, StylesheetError> {
+        let mut selectors = Vec::new();
+
+        loop {
+            self.skip_trivia()?;
+            selectors.push(self.parse_selector()?);
+            self.skip_trivia()?;
+
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                },
+                Some('{') => return Ok(selectors),
+                Some(c) => return self.error(&format!("Expected ',' or '{{' after selector, found '{}'", c)),
+                None => return self.error("Expected '{' after selector, found end of file")
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, StylesheetError> {
+        let mut selector = Selector::default();
+
+        if self.peek() == Some('*') {
+            self.next();
+        } else if self.peek() != Some('.') && self.peek() != Some(':') {
+            selector.widget_type = Some(self.parse_ident()?);
+        }
+
+        while self.peek() == Some('.') {
+            self.next();
+            selector.classes.push(self.parse_ident()?);
+        }
+
+        if self.peek() == Some(':') {
+            self.next();
+            let (line, column) = (self.line, self.column);
+            let state = self.parse_ident()?;
+            selector.state = Some(match state.as_str() {
+                "normal" => ElementState::Normal,
+                "hover" | "hovered" => ElementState::Hovered,
+                "active" | "pressed" => ElementState::Pressed,
+                "focus" | "focused" => ElementState::Focused,
+                "disabled" => ElementState::Disabled,
+                _ => {
+                    return Err(StylesheetError::Parse {
+                        line,
+                        column,
+                        message: format!("Unknown state '{}'", state)
+                    });
+                }
+            });
+        }
+
+        Ok(selector)
+    }
+
+    fn parse_block(&mut self) -> Result<Style, StylesheetError> {
+        //parse_selectors stops on the opening brace
+        self.next();
+        let mut style = Style::new();
+
+        loop {
+            self.skip_trivia()?;
+            match self.peek() {
+                Some('}') => {
+                    self.next();
+                    return Ok(style);
+                },
+                None => return self.error("Expected '}', found end of file"),
+                _ => {}
+            }
+
+            let (name_line, name_column) = (self.line, self.column);
+            let name = self.parse_ident()?;
+            self.skip_trivia()?;
+            if self.peek() != Some(':') {
+                return self.error(&format!("Expected ':' after '{}'", name));
+            }
+            self.next();
+            self.skip_trivia()?;
+
+            let (value_line, value_column) = (self.line, self.column);
+            //Comments in a value are blanked rather than removed, so error columns still line up
+            let mut value = String::new();
+            loop {
+                match self.peek() {
+                    None | Some(';') | Some('}') => break,
+                    Some('/') if self.peek_at(1) == Some('*') => {
+                        value.push_str(&self.skip_comment()?);
+                    },
+                    Some(c) => {
+                        value.push(c);
+                        self.next();
+                    }
+                }
+            }
+            if self.peek() == Some(';') {
+                self.next();
+            }
+
+            let value = Value {
+                text: value.trim_end().to_owned(),
+                line: value_line,
+                column: value_column
+            };
+
+            match name.as_str() {
+                "background" => style.background = Some(value.color()?),
+                "border-color" => style.border_color = Some(value.color()?),
+                "border-width" => style.border_width = Some(value.number::<u32>()?),
+                "border" => {
+                    //border: <color> <width>
+                    let (split, space) = value.text.char_indices().rev().find(|(_, c)| c.is_whitespace())
+                        .ok_or_else(|| value.error(0, "Expected a color and a width"))?;
+                    let color = Value { text: value.text[..split].trim_end().to_owned(), ..value.clone() };
+                    let width = value.slice(split + space.len_utf8());
+                    style.border_color = Some(color.color()?);
+                    style.border_width = Some(width.number::<u32>()?);
+                },
+                "corner-radius" => style.corner_radius = Some(value.number::<f32>()?),
+                "padding" => style.padding = Some(value.padding()?),
+                "font" => style.font = Some(value.font()?),
+                "text-color" | "color" => style.text_color = Some(value.color()?),
+                _ => {
+                    return Err(StylesheetError::Parse {
+                        line: name_line,
+                        column: name_column,
+                        message: format!("Unknown property '{}'", name)
+                    });
+                }
+            }
+        }
+    }
+}
+
+//The text of a property value, and where it starts in the file
+#[derive(Clone, Debug)]
+struct Value {
+    text: String,
+    line: usize,
+    column: usize
+}
+
+impl Value {
+    //Where a byte offset into the value text is in the file
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let mut line = self.line;
+        let mut column = self.column;
+        for c in self.text[..offset.min(self.text.len())].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        (line, column)
+    }
+
+    fn error(&self, offset: usize, message: &str) -> StylesheetError {
+        let (line, column) = self.position(offset);
+        StylesheetError::Parse {
+            line,
+            column,
+            message: message.to_owned()
+        }
+    }
+
+    //The part of the value from a byte offset on
+    fn slice(&self, offset: usize) -> Value {
+        let skipped = self.text[offset..].len() - self.text[offset..].trim_start().len();
+        let (line, column) = self.position(offset + skipped);
+        Value {
+            text: self.text[offset + skipped..].to_owned(),
+            line,
+            column
+        }
+    }
+
+    fn color(&self) -> Result<Color, StylesheetError> {
+        parse_color(&self.text).map_err(|e| {
+            let offset = self.text.char_indices().nth(e.column - 1).map(|(i, _)| i).unwrap_or(self.text.len());
+            self.error(offset, &e.message)
+        })
+    }
+
+    fn number<T: std::str::FromStr>(&self) -> Result<T, StylesheetError> {
+        self.text.parse::<T>().map_err(|_| self.error(0, &format!("Expected a number, found '{}'", self.text)))
+    }
+
+    //Same shorthand as CSS: all, vertical horizontal, or top right bottom left
+    fn padding(&self) -> Result<Padding, StylesheetError> {
+        let mut amounts = Vec::new();
+        for (offset, word) in words(&self.text) {
+            let amount = word.parse::<u32>()
+                .map_err(|_| self.error(offset, &format!("Expected a number, found '{}'", word)))?;
+            amounts.push(amount);
+        }
+
+        match amounts.len() {
+            1 => Ok(Padding::uniform(amounts[0])),
+            2 => Ok(Padding::new(amounts[1], amounts[1], amounts[0], amounts[0])),
+            4 => Ok(Padding::new(amounts[3], amounts[1], amounts[0], amounts[2])),
+            _ => Err(self.error(0, "padding takes 1, 2 or 4 numbers"))
+        }
+    }
+
+    //font: <family> [size], where the family can be quoted
+    fn font(&self) -> Result<Font, StylesheetError> {
+        let default = Font::default();
+        let (family, size) = match self.text.rsplit_once(char::is_whitespace) {
+            Some((family, size)) if size.parse::<u32>().is_ok() => (family.trim_end(), size.parse::<u32>().unwrap()),
+            _ => (self.text.as_str(), default.size)
+        };
+
+        let family = family.trim_matches(|c| c == '"' || c == '\'');
+        if family.is_empty() {
+            return Err(self.error(0, "Expected a font family"));
+        }
+
+        Ok(Font::new(family, size))
+    }
+}
+
+//Whitespace separated words with their byte offsets
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (idx, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(idx),
+            (true, Some(s)) => {
+                result.push((s, &text[s..idx]));
+                start = None;
+            },
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        result.push((s, &text[s..]));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(text: &str) -> (usize, usize, String) {
+        match Stylesheet::parse(text) {
+            Err(StylesheetError::Parse { line, column, message }) => (line, column, message),
+            other => panic!("{:?} parsed to {:?}", text, other)
+        }
+    }
+
+    fn position(text: &str) -> (usize, usize) {
+        let (line, column, _) = error_at(text);
+        (line, column)
+    }
+
+    #[test]
+    fn rules_parse_into_selectors_and_styles() {
+        let sheet = Stylesheet::parse("/* defaults */\n* { background: #e8e6e0; }\nbutton.primary:hover, .danger { padding: 4 8; border: red 2 }").unwrap();
+        assert_eq!(sheet.rules.len(), 3);
+
+        assert!(sheet.rules[0].selector.is_universal());
+        assert_eq!(sheet.rules[0].style.background, Some(Color::SRGB(Srgb::new(0xe8, 0xe6, 0xe0))));
+
+        let primary = &sheet.rules[1].selector;
+        assert_eq!(primary.widget_type.as_deref(), Some("button"));
+        assert_eq!(primary.classes, vec![String::from("primary")]);
+        assert_eq!(primary.state, Some(ElementState::Hovered));
+        assert_eq!(sheet.rules[1].style.padding, Some(Padding::new(8, 8, 4, 4)));
+        assert_eq!(sheet.rules[1].style.border_width, Some(2));
+        assert_eq!(sheet.rules[2].style, sheet.rules[1].style);
+    }
+
+    #[test]
+    fn comments_inside_values_are_ignored() {
+        let sheet = Stylesheet::parse("a { color: red /* brand; } */; border: /* ink */ blue /* width */ 3 }").unwrap();
+        assert_eq!(sheet.rules[0].style.text_color, Some(Color::SRGB(Srgb::new(255, 0, 0))));
+        assert_eq!(sheet.rules[0].style.border_color, Some(Color::SRGB(Srgb::new(0, 0, 255))));
+        assert_eq!(sheet.rules[0].style.border_width, Some(3));
+
+        //Positions after a comment still point into the file
+        assert_eq!(position("a { color: /* x */ nocolor; }"), (1, 20));
+        assert_eq!(position("a {\n  padding: /* y\n */ 1 x;\n}"), (3, 7));
+        assert_eq!(position("a {\n  color: red /* never closed }"), (2, 14));
+    }
+
+    #[test]
+    fn border_splits_on_any_whitespace() {
+        let sheet = Stylesheet::parse("a { border: red\u{a0}2 }").unwrap();
+        assert_eq!(sheet.rules[0].style.border_color, Some(Color::SRGB(Srgb::new(255, 0, 0))));
+        assert_eq!(sheet.rules[0].style.border_width, Some(2));
+
+        assert_eq!(position("a { border: red\u{a0}x }"), (1, 17));
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        assert_eq!(error_at("a { colour: red; }"), (1, 5, String::from("Unknown property 'colour'")));
+        assert_eq!(position("a:hovering { }"), (1, 3));
+        assert_eq!(position("a {\n  padding: 1 2 3;\n}"), (2, 12));
+        assert_eq!(position("a {\n  padding: 1 x;\n}"), (2, 14));
+        assert_eq!(position("\n\nb, { }"), (3, 4));
+        assert_eq!(position("a { color: red;"), (1, 16));
+        assert_eq!(position("a { color: red; }\n/* a {} "), (2, 1));
+    }
+
+    #[test]
+    fn a_missing_file_is_reported_on_the_first_poll() {
+        let mut watcher = StylesheetWatcher::new("no/such/stylesheet.css");
+        match watcher.poll() {
+            Some(Err(StylesheetError::Io(message))) => assert!(message.contains("stylesheet.css"), "{}", message),
+            other => panic!("{:?}", other)
+        }
+        assert_eq!(watcher.poll(), None);
+    }
+}